@@ -0,0 +1,274 @@
+//! Exporters rendering pages of cards for wikis, outliners and spreadsheets. They only
+//! format text, so they work the same outside the browser. Smart cards are left out, their
+//! links depend on the other cards.
+
+use quick_xml::escape::escape;
+
+use crate::{Anchor, Card, Group, Page};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Format {
+    /// A heading per page and card, links as (nested) lists.
+    Markdown,
+    /// An OPML 2.0 outline of pages, cards, groups and links.
+    Opml,
+    /// A row per link: page, card, label, URL and tags.
+    Csv,
+}
+
+impl Format {
+    pub const ALL: [Format; 3] = [Self::Markdown, Self::Opml, Self::Csv];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Markdown => "markdown",
+            Self::Opml => "opml",
+            Self::Csv => "csv",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Markdown => "Markdown",
+            Self::Opml => "OPML",
+            Self::Csv => "CSV",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Opml => "opml",
+            Self::Csv => "csv",
+        }
+    }
+
+    pub fn mime(self) -> &'static str {
+        match self {
+            Self::Markdown => "text/markdown",
+            Self::Opml => "text/x-opml",
+            Self::Csv => "text/csv",
+        }
+    }
+
+    pub fn render(self, pages: &[Page]) -> String {
+        match self {
+            Self::Markdown => markdown(pages),
+            Self::Opml => opml(pages),
+            Self::Csv => csv(pages),
+        }
+    }
+}
+
+/// The cards exported from `page`.
+fn cards(page: &Page) -> impl Iterator<Item = &Card> {
+    page.cards.iter().filter(|card| card.query.is_none())
+}
+
+/// Escapes the characters Markdown would read as formatting in a line of text.
+fn markdown_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\`*_[]<>#|".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn markdown_link(link: &Anchor) -> String {
+    // angle brackets allow spaces and parentheses in the destination
+    let url = link.url.replace('<', "%3C").replace('>', "%3E");
+    let mut line = format!("[{}](<{url}>)", markdown_text(&link.label));
+    if let Some(description) = &link.description {
+        line += &format!(" — {}", markdown_text(description));
+    }
+    for tag in &link.tags {
+        line += &format!(" `#{tag}`");
+    }
+    line
+}
+
+fn markdown_links(out: &mut String, links: &[Anchor], groups: &[Group], depth: usize) {
+    let indent = "  ".repeat(depth);
+    for link in links {
+        out.push_str(&format!("{indent}- {}\n", markdown_link(link)));
+    }
+    for group in groups {
+        out.push_str(&format!("{indent}- **{}**\n", markdown_text(&group.name)));
+        markdown_links(out, &group.links, &group.groups, depth + 1);
+    }
+}
+
+/// A Markdown document: a heading per page, and below it one per card.
+pub fn markdown(pages: &[Page]) -> String {
+    let mut out = String::new();
+    for page in pages {
+        out.push_str(&format!("# {}\n", markdown_text(&page.name)));
+        for card in cards(page) {
+            out.push_str(&format!("\n## {}\n\n", markdown_text(&card.name)));
+            markdown_links(&mut out, &card.links, &card.groups, 0);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn opml_links(out: &mut String, links: &[Anchor], groups: &[Group], depth: usize) {
+    let indent = "  ".repeat(depth);
+    for link in links {
+        out.push_str(&format!(
+            "{indent}<outline text=\"{}\" type=\"link\" url=\"{}\"",
+            escape(&link.label),
+            escape(&link.url)
+        ));
+        if let Some(description) = &link.description {
+            out.push_str(&format!(" description=\"{}\"", escape(description)));
+        }
+        if !link.tags.is_empty() {
+            // OPML categories are slash-delimited paths, separated by commas
+            let tags: Vec<String> = link.tags.iter().map(|tag| format!("/{tag}")).collect();
+            out.push_str(&format!(" category=\"{}\"", escape(tags.join(","))));
+        }
+        out.push_str("/>\n");
+    }
+    for group in groups {
+        out.push_str(&format!(
+            "{indent}<outline text=\"{}\">\n",
+            escape(&group.name)
+        ));
+        opml_links(out, &group.links, &group.groups, depth + 1);
+        out.push_str(&format!("{indent}</outline>\n"));
+    }
+}
+
+/// An OPML outline nesting links in groups, cards and pages.
+pub fn opml(pages: &[Page]) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opml version=\"2.0\">\n  \
+         <head>\n    <title>Foxhole</title>\n  </head>\n  <body>\n",
+    );
+    for page in pages {
+        out.push_str(&format!("    <outline text=\"{}\">\n", escape(&page.name)));
+        for card in cards(page) {
+            out.push_str(&format!(
+                "      <outline text=\"{}\">\n",
+                escape(&card.name)
+            ));
+            opml_links(&mut out, &card.links, &card.groups, 4);
+            out.push_str("      </outline>\n");
+        }
+        out.push_str("    </outline>\n");
+    }
+    out.push_str("  </body>\n</opml>\n");
+    out
+}
+
+/// A CSV field, quoted when needed. Text that a spreadsheet would run as a formula gets a
+/// leading `'`.
+fn csv_field(text: &str) -> String {
+    let text = if text.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{text}")
+    } else {
+        text.to_owned()
+    };
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+fn csv_links(out: &mut String, page: &str, card: &str, links: &[Anchor], groups: &[Group]) {
+    for link in links {
+        let row = [page, card, &link.label, &link.url, &link.tags.join(",")];
+        let row: Vec<String> = row.into_iter().map(csv_field).collect();
+        out.push_str(&row.join(","));
+        out.push_str("\r\n");
+    }
+    for group in groups {
+        let card = format!("{card} / {}", group.name);
+        csv_links(out, page, &card, &group.links, &group.groups);
+    }
+}
+
+/// A CSV table with a row per link. Links in groups name the group after the card, as in
+/// `Card / Group`.
+pub fn csv(pages: &[Page]) -> String {
+    let mut out = String::from("page,card,label,url,tags\r\n");
+    for page in pages {
+        for card in cards(page) {
+            csv_links(&mut out, &page.name, &card.name, &card.links, &card.groups);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pages() -> Vec<Page> {
+        let mut card = Card::new("Ops [prod]".to_owned());
+        card.links.push(Anchor {
+            tags: vec!["ops".to_owned(), "metrics".to_owned()],
+            description: Some("Dashboards & alerts".to_owned()),
+            ..Anchor::new(
+                "Grafana".to_owned(),
+                "https://grafana.example.com/d/a?x=1&y=(2)".to_owned(),
+            )
+        });
+        let mut group = Group::new("Logs".to_owned());
+        group.links.push(Anchor::new(
+            "=Kibana, \"new\"".to_owned(),
+            "https://kibana.example.com".to_owned(),
+        ));
+        card.groups.push(group);
+        let mut page = Page::new("Work".to_owned());
+        page.cards.push(card);
+        vec![page]
+    }
+
+    #[test]
+    fn exports_markdown() {
+        let markdown = markdown(&pages());
+        assert!(markdown.starts_with("# Work\n\n## Ops \\[prod\\]\n\n"));
+        assert!(markdown.contains(
+            "- [Grafana](<https://grafana.example.com/d/a?x=1&y=(2)>) — Dashboards & alerts \
+             `#ops` `#metrics`\n"
+        ));
+        assert!(markdown.contains("- **Logs**\n  - [=Kibana, \"new\"](<https://kibana"));
+    }
+
+    #[test]
+    fn exports_opml() {
+        let opml = opml(&pages());
+        assert!(opml.contains("<outline text=\"Ops [prod]\">"));
+        assert!(opml.contains("url=\"https://grafana.example.com/d/a?x=1&amp;y=(2)\""));
+        assert!(opml.contains("category=\"/ops,/metrics\""));
+        assert!(opml.contains("text=\"=Kibana, &quot;new&quot;\""));
+        let mut reader = quick_xml::Reader::from_str(&opml);
+        loop {
+            match reader.read_event().unwrap() {
+                quick_xml::events::Event::Eof => break,
+                _ => continue,
+            }
+        }
+    }
+
+    #[test]
+    fn exports_csv() {
+        let csv = csv(&pages());
+        let rows: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(
+            rows,
+            [
+                "page,card,label,url,tags",
+                "Work,Ops [prod],Grafana,https://grafana.example.com/d/a?x=1&y=(2),\"ops,metrics\"",
+                "Work,Ops [prod] / Logs,\"'=Kibana, \"\"new\"\"\",https://kibana.example.com,",
+                "",
+            ]
+        );
+    }
+}
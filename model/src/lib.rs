@@ -0,0 +1,459 @@
+//! The cards of Foxhole: pages of cards holding links, optionally in groups, smart cards
+//! running a [`query::Query`], and [`export`]s of them. Nothing here needs a browser, the
+//! app in the parent crate stores, replicates and shows them.
+
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use query::Query;
+
+pub mod export;
+pub mod query;
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Anchor {
+    pub label: String,
+    pub url: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// When the link was added, in milliseconds since the epoch.
+    #[serde(default)]
+    pub added: Option<f64>,
+    /// How many times the link was opened from Foxhole.
+    #[serde(default)]
+    pub clicks: u32,
+    /// Shown as tooltip and subtitle.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Free-form Markdown.
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// An emoji shown before the label.
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// Highlight color, see [`Anchor::parse_color`].
+    #[serde(default)]
+    pub color: Option<String>,
+    /// A user uploaded icon as a data URL.
+    #[serde(default)]
+    pub image: Option<String>,
+}
+
+impl Anchor {
+    /// Constructs a link with no tags.
+    pub fn new(label: String, url: String) -> Self {
+        Self {
+            label,
+            url,
+            tags: Vec::new(),
+            added: None,
+            clicks: 0,
+            description: None,
+            notes: None,
+            icon: None,
+            color: None,
+            image: None,
+        }
+    }
+
+    /// Uses the host of the URL as label when there is none.
+    pub fn with_default_label(mut self) -> Self {
+        if self.label.is_empty() {
+            self.label = self.host().trim_start_matches("www.").to_owned();
+        }
+        if self.label.is_empty() {
+            self.label = self.url.clone();
+        }
+        self
+    }
+
+    /// Largest uploaded icon accepted, in bytes of data URL.
+    pub const MAX_IMAGE_SIZE: usize = 64 * 1024;
+
+    /// The authority of the URL without credentials, e.g. `example.com:8080`.
+    fn authority(&self) -> &str {
+        let url = self.url.as_str();
+        let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+        let authority = without_scheme
+            .split(['/', '?', '#'])
+            .next()
+            .unwrap_or_default();
+        authority.rsplit('@').next().unwrap_or_default()
+    }
+
+    /// The host of the URL, without credentials or port.
+    pub fn host(&self) -> &str {
+        self.authority().split(':').next().unwrap_or_default()
+    }
+
+    /// `scheme://host[:port]` of http(s) links.
+    pub fn origin(&self) -> Option<String> {
+        let (scheme, _) = self.url.split_once("://")?;
+        let scheme = scheme.to_ascii_lowercase();
+        let authority = self.authority();
+        (matches!(scheme.as_str(), "http" | "https") && !authority.is_empty())
+            .then(|| format!("{scheme}://{}", authority.to_ascii_lowercase()))
+    }
+
+    /// Accepts `#rgb`/`#rrggbb` hex colors and CSS color names, so the value is safe to use
+    /// inside a `style` attribute.
+    pub fn parse_color(color: &str) -> Option<String> {
+        let color = color.trim();
+        let valid = match color.strip_prefix('#') {
+            Some(hex) => [3, 6].contains(&hex.len()) && hex.chars().all(|c| c.is_ascii_hexdigit()),
+            None => !color.is_empty() && color.chars().all(|c| c.is_ascii_alphabetic()),
+        };
+        valid.then(|| color.to_ascii_lowercase())
+    }
+
+    /// Parses a comma separated list of tags, ignoring empty and repeated ones.
+    pub fn parse_tags(tags: &str) -> Vec<String> {
+        let mut parsed: Vec<String> = Vec::new();
+        for tag in tags.split(',') {
+            let tag = tag.trim().trim_start_matches('#').trim();
+            if !tag.is_empty() && !parsed.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                parsed.push(tag.to_owned());
+            }
+        }
+        parsed
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    /// Folds a duplicate of this link into it: tags are joined, clicks added up, the
+    /// earliest added date kept and missing details taken from `other`.
+    pub fn merge(&mut self, other: &Anchor) {
+        for tag in &other.tags {
+            if !self.has_tag(tag) {
+                self.tags.push(tag.clone());
+            }
+        }
+        self.clicks += other.clicks;
+        self.added = match (self.added, other.added) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        let fill = |field: &mut Option<String>, other: &Option<String>| {
+            if field.is_none() {
+                field.clone_from(other);
+            }
+        };
+        fill(&mut self.description, &other.description);
+        fill(&mut self.notes, &other.notes);
+        fill(&mut self.icon, &other.icon);
+        fill(&mut self.color, &other.color);
+        fill(&mut self.image, &other.image);
+    }
+}
+
+/// How the links of a card are shown.
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum DisplayMode {
+    #[default]
+    List,
+    /// A list without descriptions and tags.
+    Compact,
+    /// Big clickable icons in a grid, like an app launcher.
+    Tiles,
+}
+
+impl DisplayMode {
+    pub const ALL: [Self; 3] = [Self::List, Self::Compact, Self::Tiles];
+
+    /// The mode after this one, wrapping around.
+    pub fn next(self) -> Self {
+        match self {
+            Self::List => Self::Compact,
+            Self::Compact => Self::Tiles,
+            Self::Tiles => Self::List,
+        }
+    }
+
+    /// CSS class of cards in this mode.
+    pub fn class(self) -> &'static str {
+        match self {
+            Self::List => "list",
+            Self::Compact => "compact-list",
+            Self::Tiles => "tiles",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::List => "☰ List",
+            Self::Compact => "≡ Compact",
+            Self::Tiles => "▦ Tiles",
+        }
+    }
+}
+
+impl FromStr for DisplayMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "list" => Ok(Self::List),
+            "compact" => Ok(Self::Compact),
+            "tiles" => Ok(Self::Tiles),
+            _ => Err(format!("Invalid display mode '{s}'")),
+        }
+    }
+}
+
+impl fmt::Display for DisplayMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::List => write!(f, "list"),
+            Self::Compact => write!(f, "compact"),
+            Self::Tiles => write!(f, "tiles"),
+        }
+    }
+}
+
+/// A titled section of links inside a card.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Group {
+    pub name: String,
+    pub links: Vec<Anchor>,
+    #[serde(default)]
+    pub groups: Vec<Group>,
+}
+
+impl Group {
+    /// Constructs a group with no links.
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            links: Vec::new(),
+            groups: Vec::new(),
+        }
+    }
+
+    /// The group at `path`, relative to this one.
+    pub fn group(&self, path: &[usize]) -> Option<&Group> {
+        match path.split_first() {
+            None => Some(self),
+            Some((first, rest)) => self.groups.get(*first)?.group(rest),
+        }
+    }
+
+    /// The mutable group at `path`, relative to this one.
+    pub fn group_mut(&mut self, path: &[usize]) -> Option<&mut Group> {
+        match path.split_first() {
+            None => Some(self),
+            Some((first, rest)) => self.groups.get_mut(*first)?.group_mut(rest),
+        }
+    }
+
+    /// Whether any link of the group or its subgroups has `tag`.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.links.iter().any(|link| link.has_tag(tag))
+            || self.groups.iter().any(|group| group.has_tag(tag))
+    }
+
+    /// How many links the group and its subgroups have.
+    pub fn link_count(&self) -> usize {
+        self.links.len() + self.groups.iter().map(Group::link_count).sum::<usize>()
+    }
+
+    fn retain_links(&mut self, keep: &mut impl FnMut(&Anchor) -> bool) {
+        self.links.retain(|link| keep(link));
+        for group in &mut self.groups {
+            group.retain_links(keep);
+        }
+    }
+
+    fn links_mut(&mut self, edit: &mut impl FnMut(&mut Anchor)) {
+        self.links.iter_mut().for_each(&mut *edit);
+        for group in &mut self.groups {
+            group.links_mut(edit);
+        }
+    }
+
+    fn walk_links<'a>(
+        &'a self,
+        path: &mut GroupPath,
+        out: &mut Vec<(GroupPath, LinkPos, &'a Anchor)>,
+    ) {
+        out.extend(
+            self.links
+                .iter()
+                .enumerate()
+                .map(|(pos, link)| (path.clone(), pos, link)),
+        );
+        for (i, group) in self.groups.iter().enumerate() {
+            path.push(i);
+            group.walk_links(path, out);
+            path.pop();
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Card {
+    pub name: String,
+    pub links: Vec<Anchor>,
+    /// Cards stored before groups existed simply have none.
+    #[serde(default)]
+    pub groups: Vec<Group>,
+    /// Only the name of a collapsed card is shown.
+    #[serde(default)]
+    pub collapsed: bool,
+    /// Pinned cards are shown before the others.
+    #[serde(default)]
+    pub pinned: bool,
+    /// Smart cards store no links, they show the results of this query.
+    #[serde(default)]
+    pub query: Option<Query>,
+    #[serde(default)]
+    pub display: DisplayMode,
+}
+
+impl Card {
+    /// How deep groups can be nested inside a card.
+    pub const MAX_GROUP_DEPTH: usize = 2;
+
+    /// Constructs a card with no links.
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            links: Vec::new(),
+            groups: Vec::new(),
+            collapsed: false,
+            pinned: false,
+            query: None,
+            display: DisplayMode::default(),
+        }
+    }
+
+    /// Constructs a smart card showing the results of `query`.
+    pub fn smart(name: String, query: Query) -> Self {
+        Self {
+            query: Some(query),
+            ..Self::new(name)
+        }
+    }
+
+    /// The group at `path`, an empty path is not a group.
+    pub fn group(&self, path: &[usize]) -> Option<&Group> {
+        let (first, rest) = path.split_first()?;
+        self.groups.get(*first)?.group(rest)
+    }
+
+    /// The mutable group at `path`, an empty path is not a group.
+    pub fn group_mut(&mut self, path: &[usize]) -> Option<&mut Group> {
+        let (first, rest) = path.split_first()?;
+        self.groups.get_mut(*first)?.group_mut(rest)
+    }
+
+    /// The links of the group at `path`, or of the card itself if the path is empty.
+    pub fn links_at(&self, path: &[usize]) -> Option<&Vec<Anchor>> {
+        match path {
+            [] => Some(&self.links),
+            path => self.group(path).map(|group| &group.links),
+        }
+    }
+
+    /// The mutable links of the group at `path`, or of the card itself if the path is empty.
+    pub fn links_at_mut(&mut self, path: &[usize]) -> Option<&mut Vec<Anchor>> {
+        match path {
+            [] => Some(&mut self.links),
+            path => self.group_mut(path).map(|group| &mut group.links),
+        }
+    }
+
+    /// Whether any link of the card, including the ones inside groups, has `tag`.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.links.iter().any(|link| link.has_tag(tag))
+            || self.groups.iter().any(|group| group.has_tag(tag))
+    }
+
+    /// Every link of the card, including the ones inside groups, with its position.
+    pub fn all_links(&self) -> Vec<(GroupPath, LinkPos, &Anchor)> {
+        let mut out = Vec::new();
+        out.extend(
+            self.links
+                .iter()
+                .enumerate()
+                .map(|(pos, link)| (Vec::new(), pos, link)),
+        );
+        for (i, group) in self.groups.iter().enumerate() {
+            group.walk_links(&mut vec![i], &mut out);
+        }
+        out
+    }
+
+    /// Keeps only the links of the card and its groups for which `keep` is true.
+    pub fn retain_links(&mut self, keep: &mut impl FnMut(&Anchor) -> bool) {
+        self.links.retain(|link| keep(link));
+        for group in &mut self.groups {
+            group.retain_links(keep);
+        }
+    }
+
+    /// Changes every link of the card and its groups.
+    pub fn links_mut(&mut self, edit: &mut impl FnMut(&mut Anchor)) {
+        self.links.iter_mut().for_each(&mut *edit);
+        for group in &mut self.groups {
+            group.links_mut(edit);
+        }
+    }
+
+    /// The subgroups of the group at `path`, or the top level groups if the path is empty.
+    pub fn groups_at_mut(&mut self, path: &[usize]) -> Option<&mut Vec<Group>> {
+        match path {
+            [] => Some(&mut self.groups),
+            path => self.group_mut(path).map(|group| &mut group.groups),
+        }
+    }
+}
+
+pub type CardId = usize;
+pub type LinkPos = usize;
+pub type PageId = usize;
+/// Position of a group inside a card, one index per nesting level. Empty means the card itself.
+pub type GroupPath = Vec<usize>;
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct LinkId {
+    pub card: CardId,
+    #[serde(default)]
+    pub group: GroupPath,
+    pub link: LinkPos,
+}
+
+/// A named group of cards, shown as a tab.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Page {
+    pub name: String,
+    pub cards: Vec<Card>,
+}
+
+impl Page {
+    /// Constructs a page with no cards.
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            cards: Vec::new(),
+        }
+    }
+}
+
+/// Every link of every page with its position.
+pub fn all_links(pages: &[Page]) -> Vec<(PageId, LinkId, &Anchor)> {
+    let mut out = Vec::new();
+    for (page, Page { cards, .. }) in pages.iter().enumerate() {
+        for (card, links) in cards.iter().enumerate() {
+            out.extend(
+                links
+                    .all_links()
+                    .into_iter()
+                    .map(|(group, link, anchor)| (page, LinkId { card, group, link }, anchor)),
+            );
+        }
+    }
+    out
+}
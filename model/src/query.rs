@@ -0,0 +1,170 @@
+use std::{cmp::Reverse, fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{all_links, Anchor, LinkId, Page, PageId};
+
+const DAY_MS: f64 = 24.0 * 60.0 * 60.0 * 1000.0;
+
+/// A saved search whose results are the links of a smart card.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub enum Query {
+    /// Links with a tag, `tag:oncall`.
+    Tag(String),
+    /// Links whose host (or whole URL if the pattern has a `/`) matches a glob,
+    /// `url:*.internal.example.com`.
+    Url(String),
+    /// The most clicked links, `top:10`.
+    MostClicked(usize),
+    /// Links added in the last days, `recent:7`.
+    Recent(u32),
+}
+
+impl FromStr for Query {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, arg) = s
+            .split_once(':')
+            .map(|(kind, arg)| (kind.trim(), arg.trim()))
+            .ok_or_else(|| format!("Invalid query '{s}'"))?;
+        let number = |arg: &str| {
+            arg.parse::<u32>()
+                .ok()
+                .filter(|n| *n > 0)
+                .ok_or_else(|| format!("Invalid number '{arg}'"))
+        };
+        match kind {
+            _ if arg.is_empty() => Err(format!("Missing argument in query '{s}'")),
+            "tag" => Ok(Self::Tag(arg.trim_start_matches('#').to_owned())),
+            "url" => Ok(Self::Url(arg.to_owned())),
+            "top" => Ok(Self::MostClicked(number(arg)? as usize)),
+            "recent" => Ok(Self::Recent(number(arg)?)),
+            _ => Err(format!("Unknown query kind '{kind}'")),
+        }
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tag(tag) => write!(f, "tag:{tag}"),
+            Self::Url(pattern) => write!(f, "url:{pattern}"),
+            Self::MostClicked(n) => write!(f, "top:{n}"),
+            Self::Recent(days) => write!(f, "recent:{days}"),
+        }
+    }
+}
+
+/// A link found by a query and where it is stored.
+#[derive(Clone, PartialEq)]
+pub struct QueryResult {
+    pub page: PageId,
+    pub link: LinkId,
+    pub anchor: Anchor,
+}
+
+impl Query {
+    /// Runs the query over the links of every regular card of `pages`, `now` is in
+    /// milliseconds since the epoch.
+    pub fn run(&self, pages: &[Page], now: f64) -> Vec<QueryResult> {
+        // smart cards store no links
+        let links = all_links(pages)
+            .into_iter()
+            .map(|(page, link, anchor)| QueryResult {
+                page,
+                link,
+                anchor: anchor.clone(),
+            });
+
+        match self {
+            Self::Tag(tag) => links.filter(|res| res.anchor.has_tag(tag)).collect(),
+            Self::Url(pattern) => links
+                .filter(|res| url_matches(pattern, &res.anchor))
+                .collect(),
+            Self::MostClicked(n) => {
+                let mut links: Vec<_> = links.filter(|res| res.anchor.clicks > 0).collect();
+                links.sort_by_key(|res| Reverse(res.anchor.clicks));
+                links.truncate(*n);
+                links
+            }
+            Self::Recent(days) => {
+                let since = now - f64::from(*days) * DAY_MS;
+                let mut links: Vec<_> = links
+                    .filter(|res| res.anchor.added.is_some_and(|added| added >= since))
+                    .collect();
+                links.sort_by(|a, b| b.anchor.added.partial_cmp(&a.anchor.added).unwrap());
+                links
+            }
+        }
+    }
+}
+
+/// Matches the host of the link against `pattern`, or the whole URL if the pattern has a `/`.
+fn url_matches(pattern: &str, anchor: &Anchor) -> bool {
+    let target = if pattern.contains('/') {
+        &anchor.url
+    } else {
+        anchor.host()
+    };
+    glob_matches(
+        &pattern.to_lowercase().chars().collect::<Vec<_>>(),
+        &target.to_lowercase().chars().collect::<Vec<_>>(),
+    )
+}
+
+/// Glob matching where `*` matches any sequence and `?` any single character. On a mismatch
+/// only the last `*` takes one more character, which is enough and keeps it O(n·m).
+fn glob_matches(pattern: &[char], text: &[char]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // the position of the last `*` and of the text it was matched up to
+    let mut star = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => (p, t) = (p + 1, t + 1),
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    star = Some((star_p, star_t + 1));
+                    (p, t) = (star_p + 1, star_t + 1);
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, text: &str) -> bool {
+        glob_matches(
+            &pattern.chars().collect::<Vec<_>>(),
+            &text.chars().collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn globs_match_whole_texts() {
+        assert!(matches("*.example.com", "wiki.example.com"));
+        assert!(matches("wiki.?xample.*", "wiki.example.com"));
+        assert!(matches("*", ""));
+        assert!(matches("a*b*c", "abxbc"));
+        assert!(!matches("*.example.com", "example.com"));
+        assert!(!matches("wiki", "wiki.example.com"));
+        assert!(!matches("a*b", "ab/c"));
+    }
+
+    #[test]
+    fn many_stars_match_quickly() {
+        let url = format!("https://{}.example.com", "a".repeat(5000));
+        let started = std::time::Instant::now();
+        assert!(!matches(&format!("{}b", "*a".repeat(20)), &url));
+        assert!(started.elapsed().as_secs() < 1);
+    }
+}
@@ -0,0 +1,279 @@
+//! A small server keeping one Foxhole document per user, so every device shows the same cards.
+//!
+//! Users are known by their token, sent as `Authorization: Bearer <token>`. The API is:
+//!
+//! - `GET /api/document` answers `{"revision": n, "document": ...}`, revision 0 and a `null`
+//!   document when nothing was stored yet.
+//! - `PUT /api/document` with `{"base_revision": n, "document": ...}` stores the document if
+//!   `n` is the current revision and answers like `GET` with the new revision. Otherwise it
+//!   answers `409 Conflict` with the current revision and document, for the client to merge.
+//!
+//! Documents are opaque JSON stored in a file per user.
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    io::Read,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tiny_http::{Header, Method, Request, Response};
+
+/// Largest document accepted, in bytes.
+pub const MAX_BODY: usize = 5 * 1024 * 1024;
+
+pub struct Config {
+    /// Token of every user, by user name. Names are used as file names so only letters,
+    /// digits, `-` and `_` are allowed.
+    pub users: HashMap<String, String>,
+    /// Where the documents are stored.
+    pub data_dir: PathBuf,
+    /// Value of `Access-Control-Allow-Origin`, the origin the app is served from.
+    pub allow_origin: String,
+}
+
+impl Config {
+    /// Checks the user names and tokens and creates the data directory.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut users: Vec<_> = self.users.iter().collect();
+        users.sort();
+        let mut owners: HashMap<&String, &String> = HashMap::new();
+        for (user, token) in users {
+            let valid_name = !user.is_empty()
+                && user
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !valid_name {
+                return Err(format!("Invalid user name '{user}'"));
+            }
+            if token.len() < 16 {
+                return Err(format!(
+                    "The token of '{user}' is shorter than 16 characters"
+                ));
+            }
+            // users are found by their token
+            if let Some(owner) = owners.insert(token, user) {
+                return Err(format!("'{owner}' and '{user}' have the same token"));
+            }
+        }
+        fs::create_dir_all(&self.data_dir)
+            .map_err(|err| format!("Can't create {}: {err}", self.data_dir.display()))
+    }
+}
+
+/// A document and its revision, as stored and as answered.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct Stored {
+    pub revision: u64,
+    pub document: Value,
+}
+
+/// The body of a `PUT`.
+#[derive(Deserialize)]
+struct Update {
+    base_revision: u64,
+    document: Value,
+}
+
+pub struct Server {
+    http: tiny_http::Server,
+    users: HashMap<String, String>,
+    data_dir: PathBuf,
+    allow_origin: String,
+}
+
+impl Server {
+    /// Listens on `addr`, port 0 picks a free one.
+    pub fn bind(addr: &str, config: Config) -> Result<Self, String> {
+        config.validate()?;
+        let http = tiny_http::Server::http(addr).map_err(|err| err.to_string())?;
+        let users = config
+            .users
+            .into_iter()
+            .map(|(user, token)| (token, user))
+            .collect();
+        Ok(Self {
+            http,
+            users,
+            data_dir: config.data_dir,
+            allow_origin: config.allow_origin,
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.http
+            .server_addr()
+            .to_ip()
+            .expect("listening on a TCP address")
+    }
+
+    /// Answers requests until [`Server::stop`] is called. Requests are handled one at a
+    /// time, so writes to a document never race.
+    pub fn run(&self) {
+        for request in self.http.incoming_requests() {
+            self.handle(request);
+        }
+    }
+
+    /// Makes [`Server::run`] return.
+    pub fn stop(&self) {
+        self.http.unblock();
+    }
+
+    fn handle(&self, mut request: Request) {
+        let (status, body) = match self.route(&mut request) {
+            Ok(stored) => (200, serde_json::to_string(&stored).unwrap()),
+            Err(Error::Conflict(stored)) => (409, serde_json::to_string(&stored).unwrap()),
+            Err(err) => (
+                err.status(),
+                serde_json::json!({ "error": err.to_string() }).to_string(),
+            ),
+        };
+        let response = Response::from_string(body)
+            .with_status_code(status)
+            .with_header(header("Content-Type", "application/json"));
+        let response = self.cors(response);
+        if let Err(err) = request.respond(response) {
+            eprintln!("Failed to answer: {err}");
+        }
+    }
+
+    fn route(&self, request: &mut Request) -> Result<Stored, Error> {
+        let path = request.url().split('?').next().unwrap_or_default();
+        if path != "/api/document" {
+            return Err(Error::NotFound);
+        }
+        match request.method() {
+            // CORS preflight, the headers are all it needs
+            Method::Options => Ok(Stored::default()),
+            Method::Get => self.load(self.user(request)?),
+            Method::Put => {
+                let user = self.user(request)?;
+                let update = read_update(request)?;
+                let stored = self.load(user)?;
+                if update.base_revision != stored.revision {
+                    return Err(Error::Conflict(stored));
+                }
+                let stored = Stored {
+                    revision: stored.revision + 1,
+                    document: update.document,
+                };
+                self.save(user, &stored)?;
+                Ok(stored)
+            }
+            _ => Err(Error::MethodNotAllowed),
+        }
+    }
+
+    /// The user whose token is in the request.
+    fn user(&self, request: &Request) -> Result<&str, Error> {
+        let token = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("Authorization"))
+            .and_then(|h| h.value.as_str().strip_prefix("Bearer "))
+            .ok_or(Error::Unauthorized)?;
+        self.users
+            .get(token.trim())
+            .map(String::as_str)
+            .ok_or(Error::Unauthorized)
+    }
+
+    fn path(&self, user: &str) -> PathBuf {
+        self.data_dir.join(format!("{user}.json"))
+    }
+
+    fn load(&self, user: &str) -> Result<Stored, Error> {
+        match fs::read_to_string(self.path(user)) {
+            Ok(json) => serde_json::from_str(&json).map_err(|err| Error::Storage(err.to_string())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Stored::default()),
+            Err(err) => Err(Error::Storage(err.to_string())),
+        }
+    }
+
+    /// Writes to a temporary file first, a crash never leaves half a document.
+    fn save(&self, user: &str, stored: &Stored) -> Result<(), Error> {
+        let path = self.path(user);
+        let tmp = path.with_extension("json.tmp");
+        write_then_rename(&tmp, &path, &serde_json::to_vec(stored).unwrap())
+            .map_err(|err| Error::Storage(err.to_string()))
+    }
+
+    fn cors<R: Read>(&self, response: Response<R>) -> Response<R> {
+        response
+            .with_header(header("Access-Control-Allow-Origin", &self.allow_origin))
+            .with_header(header("Access-Control-Allow-Methods", "GET, PUT, OPTIONS"))
+            .with_header(header(
+                "Access-Control-Allow-Headers",
+                "Authorization, Content-Type",
+            ))
+    }
+}
+
+fn header(field: &str, value: &str) -> Header {
+    Header::from_bytes(field.as_bytes(), value.as_bytes()).unwrap()
+}
+
+fn read_update(request: &mut Request) -> Result<Update, Error> {
+    if request.body_length().is_some_and(|len| len > MAX_BODY) {
+        return Err(Error::TooLarge);
+    }
+    let mut body = Vec::new();
+    request
+        .as_reader()
+        .take(MAX_BODY as u64 + 1)
+        .read_to_end(&mut body)
+        .map_err(|err| Error::BadRequest(err.to_string()))?;
+    if body.len() > MAX_BODY {
+        return Err(Error::TooLarge);
+    }
+    serde_json::from_slice(&body).map_err(|err| Error::BadRequest(err.to_string()))
+}
+
+fn write_then_rename(tmp: &Path, path: &Path, contents: &[u8]) -> io::Result<()> {
+    fs::write(tmp, contents)?;
+    fs::rename(tmp, path)
+}
+
+#[derive(Debug)]
+enum Error {
+    Unauthorized,
+    NotFound,
+    MethodNotAllowed,
+    BadRequest(String),
+    TooLarge,
+    /// The update was based on an older revision than this one.
+    Conflict(Stored),
+    Storage(String),
+}
+
+impl Error {
+    fn status(&self) -> u16 {
+        match self {
+            Self::Unauthorized => 401,
+            Self::NotFound => 404,
+            Self::MethodNotAllowed => 405,
+            Self::BadRequest(_) => 400,
+            Self::TooLarge => 413,
+            Self::Conflict(_) => 409,
+            Self::Storage(_) => 500,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unauthorized => write!(f, "Missing or unknown token"),
+            Self::NotFound => write!(f, "Not found"),
+            Self::MethodNotAllowed => write!(f, "Method not allowed"),
+            Self::BadRequest(reason) => write!(f, "Bad request: {reason}"),
+            Self::TooLarge => write!(f, "The document is larger than {MAX_BODY} bytes"),
+            Self::Conflict(stored) => write!(f, "The document is at revision {}", stored.revision),
+            Self::Storage(reason) => write!(f, "Storage error: {reason}"),
+        }
+    }
+}
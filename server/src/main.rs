@@ -0,0 +1,64 @@
+//! Runs the Foxhole sync server.
+//!
+//! ```text
+//! foxhole-server --tokens tokens.json [--addr 127.0.0.1:8080] [--data ./data] [--allow-origin '*']
+//! ```
+//!
+//! `tokens.json` maps user names to their secret token, `{"alice": "…", "bob": "…"}`.
+
+use std::{collections::HashMap, env, fs, path::PathBuf, process};
+
+use foxhole_server::{Config, Server};
+
+const USAGE: &str = "Usage: foxhole-server --tokens <file> [--addr <host:port>] [--data <dir>] \
+                     [--allow-origin <origin>]";
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("{err}");
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut addr = "127.0.0.1:8080".to_owned();
+    let mut data_dir = PathBuf::from("data");
+    let mut tokens = None;
+    let mut allow_origin = "*".to_owned();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("{arg} needs a value\n{USAGE}"))
+        };
+        match arg.as_str() {
+            "--addr" => addr = value()?,
+            "--data" => data_dir = value()?.into(),
+            "--tokens" => tokens = Some(value()?),
+            "--allow-origin" => allow_origin = value()?,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            _ => return Err(format!("Unknown argument {arg}\n{USAGE}")),
+        }
+    }
+
+    let tokens = tokens.ok_or_else(|| format!("--tokens is required\n{USAGE}"))?;
+    let json = fs::read_to_string(&tokens).map_err(|err| format!("Can't read {tokens}: {err}"))?;
+    let users: HashMap<String, String> =
+        serde_json::from_str(&json).map_err(|err| format!("Invalid {tokens}: {err}"))?;
+
+    let server = Server::bind(
+        &addr,
+        Config {
+            users,
+            data_dir,
+            allow_origin,
+        },
+    )?;
+    println!("Listening on http://{}", server.addr());
+    server.run();
+    Ok(())
+}
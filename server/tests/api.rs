@@ -0,0 +1,208 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+use foxhole_server::{Config, Server};
+use serde_json::{json, Value};
+
+const ALICE: &str = "alice-token-0123456789";
+const BOB: &str = "bob-token-0123456789";
+
+/// A server on a free localhost port, stopped when dropped.
+struct Running {
+    server: Arc<Server>,
+    thread: Option<JoinHandle<()>>,
+    url: String,
+}
+
+impl Running {
+    fn start(data_dir: &Path) -> Self {
+        let users = HashMap::from([
+            ("alice".to_owned(), ALICE.to_owned()),
+            ("bob".to_owned(), BOB.to_owned()),
+        ]);
+        let config = Config {
+            users,
+            data_dir: data_dir.to_owned(),
+            allow_origin: "https://foxhole.example".to_owned(),
+        };
+        let server = Arc::new(Server::bind("127.0.0.1:0", config).unwrap());
+        let url = format!("http://{}/api/document", server.addr());
+        let thread = thread::spawn({
+            let server = server.clone();
+            move || server.run()
+        });
+        Self {
+            server,
+            thread: Some(thread),
+            url,
+        }
+    }
+
+    /// The status and body of a `GET`.
+    fn get(&self, token: &str) -> (u16, Value) {
+        answer(
+            ureq::get(&self.url)
+                .set("Authorization", &format!("Bearer {token}"))
+                .call(),
+        )
+    }
+
+    fn put(&self, token: &str, base: u64, document: Value) -> (u16, Value) {
+        answer(
+            ureq::put(&self.url)
+                .set("Authorization", &format!("Bearer {token}"))
+                .send_json(json!({ "base_revision": base, "document": document })),
+        )
+    }
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        self.server.stop();
+        if let Some(thread) = self.thread.take() {
+            thread.join().unwrap();
+        }
+    }
+}
+
+/// A fresh data directory for each test.
+fn data_dir() -> PathBuf {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    let dir = std::env::temp_dir().join(format!(
+        "foxhole-server-{}-{}",
+        std::process::id(),
+        COUNT.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::remove_dir_all(&dir).ok();
+    dir
+}
+
+fn answer(result: Result<ureq::Response, ureq::Error>) -> (u16, Value) {
+    match result {
+        Ok(response) => (response.status(), response.into_json().unwrap()),
+        Err(ureq::Error::Status(code, response)) => (code, response.into_json().unwrap()),
+        Err(err) => panic!("{err}"),
+    }
+}
+
+#[test]
+fn rejects_missing_and_unknown_tokens() {
+    let server = Running::start(&data_dir());
+    assert_eq!(answer(ureq::get(&server.url).call()).0, 401);
+    assert_eq!(server.get("nope").0, 401);
+    assert_eq!(server.put("nope", 0, json!({})).0, 401);
+}
+
+#[test]
+fn rejects_users_sharing_a_token() {
+    let users = HashMap::from([
+        ("alice".to_owned(), ALICE.to_owned()),
+        ("bob".to_owned(), ALICE.to_owned()),
+    ]);
+    let config = Config {
+        users,
+        data_dir: data_dir(),
+        allow_origin: "https://foxhole.example".to_owned(),
+    };
+    assert_eq!(
+        config.validate(),
+        Err("'alice' and 'bob' have the same token".to_owned())
+    );
+    assert!(Server::bind("127.0.0.1:0", config).is_err());
+}
+
+#[test]
+fn empty_document_has_revision_zero() {
+    let server = Running::start(&data_dir());
+    let body = json!({ "revision": 0, "document": null });
+    assert_eq!(server.get(ALICE), (200, body));
+}
+
+#[test]
+fn put_then_get() {
+    let server = Running::start(&data_dir());
+    let saved = server.put(ALICE, 0, json!({ "pages": [1] }));
+    let body = json!({ "revision": 1, "document": { "pages": [1] } });
+    assert_eq!(saved, (200, body));
+    assert_eq!(server.get(ALICE), saved);
+
+    let (code, body) = server.put(ALICE, 1, json!({ "pages": [2] }));
+    assert_eq!(code, 200);
+    assert_eq!(body["revision"], 2);
+}
+
+#[test]
+fn stale_put_conflicts() {
+    let server = Running::start(&data_dir());
+    server.put(ALICE, 0, json!("first"));
+    server.put(ALICE, 1, json!("second"));
+
+    let current = json!({ "revision": 2, "document": "second" });
+    assert_eq!(server.put(ALICE, 1, json!("stale")), (409, current.clone()));
+    assert_eq!(server.get(ALICE), (200, current));
+}
+
+#[test]
+fn users_are_isolated() {
+    let server = Running::start(&data_dir());
+    server.put(ALICE, 0, json!("alice's"));
+    assert_eq!(server.get(BOB).1["revision"], 0);
+
+    server.put(BOB, 0, json!("bob's"));
+    assert_eq!(server.get(ALICE).1["document"], "alice's");
+    assert_eq!(server.get(BOB).1["document"], "bob's");
+}
+
+#[test]
+fn documents_survive_a_restart() {
+    let dir = data_dir();
+    {
+        let server = Running::start(&dir);
+        server.put(ALICE, 0, json!({ "kept": true }));
+    }
+    let server = Running::start(&dir);
+    let body = json!({ "revision": 1, "document": { "kept": true } });
+    assert_eq!(server.get(ALICE), (200, body));
+}
+
+#[test]
+fn rejects_invalid_bodies() {
+    let server = Running::start(&data_dir());
+    let response = ureq::put(&server.url)
+        .set("Authorization", &format!("Bearer {ALICE}"))
+        .send_string("{ not json");
+    assert_eq!(answer(response).0, 400);
+
+    let huge = "x".repeat(foxhole_server::MAX_BODY + 1);
+    assert_eq!(server.put(ALICE, 0, json!(huge)).0, 413);
+    assert_eq!(server.get(ALICE).1["revision"], 0);
+}
+
+#[test]
+fn answers_cors_preflight() {
+    let server = Running::start(&data_dir());
+    let response = ureq::request("OPTIONS", &server.url).call().unwrap();
+    assert_eq!(
+        response.header("Access-Control-Allow-Origin"),
+        Some("https://foxhole.example")
+    );
+    let headers = response.header("Access-Control-Allow-Headers").unwrap();
+    assert!(headers.contains("Authorization"));
+}
+
+#[test]
+fn unknown_paths_are_not_found() {
+    let server = Running::start(&data_dir());
+    let response = ureq::get(&server.url.replace("document", "other"))
+        .set("Authorization", &format!("Bearer {ALICE}"))
+        .call();
+    assert_eq!(answer(response).0, 404);
+}
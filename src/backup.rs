@@ -0,0 +1,137 @@
+//! Backups of the cards to a WebDAV folder, on demand or on a schedule.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::cards::cards_ctx::{Card, Page},
+    http::Fetch,
+    storage, urls,
+    webdav::{Entry, Folder},
+};
+
+/// Where and how often to back up, stored under `"backup"`.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupConfig {
+    /// The WebDAV folder, e.g. `https://cloud.example.com/remote.php/dav/files/me/Foxhole`.
+    pub url: String,
+    pub user: String,
+    pub password: String,
+    /// Hours between automatic backups, 0 only backs up on demand.
+    pub every: u32,
+}
+
+impl BackupConfig {
+    /// The schedules offered, as `(hours, label)`.
+    pub const SCHEDULES: [(u32, &'static str); 4] = [
+        (0, "Only on demand"),
+        (1, "Every hour"),
+        (24, "Every day"),
+        (168, "Every week"),
+    ];
+
+    pub fn load() -> Option<Self> {
+        storage::get("backup").ok()
+    }
+
+    fn folder(&self) -> Folder {
+        Folder::new(&self.url, &self.user, &self.password)
+    }
+
+    /// Whether an automatic backup is due.
+    pub fn due(&self) -> bool {
+        let last = storage::get::<f64>("backup_last").unwrap_or_default();
+        self.every > 0 && js_sys::Date::now() - last >= f64::from(self.every) * 3_600_000.0
+    }
+}
+
+/// The contents of a backup file.
+#[derive(Serialize, Deserialize)]
+struct Backup {
+    /// Milliseconds since the epoch.
+    exported: f64,
+    pages: Vec<Page>,
+}
+
+/// The name of the backup of `profile` made at `time`, like `2026-10-19T08-30-00`. The
+/// folder may be shared by several profiles.
+fn file_name(profile: &str, time: &str) -> String {
+    format!("foxhole-{profile}-{time}.json")
+}
+
+/// The profile of a backup named by [`file_name`], `None` for other files.
+fn profile_of(name: &str) -> Option<&str> {
+    let rest = name.strip_prefix("foxhole-")?.strip_suffix(".json")?;
+    let split = rest.len().checked_sub("-2026-10-19T08-30-00".len())?;
+    let time = rest.get(split..)?.strip_prefix('-')?;
+    let valid = time.char_indices().all(|(i, c)| match i {
+        4 | 7 | 13 | 16 => c == '-',
+        10 => c == 'T',
+        _ => c.is_ascii_digit(),
+    });
+    valid.then(|| &rest[..split])
+}
+
+/// Uploads the pages as a new backup and answers its name.
+pub async fn back_up(config: &BackupConfig, pages: &[Page]) -> Result<String, String> {
+    let now = js_sys::Date::new_0();
+    // 2026-10-19T08-30-00, colons are not allowed in file names everywhere
+    let time = String::from(now.to_iso_string());
+    let time = time.get(..19).unwrap_or_default().replace(':', "-");
+    let name = file_name(&storage::profile(), &time);
+    let backup = Backup {
+        exported: now.get_time(),
+        pages: pages.to_vec(),
+    };
+    let json = serde_json::to_string(&backup).map_err(|err| err.to_string())?;
+    config.folder().put(&Fetch, &name, &json).await?;
+    if let Err(err) = storage::set("backup_last", backup.exported) {
+        web_sys::console::log_1(&format!("{err}").into());
+    }
+    Ok(name)
+}
+
+/// The backups of the active profile, newest first.
+pub async fn list(config: &BackupConfig) -> Result<Vec<Entry>, String> {
+    let profile = storage::profile();
+    let mut entries = config.folder().list(&Fetch).await?;
+    entries.retain(|entry| profile_of(&entry.name) == Some(profile.as_str()));
+    // the names end with the time of the backup
+    entries.sort_by(|a, b| b.name.cmp(&a.name));
+    Ok(entries)
+}
+
+/// The pages of a backup. Links with a blocked URL scheme are left out.
+pub async fn download(config: &BackupConfig, name: &str) -> Result<Vec<Page>, String> {
+    let json = config.folder().get(&Fetch, name).await?;
+    let backup: Backup =
+        serde_json::from_str(&json).map_err(|err| format!("{name} is not a backup: {err}"))?;
+    if backup.pages.is_empty() {
+        return Err(format!("{name} has no pages"));
+    }
+    let schemes = urls::custom_schemes();
+    let mut pages = backup.pages;
+    pages
+        .iter_mut()
+        .flat_map(|page| &mut page.cards)
+        .for_each(|card: &mut Card| {
+            card.retain_links(&mut |link| urls::is_safe(&link.url, &schemes))
+        });
+    Ok(pages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backups_are_told_apart_by_their_whole_profile() {
+        let time = "2026-10-19T08-30-00";
+        assert_eq!(profile_of(&file_name("work", time)), Some("work"));
+        assert_eq!(profile_of(&file_name("work-x", time)), Some("work-x"));
+        assert_eq!(profile_of(&file_name("a b-é", time)), Some("a b-é"));
+        assert_eq!(profile_of("foxhole-work-x.json"), None);
+        assert_eq!(profile_of("foxhole-work-2026-10-19T08:30:00.json"), None);
+        assert_eq!(profile_of("foxhole-é.json"), None);
+        assert_eq!(profile_of("notes.json"), None);
+    }
+}
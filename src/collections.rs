@@ -0,0 +1,172 @@
+//! Card collections shared by a team, loaded from a URL and shown read-only next to the
+//! personal cards.
+
+use std::collections::HashMap;
+
+use gloo_net::http::Request;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::cards::cards_ctx::{Card, Page, PageId},
+    storage, urls,
+};
+
+/// A collection to show, stored in the `"collections"` list.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Subscription {
+    /// Shown in the badge of its cards.
+    pub name: String,
+    /// Absolute, or relative to Foxhole for a file served next to it.
+    pub url: String,
+}
+
+impl Subscription {
+    pub fn load() -> Vec<Self> {
+        storage::get("collections").unwrap_or_default()
+    }
+
+    /// The subscriptions as edited in the settings, one `name URL` per line.
+    pub fn to_lines(subscriptions: &[Self]) -> String {
+        subscriptions
+            .iter()
+            .map(|sub| format!("{} {}", sub.name, sub.url))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Parses [`Subscription::to_lines`], the URL is the last word of a line.
+    pub fn parse_lines(text: &str) -> Result<Vec<Self>, String> {
+        text.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| match line.rsplit_once(char::is_whitespace) {
+                Some((name, url)) if !name.trim().is_empty() => Ok(Self {
+                    name: name.trim().to_owned(),
+                    url: url.to_owned(),
+                }),
+                _ => Err(format!("Name the collection at {line}")),
+            })
+            .collect()
+    }
+}
+
+/// A Foxhole JSON document, a backup or the saved cards, only its pages are read.
+#[derive(Deserialize)]
+struct Document {
+    pages: Vec<Page>,
+}
+
+/// The pages of a document. Links with a blocked URL scheme are left out.
+pub fn parse(json: &str, schemes: &[String]) -> Result<Vec<Page>, String> {
+    let document: Document =
+        serde_json::from_str(json).map_err(|err| format!("not a Foxhole document: {err}"))?;
+    let mut pages = document.pages;
+    pages
+        .iter_mut()
+        .flat_map(|page| &mut page.cards)
+        .for_each(|card: &mut Card| {
+            card.retain_links(&mut |link| urls::is_safe(&link.url, schemes))
+        });
+    Ok(pages)
+}
+
+/// The shared cards to show on the `current` local page: the ones of the shared page of the
+/// same name, and on the first page the ones of shared pages no local page is named like.
+pub fn cards_for<'a>(shared: &'a [Page], local: &[Page], current: PageId) -> Vec<&'a Card> {
+    let Some(name) = local.get(current).map(|page| &page.name) else {
+        return Vec::new();
+    };
+    shared
+        .iter()
+        .filter(|page| {
+            &page.name == name || current == 0 && !local.iter().any(|local| local.name == page.name)
+        })
+        .flat_map(|page| &page.cards)
+        .collect()
+}
+
+/// The collections as last loaded, by URL, to show them before they are loaded again.
+pub fn cached() -> HashMap<String, Vec<Page>> {
+    storage::get("collections_cache").unwrap_or_default()
+}
+
+/// Loads a collection.
+pub async fn fetch(url: &str) -> Result<Vec<Page>, String> {
+    let response = Request::get(url)
+        .send()
+        .await
+        .map_err(|err| err.to_string())?;
+    if !response.ok() {
+        return Err(format!("{url} answered {}", response.status()));
+    }
+    let json = response.text().await.map_err(|err| err.to_string())?;
+    parse(&json, &urls::custom_schemes())
+}
+
+/// Caches the `loaded` collections for [`cached`], only the ones of the current
+/// `subscriptions`.
+pub fn cache(
+    loaded: &mut HashMap<String, Vec<Page>>,
+    subscriptions: &[Subscription],
+) -> Result<(), String> {
+    loaded.retain(|url, _| subscriptions.iter().any(|sub| &sub.url == url));
+    storage::set("collections_cache", &*loaded).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subscriptions_are_one_per_line() {
+        let subs = Subscription::parse_lines(
+            "Team runbooks https://example.com/team.json\n\n ops.json \n",
+        );
+        assert_eq!(subs, Err("Name the collection at ops.json".to_owned()));
+
+        let subs =
+            Subscription::parse_lines("Team runbooks https://example.com/team.json\nOps ops.json")
+                .unwrap();
+        assert_eq!(subs[0].name, "Team runbooks");
+        assert_eq!(subs[1].url, "ops.json");
+        assert_eq!(
+            Subscription::parse_lines(&Subscription::to_lines(&subs)),
+            Ok(subs)
+        );
+    }
+
+    #[test]
+    fn documents_lose_their_unsafe_links() {
+        let json = r#"{"exported": 0, "pages": [{"name": "Home", "cards": [{"name": "Runbooks",
+            "links": [{"label": "Wiki", "url": "https://wiki.example.com"},
+                      {"label": "Bad", "url": "javascript:alert(1)"}]}]}]}"#;
+        let pages = parse(json, &[]).unwrap();
+        let links = &pages[0].cards[0].links;
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].url, "https://wiki.example.com");
+        assert!(parse("[]", &[]).is_err());
+    }
+
+    #[test]
+    fn shared_pages_go_to_the_local_page_of_their_name() {
+        let page = |name: &str, cards: &[&str]| Page {
+            name: name.to_owned(),
+            cards: cards.iter().map(|&c| Card::new(c.to_owned())).collect(),
+        };
+        let local = [page("Home", &[]), page("Work", &[])];
+        let shared = [
+            page("Work", &["Dashboards"]),
+            page("Ops", &["Runbooks"]),
+            page("Home", &["Repos"]),
+        ];
+        let names = |page| -> Vec<String> {
+            cards_for(&shared, &local, page)
+                .into_iter()
+                .map(|card| card.name.clone())
+                .collect()
+        };
+        assert_eq!(names(0), ["Runbooks", "Repos"]);
+        assert_eq!(names(1), ["Dashboards"]);
+        assert!(names(2).is_empty());
+    }
+}
@@ -0,0 +1,255 @@
+use js_sys::Function;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::spawn_local;
+use yew::{
+    classes, function_component, html, use_context, use_effect_with_deps, use_state, use_state_eq,
+    AttrValue, Callback, Html, Properties,
+};
+
+use crate::{
+    backup::{self, BackupConfig},
+    storage,
+    webdav::Entry,
+    GlobalCtx,
+};
+
+use super::{
+    cards::cards_ctx::{Cards, CardsContext, CardsHandler},
+    confirm::{ConfirmContext, Confirmation},
+    edit::{EditForm, Field, FormData, Key, Url},
+};
+
+/// How often to check whether an automatic backup is due, in milliseconds.
+const CHECK_EVERY: i32 = 10 * 60 * 1000;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct BackupsProps {
+    pub hidden: bool,
+    pub close: Callback<()>,
+}
+
+/// Backs up the cards to a WebDAV folder and restores them, also runs the scheduled
+/// backups while the page is open.
+#[function_component(Backups)]
+pub fn backups(BackupsProps { hidden, close }: &BackupsProps) -> Html {
+    let ctx = use_context::<GlobalCtx>().unwrap();
+    let cards = use_context::<CardsContext>().unwrap();
+    let confirm = use_context::<ConfirmContext>().unwrap();
+    let config = use_state(BackupConfig::load);
+    let entries = use_state_eq(|| None::<Vec<Entry>>);
+    let status = use_state_eq(|| None::<String>);
+    let form_hide = use_state_eq(|| true);
+
+    // scheduled backups, of the saved cards so the timer never sees stale ones
+    use_effect_with_deps(
+        |config: &Option<BackupConfig>| {
+            let config = config.clone();
+            let check = Closure::<dyn Fn()>::new(move || {
+                if let Some(config) = config.clone().filter(BackupConfig::due) {
+                    spawn_local(async move {
+                        let pages = Cards::load().pages;
+                        if let Err(err) = backup::back_up(&config, &pages).await {
+                            web_sys::console::log_1(&format!("Backup failed: {err}").into());
+                        }
+                    });
+                }
+            });
+            let window = web_sys::window().unwrap();
+            let callback: &Function = check.as_ref().unchecked_ref();
+            callback.call0(&JsValue::NULL).ok();
+            let interval = window
+                .set_interval_with_callback_and_timeout_and_arguments_0(callback, CHECK_EVERY)
+                .ok();
+            move || {
+                if let Some(interval) = interval {
+                    window.clear_interval_with_handle(interval);
+                }
+                drop(check);
+            }
+        },
+        (*config).clone(),
+    );
+
+    let refresh = {
+        let (config, entries, status) = (config.clone(), entries.clone(), status.clone());
+        Callback::from(move |_| {
+            let Some(config) = (*config).clone() else {
+                return;
+            };
+            let (entries, status) = (entries.clone(), status.clone());
+            status.set(Some("Listing backups…".to_owned()));
+            spawn_local(async move {
+                match backup::list(&config).await {
+                    Ok(list) => {
+                        status.set(None);
+                        entries.set(Some(list));
+                    }
+                    Err(err) => status.set(Some(format!("Listing failed: {err}"))),
+                }
+            });
+        })
+    };
+    // list the backups when opened
+    {
+        let refresh = refresh.clone();
+        use_effect_with_deps(
+            move |(hidden, _)| {
+                if !*hidden {
+                    refresh.emit(());
+                }
+                || ()
+            },
+            (*hidden, (*config).clone()),
+        );
+    }
+
+    let back_up_now = {
+        let (config, status, refresh) = (config.clone(), status.clone(), refresh.clone());
+        let pages = cards.pages.clone();
+        Callback::from(move |_| {
+            let Some(config) = (*config).clone() else {
+                return;
+            };
+            let (pages, status, refresh) = (pages.clone(), status.clone(), refresh.clone());
+            status.set(Some("Backing up…".to_owned()));
+            spawn_local(async move {
+                match backup::back_up(&config, &pages).await {
+                    Ok(name) => {
+                        status.set(Some(format!("Saved {name}")));
+                        refresh.emit(());
+                    }
+                    Err(err) => status.set(Some(format!("Backup failed: {err}"))),
+                }
+            });
+        })
+    };
+
+    let restore = {
+        let (config, status) = (config.clone(), status.clone());
+        let ask = ctx.confirm.bulk();
+        Callback::from(move |name: AttrValue| {
+            let Some(config) = (*config).clone() else {
+                return;
+            };
+            let (cards, status) = (cards.clone(), status.clone());
+            let message = format!("Replace all your cards with the ones of {name}?");
+            let action = Callback::from(move |_| {
+                let (config, name) = (config.clone(), name.clone());
+                let (cards, status) = (cards.clone(), status.clone());
+                status.set(Some(format!("Restoring {name}…")));
+                spawn_local(async move {
+                    match backup::download(&config, &name).await {
+                        Ok(pages) => {
+                            cards.dispatch(CardsHandler::Restore(pages));
+                            status.set(Some(format!("Restored {name}, undo to go back")));
+                        }
+                        Err(err) => status.set(Some(format!("Restoring failed: {err}"))),
+                    }
+                });
+            });
+            confirm.emit(Confirmation::new(ask.clone(), message, action));
+        })
+    };
+
+    // settings form
+    let current = (*config).clone();
+    let value = |get: fn(&BackupConfig) -> String| current.as_ref().map(get).unwrap_or_default();
+    let schedules = BackupConfig::SCHEDULES
+        .iter()
+        .map(|(hours, label)| (hours.to_string(), *label));
+    const URL: Key<Option<Url>> = Key::new("url");
+    const USER: Key<String> = Key::new("user");
+    const PASSWORD: Key<String> = Key::new("password");
+    const EVERY: Key<String> = Key::new("every");
+    let fields = vec![
+        Field::url(URL, "WebDAV folder:")
+            .value(value(|c| c.url.clone()))
+            .place_holder("https://cloud.example.com/remote.php/dav/files/me/Foxhole")
+            .required(),
+        Field::text(USER, "User:").value(value(|c| c.user.clone())),
+        Field::password(PASSWORD, "Password:")
+            .value(value(|c| c.password.clone()))
+            .place_holder("Kept unencrypted in this browser, use an app password"),
+        Field::select(EVERY, "Back up:", schedules).value(value(|c| c.every.to_string())),
+    ];
+    let open_form = {
+        let form_hide = form_hide.clone();
+        Callback::from(move |_| form_hide.set(false))
+    };
+    let save = {
+        let (config, status, form_hide) = (config.clone(), status.clone(), form_hide.clone());
+        Callback::from(move |data: Option<FormData>| {
+            if let Some(data) = data {
+                let new = BackupConfig {
+                    // required
+                    url: data.get(URL).map(String::from).unwrap_or_default(),
+                    user: data.get(USER),
+                    password: data.get(PASSWORD),
+                    every: data.get(EVERY).parse().unwrap_or_default(),
+                };
+                // still used until the page is reloaded
+                if let Err(err) = storage::set("backup", &new) {
+                    status.set(Some(format!("Settings not saved: {err}")));
+                }
+                config.set(Some(new));
+            }
+            form_hide.set(true);
+        })
+    };
+
+    let list: Html = match &*entries {
+        None => Html::default(),
+        Some(list) if list.is_empty() => html! { <p>{ "No backup yet." }</p> },
+        Some(list) => list
+            .iter()
+            .map(|entry| {
+                let name = AttrValue::from(entry.name.clone());
+                let onclick = restore.reform({
+                    let name = name.clone();
+                    move |_| name.clone()
+                });
+                let size = entry
+                    .size
+                    .map(|size| format!("{:.1} kB", size as f64 / 1000.0));
+                html! {
+                    <div class={classes!("backup")} key={name.to_string()}>
+                        <span>{ name }</span>
+                        <span class={classes!("details")}>
+                            { entry.modified.clone().unwrap_or_default() } { " " } { size }
+                        </span>
+                        <button {onclick}>{ "Restore" }</button>
+                    </div>
+                }
+            })
+            .collect(),
+    };
+
+    let configured = config.is_some();
+    let close = close.reform(|_| ());
+    let hidden_class = if *hidden { Some("hidden") } else { None };
+    html! {
+        <>
+            <div class={classes!("edit-screen", hidden_class)}>
+                <div class={classes!("edit-form", "settings", "backups")}>
+                    <h3>{ "Backups" }</h3>
+                    if configured {
+                        { list }
+                    } else {
+                        <p>{ "Set a WebDAV folder, on Nextcloud for example, to back up to." }</p>
+                    }
+                    if let Some(status) = &*status {
+                        <p class={classes!("details")}>{ status }</p>
+                    }
+                    <div class={classes!("buttons")}>
+                        <button onclick={back_up_now} disabled={!configured}>
+                            { "💾 Back up now" }
+                        </button>
+                        <button onclick={open_form}>{ "⚙ Settings" }</button>
+                        <button onclick={close}>{ "Close" }</button>
+                    </div>
+                </div>
+            </div>
+            <EditForm {fields} hidden={*form_hide} {save}/>
+        </>
+    }
+}
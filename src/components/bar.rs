@@ -1,27 +1,264 @@
-use yew::{classes, function_component, html, use_context, Callback, Html};
+use yew::{classes, function_component, html, use_context, use_state_eq, Callback, Html};
 
-use crate::{CtxAction, GlobalCtx};
+use crate::{
+    collections::Subscription, policy::ConfirmPolicy, sync::SyncConfig, urls, CtxAction, GlobalCtx,
+};
+
+use super::{
+    cards::{
+        cards_ctx::{CardsContext, CardsHandler},
+        PageTabs,
+    },
+    edit::{EditForm, Field, FormData, Key, Url},
+    Backups, Duplicates, Export, History, Import, LayoutSettings, ProfileSwitcher,
+};
 
 #[function_component(Bar)]
 pub fn bar() -> Html {
     let ctx = use_context::<GlobalCtx>().unwrap();
-    let theme_button_hide = !ctx.editable;
+    let buttons_hide = !ctx.editable;
 
     let toggle_theme = {
         let ctx = ctx.clone();
         Callback::from(move |_| ctx.dispatch(CtxAction::ToggleTheme))
     };
-    let toggle_edit = { Callback::from(move |_| ctx.dispatch(CtxAction::ToggleEdit)) };
+    let toggle_edit = {
+        let ctx = ctx.clone();
+        Callback::from(move |_| ctx.dispatch(CtxAction::ToggleEdit))
+    };
+
+    let cards = use_context::<CardsContext>().unwrap();
+    let all_collapsed = cards.cards().iter().all(|card| card.collapsed);
+    let collapse_all = {
+        let cards = cards.clone();
+        Callback::from(move |_| cards.dispatch(CardsHandler::CollapseAll(!all_collapsed)))
+    };
+
+    let settings_hide = use_state_eq(|| true);
+    let open_settings = {
+        let hide = settings_hide.clone();
+        Callback::from(move |_| hide.set(false))
+    };
+    let close_settings = {
+        let hide = settings_hide.clone();
+        Callback::from(move |_| hide.set(true))
+    };
+
+    let duplicates_hide = use_state_eq(|| true);
+    let open_duplicates = {
+        let hide = duplicates_hide.clone();
+        Callback::from(move |_| hide.set(false))
+    };
+    let close_duplicates = {
+        let hide = duplicates_hide.clone();
+        Callback::from(move |_| hide.set(true))
+    };
+    let export_hide = use_state_eq(|| true);
+    let open_export = {
+        let hide = export_hide.clone();
+        Callback::from(move |_| hide.set(false))
+    };
+    let close_export = {
+        let hide = export_hide.clone();
+        Callback::from(move |_| hide.set(true))
+    };
+    let import_hide = use_state_eq(|| true);
+    let open_import = {
+        let hide = import_hide.clone();
+        Callback::from(move |_| hide.set(false))
+    };
+    let close_import = {
+        let hide = import_hide.clone();
+        Callback::from(move |_| hide.set(true))
+    };
+    let history_hide = use_state_eq(|| true);
+    let open_history = {
+        let hide = history_hide.clone();
+        Callback::from(move |_| hide.set(false))
+    };
+    let close_history = {
+        let hide = history_hide.clone();
+        Callback::from(move |_| hide.set(true))
+    };
+    let backups_hide = use_state_eq(|| true);
+    let open_backups = {
+        let hide = backups_hide.clone();
+        Callback::from(move |_| hide.set(false))
+    };
+    let close_backups = {
+        let hide = backups_hide.clone();
+        Callback::from(move |_| hide.set(true))
+    };
+    let undo = {
+        let cards = cards.clone();
+        Callback::from(move |_| cards.dispatch(CardsHandler::Undo))
+    };
+
+    // approved URL schemes form
+    const SCHEMES: Key<String> = Key::new("schemes");
+    let schemes_fields = vec![Field::text(SCHEMES, "Also allow URL schemes:")
+        .value(ctx.schemes.join(", "))
+        .place_holder("obsidian, vscode")
+        .validate(|schemes| match urls::parse_schemes(schemes) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err.to_string()),
+        })];
+    let schemes_hide = use_state_eq(|| true);
+    let open_schemes = {
+        let hide = schemes_hide.clone();
+        Callback::from(move |_| hide.set(false))
+    };
+    let save_schemes = {
+        let ctx = ctx.clone();
+        let hide = schemes_hide.clone();
+        Callback::from(move |data: Option<FormData>| {
+            // already validated
+            if let Some(Ok(schemes)) = data.map(|data| urls::parse_schemes(&data.get(SCHEMES))) {
+                ctx.dispatch(CtxAction::SetSchemes(schemes));
+            }
+            hide.set(true);
+        })
+    };
+
+    // confirmation policy form
+    const CARDS: Key<bool> = Key::new("cards");
+    const TYPE_ABOVE: Key<String> = Key::new("type_above");
+    const LINKS: Key<bool> = Key::new("links");
+    const BULK: Key<bool> = Key::new("bulk");
+    let policy = &ctx.confirm;
+    let policy_fields = vec![
+        Field::checkbox(CARDS, "Confirm removing cards and groups with links:")
+            .checked(policy.cards),
+        Field::text(
+            TYPE_ABOVE,
+            "Type their name when they have more links than:",
+        )
+        .value(policy.type_above.to_string())
+        .place_holder("0 never asks")
+        .validate(|n| match n.parse::<usize>() {
+            Ok(_) => Ok(()),
+            Err(_) => Err("Use a whole number, 0 never asks".to_owned()),
+        }),
+        Field::checkbox(LINKS, "Confirm removing links:").checked(policy.links),
+        Field::checkbox(BULK, "Confirm removing pages, profiles and many links:")
+            .checked(policy.bulk),
+    ];
+    let policy_hide = use_state_eq(|| true);
+    let open_policy = {
+        let hide = policy_hide.clone();
+        Callback::from(move |_| hide.set(false))
+    };
+    let save_policy = {
+        let ctx = ctx.clone();
+        let hide = policy_hide.clone();
+        Callback::from(move |data: Option<FormData>| {
+            if let Some(data) = data {
+                ctx.dispatch(CtxAction::SetConfirmPolicy(ConfirmPolicy {
+                    cards: data.get(CARDS),
+                    type_above: data.get(TYPE_ABOVE).parse().unwrap_or_default(),
+                    links: data.get(LINKS),
+                    bulk: data.get(BULK),
+                }));
+            }
+            hide.set(true);
+        })
+    };
+
+    // sync server form
+    const SERVER: Key<Option<Url>> = Key::new("server");
+    const TOKEN: Key<String> = Key::new("token");
+    let sync = ctx.sync.as_ref();
+    let sync_fields = vec![
+        Field::url(SERVER, "Sync server:")
+            .value(sync.map(|sync| sync.server.clone()).unwrap_or_default())
+            .place_holder("https://foxhole.example.com, empty stops syncing"),
+        Field::text(TOKEN, "Token:").value(sync.map(|sync| sync.token.clone()).unwrap_or_default()),
+    ];
+    let sync_hide = use_state_eq(|| true);
+    let open_sync = {
+        let hide = sync_hide.clone();
+        Callback::from(move |_| hide.set(false))
+    };
+    let save_sync = {
+        let ctx = ctx.clone();
+        let hide = sync_hide.clone();
+        Callback::from(move |data: Option<FormData>| {
+            if let Some(data) = data {
+                let config = data.get(SERVER).map(|server| SyncConfig {
+                    server: server.into(),
+                    token: data.get(TOKEN),
+                });
+                ctx.dispatch(CtxAction::SetSync(config));
+            }
+            hide.set(true);
+        })
+    };
+
+    // shared collections form
+    const COLLECTIONS: Key<String> = Key::new("collections");
+    let collections_fields = vec![Field::textarea(
+        COLLECTIONS,
+        "Shared collections, one name and URL per line:",
+    )
+    .value(Subscription::to_lines(&ctx.collections))
+    .place_holder("Team https://intranet.example.com/foxhole.json")
+    .validate(|text| Subscription::parse_lines(text).map(|_| ()))];
+    let collections_hide = use_state_eq(|| true);
+    let open_collections = {
+        let hide = collections_hide.clone();
+        Callback::from(move |_| hide.set(false))
+    };
+    let save_collections = {
+        let ctx = ctx.clone();
+        let hide = collections_hide.clone();
+        Callback::from(move |data: Option<FormData>| {
+            // already validated
+            if let Some(Ok(collections)) =
+                data.map(|data| Subscription::parse_lines(&data.get(COLLECTIONS)))
+            {
+                ctx.dispatch(CtxAction::SetCollections(collections));
+            }
+            hide.set(true);
+        })
+    };
 
     html! {
         <div class={classes!("bar")}>
-            <button onclick={toggle_theme} hidden={theme_button_hide}>{ "🎨 Theme" }</button>
+            <PageTabs />
+            <button onclick={collapse_all} hidden={cards.cards().is_empty()}>
+                { if all_collapsed { "⊞ Expand all" } else { "⊟ Collapse all" } }
+            </button>
+            <button onclick={undo} hidden={cards.history.is_empty()} title="Undo the last removal">
+                { "↶ Undo" }
+            </button>
+            <ProfileSwitcher />
+            <button onclick={toggle_theme} hidden={buttons_hide}>{ "🎨 Theme" }</button>
+            <button onclick={open_settings} hidden={buttons_hide}>{ "📐 Layout" }</button>
+            <button onclick={open_duplicates} hidden={buttons_hide}>{ "⧉ Duplicates" }</button>
+            <button onclick={open_schemes} hidden={buttons_hide}>{ "🔒 Schemes" }</button>
+            <button onclick={open_policy} hidden={buttons_hide}>{ "🛡️ Confirmations" }</button>
+            <button onclick={open_sync} hidden={buttons_hide}>{ "☁ Sync" }</button>
+            <button onclick={open_collections} hidden={buttons_hide}>{ "📚 Collections" }</button>
+            <button onclick={open_import} hidden={buttons_hide}>{ "📥 Import" }</button>
+            <button onclick={open_export} hidden={buttons_hide}>{ "📄 Export" }</button>
+            <button onclick={open_backups} hidden={buttons_hide}>{ "💾 Backups" }</button>
+            <button onclick={open_history} hidden={buttons_hide}>{ "🕘 History" }</button>
             <div id="toggle-edit">
                 <label>
                     <span>{ "⚙️  Edit mode:" }</span>
                     <input type="checkbox" onchange={toggle_edit}/>
                 </label>
             </div>
+            <LayoutSettings hidden={*settings_hide} close={close_settings}/>
+            <Duplicates hidden={*duplicates_hide} close={close_duplicates}/>
+            <Backups hidden={*backups_hide} close={close_backups}/>
+            <History hidden={*history_hide} close={close_history}/>
+            <Import hidden={*import_hide} close={close_import}/>
+            <Export hidden={*export_hide} close={close_export}/>
+            <EditForm fields={schemes_fields} hidden={*schemes_hide} save={save_schemes}/>
+            <EditForm fields={policy_fields} hidden={*policy_hide} save={save_policy}/>
+            <EditForm fields={sync_fields} hidden={*sync_hide} save={save_sync}/>
+            <EditForm fields={collections_fields} hidden={*collections_hide} save={save_collections}/>
         </div>
     }
 }
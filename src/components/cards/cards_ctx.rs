@@ -1,60 +1,282 @@
-use gloo_storage::{LocalStorage, Storage};
+use crate::{
+    components::cards::{
+        doc::{self, Doc},
+        query::Query,
+    },
+    storage, urls,
+};
+pub use foxhole_model::{
+    Anchor, Card, CardId, DisplayMode, Group, GroupPath, LinkId, LinkPos, Page, PageId,
+};
 use serde::{Deserialize, Serialize};
-use std::rc::Rc;
+use std::{collections::HashMap, rc::Rc};
 use yew::{
-    function_component, html, use_reducer, Children, ContextProvider, Html, Properties, Reducible,
-    UseReducerHandle,
+    function_component, html, use_effect_with_deps, use_reducer, Children, ContextProvider, Html,
+    Properties, Reducible, UseReducerHandle,
 };
 
-#[derive(Clone, PartialEq, Serialize, Deserialize)]
-pub struct Anchor {
-    pub label: String,
-    pub url: String,
+/// Storage formats of the cards, older formats are migrated on load.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredCards {
+    Pages {
+        pages: Vec<Page>,
+        /// Missing in cards saved before edits were merged.
+        #[serde(default)]
+        doc: Option<Doc>,
+    },
+    Flat {
+        inner: Vec<Card>,
+    },
+}
+
+impl From<StoredCards> for Cards {
+    fn from(stored: StoredCards) -> Self {
+        let (mut pages, doc) = match stored {
+            StoredCards::Pages { pages, doc } => (pages, doc),
+            StoredCards::Flat { inner } => (
+                vec![Page {
+                    name: Cards::DEFAULT_PAGE.to_owned(),
+                    cards: inner,
+                }],
+                None,
+            ),
+        };
+        if pages.is_empty() {
+            pages.push(Page::new(Self::DEFAULT_PAGE.to_owned()));
+        }
+        let doc = match doc {
+            Some(doc) if doc.pages() == pages => doc,
+            // saved by a version that didn't know about the document
+            Some(mut doc) => {
+                doc.update(&pages, doc::replica());
+                doc
+            }
+            None => Doc::migrate(&pages, doc::replica()),
+        };
+        Self {
+            pages,
+            doc,
+            current: 0,
+            history: Vec::new(),
+            revision: 0,
+            unsaved: None,
+        }
+    }
 }
 
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
-pub struct Card {
-    pub name: String,
-    pub links: Vec<Anchor>,
+#[serde(from = "StoredCards")]
+pub struct Cards {
+    pub pages: Vec<Page>,
+    /// The edits that led to the pages, merged with the ones of other tabs and devices.
+    pub doc: Doc,
+    /// The page being shown, never out of bounds.
+    #[serde(skip)]
+    pub current: PageId,
+    /// Earlier versions of the pages, the last one is restored by [`CardsHandler::Undo`].
+    #[serde(skip)]
+    pub history: Vec<Vec<Page>>,
+    /// Bumped on every save, tells whether another tab saved since these cards were loaded.
+    #[serde(skip)]
+    pub revision: u64,
+    /// Why the last save failed, usually a full storage. The pages are still shown.
+    #[serde(skip)]
+    pub unsaved: Option<String>,
 }
 
-impl Card {
-    /// Constructs a card with no links.
-    pub fn new(name: String) -> Self {
-        Self {
-            name,
-            links: Vec::new(),
+impl Cards {
+    pub const DEFAULT_PAGE: &'static str = "Home";
+
+    /// How many undoable actions are remembered.
+    const MAX_HISTORY: usize = 20;
+
+    /// The cards as last saved by any tab.
+    pub fn load() -> Self {
+        let mut cards = storage::get::<Cards>("cards").unwrap_or_default();
+        cards.revision = storage::get("cards_rev").unwrap_or_default();
+        cards
+    }
+
+    /// Takes the edits of the saved cards, keeping the page being shown.
+    fn reload(&mut self) {
+        let saved = Self::load();
+        self.merge(&saved.doc);
+        self.revision = saved.revision;
+        // undoing would bring back the other tab's old pages
+        self.history.clear();
+    }
+
+    /// Takes the edits of another replica of the cards.
+    fn merge(&mut self, doc: &Doc) {
+        self.doc.merge(doc);
+        self.pages = self.doc.pages();
+        // the replicas removed every page between them
+        if self.pages.is_empty() {
+            self.pages.push(Page::new(Self::DEFAULT_PAGE.to_owned()));
+            self.doc.update(&self.pages, doc::replica());
         }
+        self.current = self.current.min(self.pages.len() - 1);
     }
 
-    /// Append a link to the end of the card.
-    pub fn push_link(&mut self, anchor: Anchor) {
-        self.links.push(anchor)
+    /// Saves the cards, merged with the ones another tab saved meanwhile if any.
+    fn save(&mut self) {
+        if let Err(err) = storage::set("page", self.current) {
+            web_sys::console::log_1(&format!("{err}").into());
+        }
+        self.doc.update(&self.pages, doc::replica());
+        let saved = storage::get::<u64>("cards_rev").unwrap_or_default();
+        if saved != self.revision {
+            let saved = Self::load();
+            self.merge(&saved.doc);
+            self.revision = saved.revision;
+        }
+        // the cards stay unsaved until a save succeeds, the other tabs keep theirs
+        if let Err(err) = storage::set("cards", &*self) {
+            self.unsaved = Some(err.to_string());
+            return;
+        }
+        self.unsaved = None;
+        self.revision += 1;
+        if let Err(err) = storage::set("cards_rev", self.revision) {
+            web_sys::console::log_1(&format!("{err}").into());
+        }
     }
 
-    /// Removes the nth link.
-    pub fn remove_link(&mut self, nth: usize) -> Anchor {
-        self.links.remove(nth)
+    /// The page being shown.
+    pub fn page(&self) -> &Page {
+        &self.pages[self.current]
     }
-}
 
-pub type CardId = usize;
-pub type LinkPos = usize;
+    /// The cards of the page being shown.
+    pub fn cards(&self) -> &[Card] {
+        &self.page().cards
+    }
 
-#[derive(Clone, PartialEq, Serialize, Deserialize)]
-pub struct LinkId {
-    pub card: CardId,
-    pub link: LinkPos,
-}
+    fn cards_mut(&mut self) -> &mut Vec<Card> {
+        &mut self.pages[self.current].cards
+    }
 
-#[derive(Clone, PartialEq, Default, Serialize, Deserialize)]
-pub struct Cards {
-    pub inner: Vec<Card>,
+    /// Ids of the cards of the page being shown in display order, pinned cards first.
+    pub fn display_order(&self) -> Vec<CardId> {
+        let mut ids: Vec<CardId> = (0..self.cards().len()).collect();
+        ids.sort_by_key(|&id| !self.cards()[id].pinned);
+        ids
+    }
+
+    /// Finds a page by its position (starting at 1) or by its name, ignoring case.
+    pub fn find_page(&self, query: &str) -> Option<PageId> {
+        if let Ok(n) = query.parse::<usize>() {
+            return (1..=self.pages.len()).contains(&n).then(|| n - 1);
+        }
+        self.pages
+            .iter()
+            .position(|page| page.name.eq_ignore_ascii_case(query))
+    }
+
+    /// Every link of every page with its position.
+    pub fn all_links(&self) -> Vec<(PageId, LinkId, &Anchor)> {
+        foxhole_model::all_links(&self.pages)
+    }
+
+    /// The link at `link` of `page`.
+    pub fn link(&self, page: PageId, link: &LinkId) -> Option<&Anchor> {
+        let card = self.pages.get(page)?.cards.get(link.card)?;
+        card.links_at(&link.group)?.get(link.link)
+    }
+
+    fn link_mut(&mut self, page: PageId, link: &LinkId) -> Option<&mut Anchor> {
+        let card = self.pages.get_mut(page)?.cards.get_mut(link.card)?;
+        card.links_at_mut(&link.group)?.get_mut(link.link)
+    }
+
+    /// `Page › Card › Group` path of a link, for humans.
+    pub fn location(&self, page: PageId, link: &LinkId) -> String {
+        let Some(card) = self.pages.get(page).and_then(|p| p.cards.get(link.card)) else {
+            return String::default();
+        };
+        let mut names = vec![self.pages[page].name.as_str(), card.name.as_str()];
+        for depth in 1..=link.group.len() {
+            if let Some(group) = card.group(&link.group[..depth]) {
+                names.push(&group.name);
+            }
+        }
+        names.join(" › ")
+    }
+
+    /// Links on any page with the same URL as `url`, compared with [`urls::comparable`].
+    pub fn duplicates_of(&self, url: &str) -> Vec<(PageId, LinkId)> {
+        let url = urls::comparable(url);
+        self.all_links()
+            .into_iter()
+            .filter(|(_, _, link)| urls::comparable(&link.url) == url)
+            .map(|(page, link, _)| (page, link))
+            .collect()
+    }
+
+    /// Sets of links sharing a URL, in the order they first appear.
+    pub fn duplicates(&self) -> Vec<Vec<(PageId, LinkId)>> {
+        let mut sets: Vec<Vec<(PageId, LinkId)>> = Vec::new();
+        let mut by_url: HashMap<String, usize> = HashMap::new();
+        for (page, link, anchor) in self.all_links() {
+            let url = urls::comparable(&anchor.url);
+            if url.is_empty() {
+                continue;
+            }
+            match by_url.get(&url) {
+                Some(&set) => sets[set].push((page, link)),
+                None => {
+                    by_url.insert(url, sets.len());
+                    sets.push(vec![(page, link)]);
+                }
+            }
+        }
+        sets.retain(|set| set.len() > 1);
+        sets
+    }
+
+    /// Every tag in use on any page with the number of links having it, sorted by name.
+    pub fn tags(&self) -> Vec<(String, usize)> {
+        let mut tags: Vec<(String, usize)> = Vec::new();
+        for (_, _, link) in self.all_links() {
+            for tag in &link.tags {
+                match tags.iter_mut().find(|(t, _)| t.eq_ignore_ascii_case(tag)) {
+                    Some((_, count)) => *count += 1,
+                    None => tags.push((tag.clone(), 1)),
+                }
+            }
+        }
+        tags.sort_by_key(|(tag, _)| tag.to_lowercase());
+        tags
+    }
+
+    /// How many links on any page have a URL that is not safe to open, see [`urls::is_safe`].
+    pub fn unsafe_links(&self, schemes: &[String]) -> usize {
+        self.all_links()
+            .into_iter()
+            .filter(|(_, _, link)| !urls::is_safe(&link.url, schemes))
+            .count()
+    }
+
+    /// Selects a page, out of bounds pages are ignored.
+    pub fn select(&mut self, page: PageId) {
+        if page < self.pages.len() {
+            self.current = page;
+        }
+    }
 }
 
-impl Cards {
-    pub fn into_inner(self) -> Vec<Card> {
-        self.inner
+impl Default for Cards {
+    fn default() -> Self {
+        let pages = vec![Page::new(Self::DEFAULT_PAGE.to_owned())];
+        Self {
+            doc: Doc::new(&pages),
+            pages,
+            current: 0,
+            history: Vec::new(),
+            revision: 0,
+            unsaved: None,
+        }
     }
 }
 
@@ -71,66 +293,503 @@ pub enum CardsHandler {
         card1: CardId,
         card2: CardId,
     },
+    AddSmart {
+        name: String,
+        query: Query,
+    },
+    /// Adds a copy of a card, from a shared collection, without its unsafe links.
+    Copy(Card),
+    SetQuery {
+        card: CardId,
+        query: Query,
+    },
+    ToggleCollapse(CardId),
+    TogglePin(CardId),
+    SetDisplay {
+        card: CardId,
+        mode: DisplayMode,
+    },
+    /// Collapses (`true`) or expands (`false`) every card of the page.
+    CollapseAll(bool),
+    /// Removes the links with unsafe URLs from every page, given the approved schemes.
+    RemoveUnsafe(Vec<String>),
+    /// Keeps one of a set of duplicate links and removes the others, merging them into the
+    /// kept one first if `merge` is true. Links are on any page.
+    MergeDuplicates {
+        keep: (PageId, LinkId),
+        others: Vec<(PageId, LinkId)>,
+        merge: bool,
+    },
+    /// Restores the pages as they were before the last removal or merge.
+    Undo,
+    /// Takes the edits of the cards saved by another tab.
+    Reload,
+    /// Takes the edits made on other devices, from the sync server. Their links are kept
+    /// whatever their scheme, another device may have approved it; unsafe ones are
+    /// quarantined like any stored link.
+    Merge(Doc),
+    /// Replaces every page, with the ones of a backup.
+    Restore(Vec<Page>),
+    /// Adds an imported page after the others and shows it, its name gets a number if
+    /// taken.
+    Import(Page),
+    /// Puts back a card of a snapshot, in place of the card of the same name on the page of
+    /// the same name. The page is added if it is gone.
+    RestoreCard {
+        page: String,
+        card: Card,
+    },
+    /// Adds a link, unless its URL is unsafe.
     AddLink {
         card_index: CardId,
+        group: GroupPath,
         link: Anchor,
     },
     // link actions
     RemoveLink(LinkId),
+    /// Counts an opened link, which may be on any page.
+    RecordClick {
+        page: PageId,
+        link: LinkId,
+    },
     EditLink {
         link: LinkId,
         new_label: Option<String>,
         new_url: Option<String>,
+        new_tags: Option<Vec<String>>,
+    },
+    /// Sets or clears the uploaded icon of a link.
+    SetLinkImage {
+        link: LinkId,
+        image: Option<String>,
+    },
+    /// Replaces the optional metadata of a link, `None` clears a field.
+    SetLinkDetails {
+        link: LinkId,
+        description: Option<String>,
+        notes: Option<String>,
+        icon: Option<String>,
+        color: Option<String>,
     },
     SwapLinks {
         card: CardId,
+        group: GroupPath,
         link1: LinkPos,
         link2: LinkPos,
     },
+    // group actions
+    AddGroup {
+        card: CardId,
+        parent: GroupPath,
+        name: String,
+    },
+    RemoveGroup {
+        card: CardId,
+        group: GroupPath,
+    },
+    RenameGroup {
+        card: CardId,
+        group: GroupPath,
+        new_name: String,
+    },
+    SwapGroups {
+        card: CardId,
+        parent: GroupPath,
+        group1: usize,
+        group2: usize,
+    },
+    // page actions
+    AddPage(String),
+    RemovePage(PageId),
+    RenamePage {
+        page: PageId,
+        new_name: String,
+    },
+    SwapPages {
+        page1: PageId,
+        page2: PageId,
+    },
+    SelectPage(PageId),
+    MoveCard {
+        card: CardId,
+        page: PageId,
+    },
+}
+
+impl CardsHandler {
+    /// Whether the pages before this action are kept for [`CardsHandler::Undo`].
+    fn undoable(&self) -> bool {
+        matches!(
+            self,
+            Self::Remove(_)
+                | Self::RemoveUnsafe(_)
+                | Self::MergeDuplicates { .. }
+                | Self::RemoveLink(_)
+                | Self::RemoveGroup { .. }
+                | Self::RemovePage(_)
+                | Self::MoveCard { .. }
+                | Self::Restore(_)
+                | Self::RestoreCard { .. }
+                | Self::Import(_)
+        )
+    }
 }
 
 impl Reducible for Cards {
     type Action = CardsHandler;
 
     fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
-        let mut inner = (*self).clone().into_inner();
+        let mut cards = (*self).clone();
+        // already saved by the other tab
+        let from_storage = matches!(action, CardsHandler::Reload);
+        if !cards.apply(action) {
+            return self;
+        }
+        if !from_storage {
+            cards.save();
+        }
+        Rc::new(cards)
+    }
+}
+
+impl Cards {
+    /// Changes the pages as `action` says, without saving them. Returns `false` when the
+    /// action is ignored, as when it points at a card, group or link that is gone: forms
+    /// and dialogs may still be open with positions from before a reload or a merge.
+    fn apply(&mut self, action: CardsHandler) -> bool {
+        let cards = self;
+        let before = action.undoable().then(|| cards.pages.clone());
+        let inner = cards.cards_mut();
         match action {
             // card actions
             CardsHandler::Add(name) => {
                 inner.push(Card::new(name));
             }
             CardsHandler::Remove(index) => {
+                if index >= inner.len() {
+                    return false;
+                }
                 inner.remove(index);
             }
-            CardsHandler::Rename { card, new_name } => inner.get_mut(card).unwrap().name = new_name,
-            CardsHandler::Swap { card1, card2 } => inner.swap(card1, card2),
+            CardsHandler::Rename { card, new_name } => {
+                let Some(card) = inner.get_mut(card) else {
+                    return false;
+                };
+                card.name = new_name;
+            }
+            CardsHandler::Swap { card1, card2 } => {
+                if card1.max(card2) >= inner.len() {
+                    return false;
+                }
+                inner.swap(card1, card2);
+            }
+            CardsHandler::AddSmart { name, query } => inner.push(Card::smart(name, query)),
+            CardsHandler::Copy(mut card) => {
+                let schemes = urls::custom_schemes();
+                card.retain_links(&mut |link| urls::is_safe(&link.url, &schemes));
+                card.collapsed = false;
+                inner.push(card);
+            }
+            CardsHandler::SetQuery { card, query } => {
+                let Some(card) = inner.get_mut(card) else {
+                    return false;
+                };
+                card.query = Some(query);
+            }
+            CardsHandler::ToggleCollapse(card) => {
+                let Some(card) = inner.get_mut(card) else {
+                    return false;
+                };
+                card.collapsed = !card.collapsed;
+            }
+            CardsHandler::TogglePin(card) => {
+                let Some(card) = inner.get_mut(card) else {
+                    return false;
+                };
+                card.pinned = !card.pinned;
+            }
+            CardsHandler::SetDisplay { card, mode } => {
+                let Some(card) = inner.get_mut(card) else {
+                    return false;
+                };
+                card.display = mode;
+            }
+            CardsHandler::CollapseAll(collapsed) => {
+                inner.iter_mut().for_each(|card| card.collapsed = collapsed)
+            }
+            CardsHandler::RemoveUnsafe(schemes) => {
+                let cards = cards.pages.iter_mut().flat_map(|page| &mut page.cards);
+                cards.for_each(|card| {
+                    card.retain_links(&mut |link| urls::is_safe(&link.url, &schemes))
+                });
+            }
+            CardsHandler::MergeDuplicates {
+                keep: (keep_page, keep),
+                mut others,
+                merge,
+            } => {
+                // the kept link would be removed with the others
+                if others.contains(&(keep_page, keep.clone())) {
+                    return false;
+                }
+                let Some(mut kept) = cards.link(keep_page, &keep).cloned() else {
+                    return false;
+                };
+                let mut removed = Vec::new();
+                for (page, link) in &others {
+                    let Some(other) = cards.link(*page, link) else {
+                        return false;
+                    };
+                    removed.push(other.clone());
+                }
+                if merge {
+                    removed.iter().for_each(|other| kept.merge(other));
+                    *cards.link_mut(keep_page, &keep).unwrap() = kept;
+                }
+                // later positions first so the earlier ones stay valid
+                others.sort();
+                others.dedup();
+                for (page, LinkId { card, group, link }) in others.into_iter().rev() {
+                    let card = &mut cards.pages[page].cards[card];
+                    card.links_at_mut(&group).unwrap().remove(link);
+                }
+            }
+            CardsHandler::Reload => cards.reload(),
+            CardsHandler::Merge(doc) => {
+                cards.merge(&doc);
+                // undoing would take back the other devices' edits, and push that to them
+                cards.history.clear();
+            }
+            CardsHandler::Restore(pages) => {
+                if !pages.is_empty() {
+                    cards.pages = pages;
+                    cards.current = cards.current.min(cards.pages.len() - 1);
+                }
+            }
+            CardsHandler::Import(mut page) => {
+                let taken = |name: &str| cards.pages.iter().any(|page| page.name == name);
+                if taken(&page.name) {
+                    let base = page.name.clone();
+                    let n = (2..).find(|n| !taken(&format!("{base} ({n})"))).unwrap();
+                    page.name = format!("{base} ({n})");
+                }
+                let now = js_sys::Date::now();
+                for card in &mut page.cards {
+                    card.links_mut(&mut |link| {
+                        link.added.get_or_insert(now);
+                    });
+                }
+                cards.pages.push(page);
+                cards.current = cards.pages.len() - 1;
+            }
+            CardsHandler::RestoreCard { page, card } => {
+                let index = match cards.pages.iter().position(|p| p.name == page) {
+                    Some(index) => index,
+                    None => {
+                        cards.pages.push(Page::new(page));
+                        cards.pages.len() - 1
+                    }
+                };
+                let page = &mut cards.pages[index].cards;
+                match page.iter_mut().find(|c| c.name == card.name) {
+                    Some(old) => *old = card,
+                    None => page.push(card),
+                }
+            }
+            CardsHandler::Undo => {
+                if let Some(pages) = cards.history.pop() {
+                    cards.pages = pages;
+                    cards.current = cards.current.min(cards.pages.len() - 1);
+                }
+            }
             // link actions
-            CardsHandler::AddLink { card_index, link } => {
-                inner.get_mut(card_index).unwrap().push_link(link);
+            CardsHandler::AddLink {
+                card_index,
+                group,
+                mut link,
+            } => {
+                if !urls::is_safe(&link.url, &urls::custom_schemes()) {
+                    return false;
+                }
+                let card = inner.get_mut(card_index);
+                let Some(links) = card.and_then(|card| card.links_at_mut(&group)) else {
+                    return false;
+                };
+                link.added.get_or_insert_with(js_sys::Date::now);
+                links.push(link);
             }
-            CardsHandler::RemoveLink(LinkId { card, link }) => {
-                inner.get_mut(card).unwrap().remove_link(link);
+            CardsHandler::RemoveLink(LinkId { card, group, link }) => {
+                let card = inner.get_mut(card);
+                let Some(links) = card.and_then(|card| card.links_at_mut(&group)) else {
+                    return false;
+                };
+                if link >= links.len() {
+                    return false;
+                }
+                links.remove(link);
+            }
+            CardsHandler::RecordClick { page, link } => {
+                let Some(link) = cards.link_mut(page, &link) else {
+                    return false;
+                };
+                link.clicks += 1;
             }
             CardsHandler::EditLink {
-                link: LinkId { card, link },
+                link,
                 new_label,
                 new_url,
+                new_tags,
             } => {
-                let link = inner.get_mut(card).unwrap().links.get_mut(link).unwrap();
+                let Some(link) = cards.link_mut(cards.current, &link) else {
+                    return false;
+                };
                 if let Some(label) = new_label {
                     link.label = label;
                 }
-                if let Some(url) = new_url {
+                if let Some(url) = new_url.filter(|url| urls::is_safe(url, &urls::custom_schemes()))
+                {
                     link.url = url;
                 }
+                if let Some(tags) = new_tags {
+                    link.tags = tags;
+                }
+            }
+            CardsHandler::SetLinkImage { link, image } => {
+                let Some(link) = cards.link_mut(cards.current, &link) else {
+                    return false;
+                };
+                link.image = image.filter(|image| image.len() <= Anchor::MAX_IMAGE_SIZE);
+            }
+            CardsHandler::SetLinkDetails {
+                link,
+                description,
+                notes,
+                icon,
+                color,
+            } => {
+                let Some(link) = cards.link_mut(cards.current, &link) else {
+                    return false;
+                };
+                link.description = description;
+                link.notes = notes;
+                link.icon = icon;
+                link.color = color.as_deref().and_then(Anchor::parse_color);
+            }
+            CardsHandler::SwapLinks {
+                card,
+                group,
+                link1,
+                link2,
+            } => {
+                let card = inner.get_mut(card);
+                let Some(links) = card.and_then(|card| card.links_at_mut(&group)) else {
+                    return false;
+                };
+                if link1.max(link2) >= links.len() {
+                    return false;
+                }
+                links.swap(link1, link2);
+            }
+            // group actions
+            CardsHandler::AddGroup { card, parent, name } => {
+                if parent.len() >= Card::MAX_GROUP_DEPTH {
+                    return false;
+                }
+                let card = inner.get_mut(card);
+                let Some(groups) = card.and_then(|card| card.groups_at_mut(&parent)) else {
+                    return false;
+                };
+                groups.push(Group::new(name));
+            }
+            CardsHandler::RemoveGroup { card, mut group } => {
+                let Some(nth) = group.pop() else {
+                    return false;
+                };
+                let card = inner.get_mut(card);
+                let Some(groups) = card.and_then(|card| card.groups_at_mut(&group)) else {
+                    return false;
+                };
+                if nth >= groups.len() {
+                    return false;
+                }
+                groups.remove(nth);
+            }
+            CardsHandler::RenameGroup {
+                card,
+                group,
+                new_name,
+            } => {
+                let card = inner.get_mut(card);
+                let Some(group) = card.and_then(|card| card.group_mut(&group)) else {
+                    return false;
+                };
+                group.name = new_name;
+            }
+            CardsHandler::SwapGroups {
+                card,
+                parent,
+                group1,
+                group2,
+            } => {
+                let card = inner.get_mut(card);
+                let Some(groups) = card.and_then(|card| card.groups_at_mut(&parent)) else {
+                    return false;
+                };
+                if group1.max(group2) >= groups.len() {
+                    return false;
+                }
+                groups.swap(group1, group2);
+            }
+            // page actions
+            CardsHandler::AddPage(name) => {
+                cards.pages.push(Page::new(name));
+                cards.current = cards.pages.len() - 1;
+            }
+            CardsHandler::RemovePage(page) => {
+                // there is always at least one page
+                if cards.pages.len() < 2 || page >= cards.pages.len() {
+                    return false;
+                }
+                cards.pages.remove(page);
+                // the page shown stays shown, or the one after it if it was removed
+                if page < cards.current {
+                    cards.current -= 1;
+                }
+                cards.current = cards.current.min(cards.pages.len() - 1);
+            }
+            CardsHandler::RenamePage { page, new_name } => {
+                let Some(page) = cards.pages.get_mut(page) else {
+                    return false;
+                };
+                page.name = new_name;
             }
-            CardsHandler::SwapLinks { card, link1, link2 } => {
-                inner.get_mut(card).unwrap().links.swap(link1, link2);
+            CardsHandler::SwapPages { page1, page2 } => {
+                if page1.max(page2) >= cards.pages.len() {
+                    return false;
+                }
+                cards.pages.swap(page1, page2);
+                if cards.current == page1 {
+                    cards.current = page2;
+                } else if cards.current == page2 {
+                    cards.current = page1;
+                }
+            }
+            CardsHandler::SelectPage(page) => cards.select(page),
+            CardsHandler::MoveCard { card, page } => {
+                if page >= cards.pages.len() || card >= cards.cards().len() {
+                    return false;
+                }
+                let card = cards.cards_mut().remove(card);
+                cards.pages[page].cards.push(card);
             }
         }
-        let cards = Rc::new(Cards { inner });
-        LocalStorage::set("cards", Rc::clone(&cards).as_ref()).unwrap();
-        cards
+        if let Some(pages) = before {
+            if cards.history.len() == Cards::MAX_HISTORY {
+                cards.history.remove(0);
+            }
+            cards.history.push(pages);
+        }
+        true
     }
 }
 
@@ -143,7 +802,38 @@ pub struct CardsProviderProps {
 
 #[function_component(CardsProvider)]
 pub fn cards_provider(CardsProviderProps { children }: &CardsProviderProps) -> Html {
-    let cards = use_reducer(|| LocalStorage::get::<Cards>("cards").unwrap_or_default());
+    let cards = use_reducer(|| {
+        let mut cards = Cards::load();
+        // a page in the URL fragment (`#page=Work`) takes precedence over the last open one
+        let hash = web_sys::window()
+            .and_then(|window| window.location().hash().ok())
+            .unwrap_or_default();
+        let from_hash = hash
+            .strip_prefix("#page=")
+            .and_then(|page| js_sys::decode_uri_component(page).ok())
+            .and_then(|page| cards.find_page(&String::from(page)));
+        match from_hash {
+            Some(page) => cards.select(page),
+            None => cards.select(storage::get::<PageId>("page").unwrap_or_default()),
+        }
+        cards
+    });
+
+    // follow the edits made in other tabs, the revision is saved after the cards
+    {
+        let cards = cards.clone();
+        use_effect_with_deps(
+            move |_| {
+                let listener = storage::listen(move |name| {
+                    if name == "cards_rev" {
+                        cards.dispatch(CardsHandler::Reload);
+                    }
+                });
+                move || drop(listener)
+            },
+            (),
+        );
+    }
 
     html! {
         <ContextProvider<CardsContext> context={cards}>
@@ -151,3 +841,469 @@ pub fn cards_provider(CardsProviderProps { children }: &CardsProviderProps) -> H
         </ContextProvider<CardsContext>>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cards with a page per name, each with a card of the same name, showing `current`.
+    fn cards(pages: &[&str], current: PageId) -> Cards {
+        let mut cards = Cards {
+            pages: pages
+                .iter()
+                .map(|&name| Page {
+                    name: name.to_owned(),
+                    cards: vec![Card::new(name.to_owned())],
+                })
+                .collect(),
+            ..Cards::default()
+        };
+        cards.current = current;
+        cards
+    }
+
+    fn page_names(cards: &Cards) -> Vec<&str> {
+        cards.pages.iter().map(|page| page.name.as_str()).collect()
+    }
+
+    #[test]
+    fn removing_a_page_keeps_the_shown_one() {
+        let mut cards = cards(&["Home", "Work", "Music"], 2);
+        assert!(cards.apply(CardsHandler::RemovePage(0)));
+        assert_eq!(page_names(&cards), ["Work", "Music"]);
+        assert_eq!(cards.page().name, "Music");
+
+        // the shown page itself, the one after it is shown
+        let mut cards = self::cards(&["Home", "Work", "Music"], 1);
+        cards.apply(CardsHandler::RemovePage(1));
+        assert_eq!(cards.page().name, "Music");
+
+        // the last page, shown
+        cards.apply(CardsHandler::RemovePage(1));
+        assert_eq!(page_names(&cards), ["Home"]);
+        assert_eq!(cards.current, 0);
+
+        // the only page stays
+        assert!(!cards.apply(CardsHandler::RemovePage(0)));
+        assert_eq!(page_names(&cards), ["Home"]);
+        cards.apply(CardsHandler::Undo);
+        assert_eq!(page_names(&cards), ["Home", "Music"]);
+    }
+
+    #[test]
+    fn cards_move_between_pages() {
+        let mut cards = cards(&["Home", "Work"], 0);
+        cards.apply(CardsHandler::Add("News".to_owned()));
+        cards.apply(CardsHandler::Swap { card1: 0, card2: 1 });
+        let names: Vec<&str> = cards.cards().iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["News", "Home"]);
+
+        cards.apply(CardsHandler::MoveCard { card: 0, page: 1 });
+        assert_eq!(cards.cards().len(), 1);
+        let work: Vec<&str> = cards.pages[1]
+            .cards
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(work, ["Work", "News"]);
+
+        cards.apply(CardsHandler::SwapPages { page1: 0, page2: 1 });
+        assert_eq!(page_names(&cards), ["Work", "Home"]);
+        // the shown page follows
+        assert_eq!(cards.page().name, "Home");
+        cards.apply(CardsHandler::SelectPage(5));
+        assert_eq!(cards.current, 1);
+    }
+
+    fn link(name: &str) -> Anchor {
+        Anchor::new(name.to_owned(), format!("https://{name}.example.com/"))
+    }
+
+    #[test]
+    fn groups_nest_up_to_the_limit() {
+        let mut cards = cards(&["Home"], 0);
+        let add = |parent: GroupPath, name: &str| CardsHandler::AddGroup {
+            card: 0,
+            parent,
+            name: name.to_owned(),
+        };
+        cards.apply(add(vec![], "Dev"));
+        cards.apply(add(vec![], "Ops"));
+        cards.apply(add(vec![0], "Docs"));
+        cards.apply(add(vec![0, 0], "Too deep"));
+        let card = &mut cards.pages[0].cards[0];
+        card.group_mut(&[0, 0]).unwrap().links = vec![link("a"), link("b")];
+        assert!(card.group(&[0, 0]).unwrap().groups.is_empty());
+
+        cards.apply(CardsHandler::SwapLinks {
+            card: 0,
+            group: vec![0, 0],
+            link1: 0,
+            link2: 1,
+        });
+        cards.apply(CardsHandler::RemoveLink(LinkId {
+            card: 0,
+            group: vec![0, 0],
+            link: 1,
+        }));
+        let card = &cards.cards()[0];
+        let links: Vec<&str> = card
+            .all_links()
+            .iter()
+            .map(|l| l.2.label.as_str())
+            .collect();
+        assert_eq!(links, ["b"]);
+
+        cards.apply(CardsHandler::SwapGroups {
+            card: 0,
+            parent: vec![],
+            group1: 0,
+            group2: 1,
+        });
+        cards.apply(CardsHandler::RenameGroup {
+            card: 0,
+            group: vec![1, 0],
+            new_name: "Manuals".to_owned(),
+        });
+        let card = &cards.cards()[0];
+        assert_eq!(card.groups[0].name, "Ops");
+        assert_eq!(card.group(&[1, 0]).unwrap().name, "Manuals");
+
+        cards.apply(CardsHandler::RemoveGroup {
+            card: 0,
+            group: vec![1],
+        });
+        assert_eq!(cards.cards()[0].groups.len(), 1);
+        cards.apply(CardsHandler::Undo);
+        assert_eq!(cards.cards()[0].all_links().len(), 1);
+    }
+
+    #[test]
+    fn pinned_cards_come_first() {
+        let mut cards = cards(&["Home"], 0);
+        cards.apply(CardsHandler::Add("News".to_owned()));
+        cards.apply(CardsHandler::Add("Work".to_owned()));
+        cards.apply(CardsHandler::TogglePin(2));
+        assert_eq!(cards.display_order(), [2, 0, 1]);
+        cards.apply(CardsHandler::TogglePin(2));
+        assert_eq!(cards.display_order(), [0, 1, 2]);
+
+        cards.apply(CardsHandler::ToggleCollapse(1));
+        assert!(cards.cards()[1].collapsed);
+        cards.apply(CardsHandler::CollapseAll(true));
+        assert!(cards.cards().iter().all(|card| card.collapsed));
+        cards.apply(CardsHandler::CollapseAll(false));
+        assert!(cards.cards().iter().all(|card| !card.collapsed));
+    }
+
+    #[test]
+    fn tags_are_counted_on_every_page() {
+        let mut cards = cards(&["Home", "Work"], 0);
+        cards.pages[0].cards[0].links = vec![link("a"), link("b")];
+        cards.pages[1].cards[0].links = vec![link("c")];
+        let tag = |link, tags: &str| CardsHandler::EditLink {
+            link: LinkId {
+                card: 0,
+                group: vec![],
+                link,
+            },
+            new_label: None,
+            new_url: None,
+            new_tags: Some(Anchor::parse_tags(tags)),
+        };
+        cards.apply(tag(0, "ops, #Dev"));
+        cards.apply(tag(1, "dev,dev"));
+        cards.apply(CardsHandler::SelectPage(1));
+        cards.apply(tag(0, "ops"));
+        assert_eq!(cards.tags(), [("Dev".to_owned(), 2), ("ops".to_owned(), 2)]);
+        assert!(cards.pages[0].cards[0].has_tag("DEV"));
+        assert!(!cards.pages[1].cards[0].has_tag("dev"));
+    }
+
+    #[test]
+    fn smart_cards_show_the_results_of_their_query() {
+        let mut cards = cards(&["Home", "Work"], 1);
+        cards.pages[0].cards[0].links = vec![
+            Anchor {
+                tags: vec!["ops".to_owned()],
+                clicks: 3,
+                ..link("grafana")
+            },
+            Anchor {
+                added: Some(1000.0),
+                ..link("wiki")
+            },
+        ];
+        cards.apply(CardsHandler::AddSmart {
+            name: "On call".to_owned(),
+            query: "tag:#ops".parse().unwrap(),
+        });
+        let smart = &cards.cards()[1];
+        let results = smart.query.as_ref().unwrap().run(&cards.pages, 0.0);
+        assert_eq!(results.len(), 1);
+        assert_eq!((results[0].page, results[0].link.link), (0, 0));
+        assert!(smart.links.is_empty());
+
+        cards.apply(CardsHandler::SetQuery {
+            card: 1,
+            query: "url:wiki.*".parse().unwrap(),
+        });
+        let query = cards.cards()[1].query.clone().unwrap();
+        assert_eq!(query.to_string(), "url:wiki.*");
+        assert_eq!(query.run(&cards.pages, 0.0)[0].anchor.label, "wiki");
+        let recent: Query = "recent:1".parse().unwrap();
+        assert_eq!(recent.run(&cards.pages, 2000.0)[0].anchor.label, "wiki");
+        assert!("top:0".parse::<Query>().is_err());
+    }
+
+    #[test]
+    fn display_modes_are_set_per_card() {
+        let mut cards = cards(&["Home"], 0);
+        cards.apply(CardsHandler::Add("Apps".to_owned()));
+        cards.apply(CardsHandler::SetDisplay {
+            card: 1,
+            mode: DisplayMode::Tiles,
+        });
+        let modes: Vec<DisplayMode> = cards.cards().iter().map(|card| card.display).collect();
+        assert!(modes == [DisplayMode::List, DisplayMode::Tiles]);
+        for mode in DisplayMode::ALL {
+            assert!(mode.to_string().parse::<DisplayMode>() == Ok(mode));
+        }
+        assert!(DisplayMode::Tiles.next() == DisplayMode::List);
+    }
+
+    #[test]
+    fn duplicates_are_merged_into_the_kept_link() {
+        let mut cards = cards(&["Home", "Work"], 0);
+        let tagged = |url: &str, tag: &str, clicks| Anchor {
+            tags: vec![tag.to_owned()],
+            clicks,
+            ..Anchor::new(tag.to_owned(), url.to_owned())
+        };
+        cards.pages[0].cards[0].links =
+            vec![tagged("https://wiki.example.com/", "docs", 2), link("mail")];
+        let mut group = Group::new("Team".to_owned());
+        group.links = vec![tagged("http://www.wiki.example.com#intro", "team", 3)];
+        cards.pages[1].cards[0].groups.push(group);
+        cards.pages[1].cards[0].links = vec![tagged("https://WIKI.example.com", "docs", 1)];
+
+        let sets = cards.duplicates();
+        assert_eq!(sets.len(), 1);
+        let (keep, others) = sets[0].split_first().unwrap();
+        assert_eq!(others.len(), 2);
+        assert_eq!(keep.0, 0);
+        cards.apply(CardsHandler::MergeDuplicates {
+            keep: keep.clone(),
+            others: others.to_vec(),
+            merge: true,
+        });
+        assert!(cards.duplicates().is_empty());
+        let kept = &cards.pages[0].cards[0].links[0];
+        assert_eq!(kept.tags, ["docs", "team"]);
+        assert_eq!(kept.clicks, 6);
+        assert_eq!(cards.pages[1].cards[0].all_links().len(), 0);
+        assert_eq!(cards.pages[1].cards[0].groups.len(), 1);
+
+        cards.apply(CardsHandler::Undo);
+        assert_eq!(cards.duplicates()[0].len(), 3);
+        assert_eq!(cards.pages[0].cards[0].links[0].clicks, 2);
+
+        // without merging the others are only removed
+        let sets = cards.duplicates();
+        let (keep, others) = sets[0].split_first().unwrap();
+        assert!(!cards.apply(CardsHandler::MergeDuplicates {
+            keep: keep.clone(),
+            others: sets[0].clone(),
+            merge: false,
+        }));
+        assert_eq!(cards.duplicates()[0].len(), 3);
+        cards.apply(CardsHandler::MergeDuplicates {
+            keep: keep.clone(),
+            others: others.to_vec(),
+            merge: false,
+        });
+        assert_eq!(cards.pages[0].cards[0].links[0].tags, ["docs"]);
+        assert!(cards.duplicates().is_empty());
+    }
+
+    #[test]
+    fn link_details_are_set_and_checked() {
+        let mut cards = cards(&["Home"], 0);
+        cards.pages[0].cards[0].links = vec![link("wiki")];
+        let wiki = LinkId {
+            card: 0,
+            group: vec![],
+            link: 0,
+        };
+        cards.apply(CardsHandler::SetLinkDetails {
+            link: wiki.clone(),
+            description: Some("Team docs".to_owned()),
+            notes: Some("**On call** runbooks".to_owned()),
+            icon: Some("📚".to_owned()),
+            color: Some("#A0B".to_owned()),
+        });
+        let details = |cards: &Cards| cards.cards()[0].links[0].clone();
+        assert_eq!(details(&cards).description.as_deref(), Some("Team docs"));
+        assert_eq!(details(&cards).icon.as_deref(), Some("📚"));
+        assert_eq!(details(&cards).color.as_deref(), Some("#a0b"));
+
+        // colors that could escape the style attribute are dropped
+        cards.apply(CardsHandler::SetLinkDetails {
+            link: wiki.clone(),
+            description: None,
+            notes: None,
+            icon: None,
+            color: Some("red; background: url(x)".to_owned()),
+        });
+        assert_eq!(details(&cards).color, None);
+        assert_eq!(details(&cards).description, None);
+
+        let image = |size| Some(format!("data:image/png;base64,{}", "A".repeat(size)));
+        cards.apply(CardsHandler::SetLinkImage {
+            link: wiki.clone(),
+            image: image(16),
+        });
+        assert!(details(&cards).image.is_some());
+        cards.apply(CardsHandler::SetLinkImage {
+            link: wiki,
+            image: image(Anchor::MAX_IMAGE_SIZE),
+        });
+        assert_eq!(details(&cards).image, None);
+    }
+
+    #[test]
+    fn actions_on_items_that_are_gone_are_ignored() {
+        let mut cards = cards(&["Home"], 0);
+        cards.pages[0].cards[0].links = vec![link("a")];
+        let before = cards.pages.clone();
+        let gone = LinkId {
+            card: 0,
+            group: vec![],
+            link: 3,
+        };
+        let stale = [
+            CardsHandler::Remove(1),
+            CardsHandler::Swap { card1: 0, card2: 1 },
+            CardsHandler::TogglePin(2),
+            CardsHandler::RemoveLink(gone.clone()),
+            CardsHandler::RecordClick {
+                page: 1,
+                link: gone.clone(),
+            },
+            CardsHandler::SetLinkImage {
+                link: gone.clone(),
+                image: None,
+            },
+            CardsHandler::SwapLinks {
+                card: 0,
+                group: vec![],
+                link1: 0,
+                link2: 1,
+            },
+            CardsHandler::RemoveGroup {
+                card: 0,
+                group: vec![0],
+            },
+            CardsHandler::RenameGroup {
+                card: 0,
+                group: vec![1],
+                new_name: "Ops".to_owned(),
+            },
+            CardsHandler::RenamePage {
+                page: 1,
+                new_name: "Work".to_owned(),
+            },
+            CardsHandler::MoveCard { card: 0, page: 1 },
+            CardsHandler::MergeDuplicates {
+                keep: (0, gone.clone()),
+                others: vec![],
+                merge: true,
+            },
+        ];
+        for action in stale {
+            assert!(!cards.apply(action));
+        }
+        assert!(cards.pages == before);
+        assert!(cards.history.is_empty());
+    }
+
+    #[test]
+    fn merged_links_with_blocked_schemes_are_quarantined() {
+        let mut cards = Cards::default();
+        let mut pages = cards.pages.clone();
+        let mut card = Card::new("Notes".to_owned());
+        card.links = vec![
+            link("wiki"),
+            Anchor::new("Vault".to_owned(), "obsidian://open".to_owned()),
+        ];
+        pages[0].cards.push(card);
+        let mut remote = cards.doc.clone();
+        remote.update(&pages, 2);
+
+        assert!(cards.apply(CardsHandler::Merge(remote)));
+        assert_eq!(cards.cards()[0].links.len(), 2);
+        assert_eq!(cards.unsafe_links(&[]), 1);
+        assert_eq!(cards.unsafe_links(&["obsidian".to_owned()]), 0);
+    }
+
+    #[test]
+    fn sync_merges_are_not_undone() {
+        let mut cards = Cards::default();
+        let mut pages = cards.pages.clone();
+        let mut card = Card::new("Notes".to_owned());
+        card.links = vec![link("wiki"), link("mail")];
+        pages[0].cards.push(card);
+        cards.doc.update(&pages, 1);
+        cards.pages = pages;
+        // another device renames the card
+        let mut remote = cards.doc.clone();
+        let mut renamed = cards.pages.clone();
+        renamed[0].cards[0].name = "Docs".to_owned();
+        remote.update(&renamed, 2);
+
+        cards.apply(CardsHandler::RemoveLink(LinkId {
+            card: 0,
+            group: Vec::new(),
+            link: 1,
+        }));
+        assert_eq!(cards.history.len(), 1);
+        let pages = cards.pages.clone();
+        cards.doc.update(&pages, 1);
+
+        assert!(!CardsHandler::Merge(remote.clone()).undoable());
+        cards.apply(CardsHandler::Merge(remote));
+        assert!(cards.history.is_empty());
+        cards.apply(CardsHandler::Undo);
+        assert_eq!(cards.cards()[0].name, "Docs");
+        assert_eq!(cards.cards()[0].links.len(), 1);
+    }
+
+    #[test]
+    fn only_links_with_blocked_schemes_are_removed() {
+        let mut cards = cards(&["Home", "Work"], 0);
+        let stored = |url: &str| Anchor::new(url.to_owned(), url.to_owned());
+        cards.pages[0].cards[0].links = vec![
+            stored("javascript:alert(1)"),
+            stored("nas:5000"),
+            stored("localhost:8080/x"),
+        ];
+        let mut group = Group::new("Apps".to_owned());
+        group.links = vec![stored("obsidian://open"), stored("data:text/html,x")];
+        cards.pages[1].cards[0].groups.push(group);
+        assert_eq!(cards.unsafe_links(&[]), 3);
+        assert_eq!(cards.unsafe_links(&["obsidian".to_owned()]), 2);
+
+        cards.apply(CardsHandler::RemoveUnsafe(vec!["obsidian".to_owned()]));
+        let urls = |card: &Card| -> Vec<String> {
+            let links = card.all_links();
+            links.iter().map(|(_, _, link)| link.url.clone()).collect()
+        };
+        assert_eq!(
+            urls(&cards.pages[0].cards[0]),
+            ["nas:5000", "localhost:8080/x"]
+        );
+        assert_eq!(urls(&cards.pages[1].cards[0]), ["obsidian://open"]);
+        cards.apply(CardsHandler::Undo);
+        assert_eq!(cards.unsafe_links(&[]), 3);
+    }
+}
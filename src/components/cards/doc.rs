@@ -0,0 +1,177 @@
+//! The pages as a replicated document, so the edits of several tabs and devices can be
+//! merged, see [`crate::crdt`].
+//!
+//! Cards, groups, links and pages are ordered lists, their fields registers. Two replicas
+//! changing different cards keep both changes; when they change the same field, the last
+//! change wins.
+
+use serde::{Deserialize, Serialize};
+
+use crate::crdt::{replicated, Clock, Node, Register, Sequence};
+
+use super::{
+    cards_ctx::{Anchor, Card, Cards, DisplayMode, Group, Page},
+    query::Query,
+};
+
+replicated! {
+    struct AnchorNode for Anchor {
+        label: Register<String>,
+        url: Register<String>,
+        tags: Register<Vec<String>>,
+        added: Register<Option<f64>>,
+        clicks: Register<u32>,
+        description: Register<Option<String>>,
+        notes: Register<Option<String>>,
+        icon: Register<Option<String>>,
+        color: Register<Option<String>>,
+        image: Register<Option<String>>,
+    }
+}
+
+replicated! {
+    struct GroupNode for Group {
+        name: Register<String>,
+        links: Sequence<AnchorNode>,
+        groups: Sequence<GroupNode>,
+    }
+}
+
+replicated! {
+    struct CardNode for Card {
+        name: Register<String>,
+        links: Sequence<AnchorNode>,
+        groups: Sequence<GroupNode>,
+        collapsed: Register<bool>,
+        pinned: Register<bool>,
+        query: Register<Option<Query>>,
+        display: Register<DisplayMode>,
+    }
+}
+
+replicated! {
+    struct PageNode for Page {
+        name: Register<String>,
+        cards: Sequence<CardNode>,
+    }
+}
+
+/// Every edit made to the pages by any replica merged so far. Removed elements are kept, a
+/// replica may still be editing them.
+#[derive(Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Doc(Sequence<PageNode>);
+
+impl Doc {
+    /// The document of pages nobody edited yet. It is the same on every replica, so
+    /// devices starting from the same pages merge their edits instead of duplicating them.
+    pub fn new(pages: &[Page]) -> Self {
+        let mut doc = Self::default();
+        doc.update(pages, 0);
+        doc
+    }
+
+    /// The document of pages saved before there were documents. Only the empty default
+    /// page gets the document of [`Doc::new`]; other pages are edits of `replica`, as two
+    /// devices with different pages would otherwise write different values with the same
+    /// stamps.
+    pub fn migrate(pages: &[Page], replica: u64) -> Self {
+        if pages == [Page::new(Cards::DEFAULT_PAGE.to_owned())] {
+            return Self::new(pages);
+        }
+        let mut doc = Self::default();
+        doc.update(pages, replica);
+        doc
+    }
+
+    pub fn pages(&self) -> Vec<Page> {
+        self.0.value()
+    }
+
+    /// Records the edits made by `replica` to get to `pages`.
+    pub fn update(&mut self, pages: &[Page], replica: u64) {
+        let mut clock = Clock::new(replica, &self.0);
+        self.0.update(&pages.to_vec(), &mut clock);
+    }
+
+    /// Takes the edits of another replica.
+    pub fn merge(&mut self, other: &Doc) {
+        self.0.merge(&other.0);
+    }
+}
+
+thread_local! {
+    static REPLICA: u64 = (js_sys::Math::random() * u32::MAX as f64) as u64 + 1;
+}
+
+/// The replica of this tab, random so tabs and devices don't share it. 0 is kept for
+/// [`Doc::new`].
+pub fn replica() -> u64 {
+    REPLICA.with(|replica| *replica)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(name: &str, cards: &[&str]) -> Page {
+        Page {
+            name: name.to_owned(),
+            cards: cards
+                .iter()
+                .map(|&name| Card::new(name.to_owned()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn edits_of_different_cards_are_merged() {
+        let pages = vec![page("Home", &["News", "Work"])];
+        let (mut a, mut b) = (Doc::new(&pages), Doc::new(&pages));
+
+        let mut mine = pages.clone();
+        mine[0].cards[0]
+            .links
+            .push(Anchor::new("Example".into(), "https://example.com".into()));
+        a.update(&mine, 1);
+
+        let mut theirs = pages.clone();
+        theirs[0].cards[1].name = "Office".to_owned();
+        theirs[0].cards.push(Card::new("Music".to_owned()));
+        b.update(&theirs, 2);
+
+        a.merge(&b);
+        b.merge(&a);
+        let merged = a.pages();
+        assert!(merged == b.pages());
+        let names: Vec<&str> = merged[0].cards.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["News", "Office", "Music"]);
+        assert_eq!(merged[0].cards[0].links[0].url, "https://example.com");
+    }
+
+    #[test]
+    fn removals_win_over_edits_of_the_removed_card() {
+        let pages = vec![page("Home", &["News", "Work"])];
+        let (mut a, mut b) = (Doc::new(&pages), Doc::new(&pages));
+
+        a.update(&[page("Home", &["Work"])], 1);
+        b.update(&[page("Home", &["Daily news", "Work"])], 2);
+
+        a.merge(&b);
+        let names: Vec<String> = a.pages()[0].cards.iter().map(|c| c.name.clone()).collect();
+        assert_eq!(names, ["Work"]);
+    }
+
+    #[test]
+    fn different_legacy_pages_are_all_kept() {
+        let mut a = Doc::migrate(&[page("Home", &["News"])], 1);
+        let b = Doc::migrate(&[page("Home", &["Music", "Work"])], 2);
+        a.merge(&b);
+        let pages: Vec<String> = a.pages().into_iter().map(|page| page.name).collect();
+        assert_eq!(pages, ["Home", "Home"]);
+        assert_eq!(a.pages()[0].cards.len() + a.pages()[1].cards.len(), 3);
+
+        let default = [Page::new(Cards::DEFAULT_PAGE.to_owned())];
+        assert!(Doc::migrate(&default, 1) == Doc::new(&default));
+    }
+}
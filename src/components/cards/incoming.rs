@@ -0,0 +1,110 @@
+use wasm_bindgen::{closure::Closure, JsCast};
+use yew::{
+    classes, function_component, html, use_context, use_effect_with_deps, use_state, Callback, Html,
+};
+
+use crate::{
+    components::cards::{
+        cards_ctx::{Card, CardsContext, CardsHandler},
+        shared::links,
+    },
+    share, urls,
+};
+
+/// The card shared by the URL fragment, if any.
+fn from_location() -> Option<Result<Card, String>> {
+    let hash = web_sys::window()?.location().hash().ok()?;
+    share::parse(&hash, &urls::custom_schemes())
+}
+
+/// Previews a card shared with a link, see [`share`], and offers to add it to the current
+/// page.
+#[function_component(IncomingCard)]
+pub fn incoming_card() -> Html {
+    let cards = use_context::<CardsContext>().unwrap();
+    let incoming = use_state(from_location);
+
+    // a link opened in a tab already showing Foxhole only changes the fragment
+    {
+        let incoming = incoming.clone();
+        use_effect_with_deps(
+            move |_| {
+                let window = web_sys::window().unwrap();
+                let listener = Closure::<dyn Fn()>::new(move || incoming.set(from_location()));
+                window
+                    .add_event_listener_with_callback(
+                        "hashchange",
+                        listener.as_ref().unchecked_ref(),
+                    )
+                    .ok();
+                move || {
+                    window
+                        .remove_event_listener_with_callback(
+                            "hashchange",
+                            listener.as_ref().unchecked_ref(),
+                        )
+                        .ok();
+                }
+            },
+            (),
+        );
+    }
+
+    let close = {
+        let incoming = incoming.clone();
+        Callback::from(move |_| {
+            // so reloading doesn't offer the card again
+            web_sys::window().unwrap().location().set_hash("").ok();
+            incoming.set(None);
+        })
+    };
+
+    let Some(shared) = &*incoming else {
+        return Html::default();
+    };
+    let body = match shared {
+        Ok(card) => {
+            let add = {
+                let (cards, card, close) = (cards.clone(), card.clone(), close.clone());
+                Callback::from(move |_| {
+                    cards.dispatch(CardsHandler::Copy(card.clone()));
+                    close.emit(());
+                })
+            };
+            let count = card.all_links().len();
+            html! {
+                <>
+                    <div class={classes!("cards")}>
+                        <div class={classes!("card", "shared", card.display.class())}>
+                            <h3 class={classes!("card-name")}>{ &card.name }</h3>
+                            { links(&card.links, &card.groups, None) }
+                        </div>
+                    </div>
+                    <p class={classes!("details")}>
+                        { format!("{count} link(s). Links with a blocked URL scheme were left out.") }
+                    </p>
+                    <div class={classes!("buttons")}>
+                        <button onclick={add}>{ format!("Add to {}", cards.page().name) }</button>
+                        <button onclick={close.reform(|_| ())}>{ "Dismiss" }</button>
+                    </div>
+                </>
+            }
+        }
+        Err(err) => html! {
+            <>
+                <p>{ err }</p>
+                <div class={classes!("buttons")}>
+                    <button onclick={close.reform(|_| ())}>{ "Close" }</button>
+                </div>
+            </>
+        },
+    };
+    html! {
+        <div class={classes!("edit-screen")}>
+            <div class={classes!("edit-form", "settings", "incoming")}>
+                <h3>{ "A card was shared with you" }</h3>
+                { body }
+            </div>
+        </div>
+    }
+}
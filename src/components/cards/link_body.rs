@@ -0,0 +1,58 @@
+use crate::{
+    components::{
+        cards::{cards_ctx::Anchor, link_icon::LinkIcon, tags::TagChips},
+        markdown::Markdown,
+    },
+    urls, GlobalCtx,
+};
+use yew::{classes, function_component, html, use_context, Callback, Html, MouseEvent, Properties};
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct LinkBodyProps {
+    pub anchor: Anchor,
+    #[prop_or_default]
+    pub onclick: Callback<MouseEvent>,
+}
+
+/// A link with its icon, tags, description and notes. Links with an unsafe URL are
+/// quarantined: shown with a warning and without `href`.
+#[function_component(LinkBody)]
+pub fn link_body(LinkBodyProps { anchor, onclick }: &LinkBodyProps) -> Html {
+    let ctx = use_context::<GlobalCtx>().unwrap();
+    let Anchor {
+        label,
+        url,
+        tags,
+        description,
+        notes,
+        ..
+    } = anchor.clone();
+
+    html! {
+        <div class={classes!("link-body")}>
+            <span class={classes!("link-line")}>
+                if urls::is_safe(&url, &ctx.schemes) {
+                    <a href={urls::href(&url)} title={description.clone()} {onclick}>
+                        <LinkIcon anchor={anchor.clone()}/>
+                        <span class={classes!("label")}>{ label }</span>
+                    </a>
+                } else {
+                    <a class={classes!("unsafe")} title={format!("Blocked URL scheme: {url}")}>
+                        <span class={classes!("link-icon")}>{ "⚠" }</span>
+                        <span class={classes!("label")}>{ label }</span>
+                    </a>
+                }
+                <TagChips {tags}/>
+                if let Some(notes) = notes.clone() {
+                    <details class={classes!("notes")}>
+                        <summary title="Notes">{ "📝" }</summary>
+                        <Markdown source={notes}/>
+                    </details>
+                }
+            </span>
+            if let Some(description) = description {
+                <small class={classes!("description")}>{ description }</small>
+            }
+        </div>
+    }
+}
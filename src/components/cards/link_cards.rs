@@ -1,21 +1,42 @@
 use crate::{
     components::{
-        cards::cards_ctx::{Anchor, CardId, CardsContext, CardsHandler, CardsProvider, LinkId},
-        edit::{EditForm, Input},
+        cards::{
+            cards_ctx::{
+                Anchor, Card, CardId, CardsContext, CardsHandler, DisplayMode, GroupPath, LinkId,
+                Page,
+            },
+            incoming::IncomingCard,
+            link_list::{LinkList, SmartLinkList},
+            quarantine::QuarantineNotice,
+            query::Query,
+            save_notice::SaveNotice,
+            shared::SharedCards,
+            tags::{TagBar, TagView},
+        },
+        confirm::{ConfirmContext, Confirmation},
+        edit::{use_close_when_gone, Color, EditForm, Field, FormData, Key, Url},
+        SyncNotice,
     },
-    GlobalCtx,
+    share, GlobalCtx,
 };
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::HtmlSelectElement;
 use yew::{
     classes, function_component, html, use_callback, use_context, use_state_eq, AttrValue,
-    Callback, Html, Properties,
+    Callback, Event, Html, Properties, TargetCast,
 };
 
 #[function_component(LinkCards)]
 pub fn link_cards() -> Html {
     html! {
-        <CardsProvider>
+        <>
+            <SaveNotice />
+            <SyncNotice />
+            <QuarantineNotice />
+            <IncomingCard />
+            <TagBar />
             <CardList />
-        </CardsProvider>
+        </>
     }
 }
 
@@ -23,103 +44,308 @@ pub fn link_cards() -> Html {
 enum CardFormAct {
     Add,
     Rename(CardId),
+    AddGroup { card: CardId, parent: GroupPath },
+    RenameGroup { card: CardId, group: GroupPath },
+}
+
+#[derive(Clone, PartialEq)]
+enum SmartFormAct {
+    Add,
+    Edit(CardId),
 }
 
 #[derive(Clone, PartialEq)]
 enum LinkFormAct {
-    Add(CardId),
+    Add(CardId, GroupPath),
     Edit(LinkId),
 }
 
 #[function_component(CardList)]
 fn card_list() -> Html {
-    let hide_buttons = !use_context::<GlobalCtx>().unwrap().editable;
+    let ctx = use_context::<GlobalCtx>().unwrap();
+    let hide_buttons = !ctx.editable;
     let cards = use_context::<CardsContext>().unwrap();
 
     // card form
+    const NAME: Key<String> = Key::new("name");
+    const DISPLAY: Key<String> = Key::new("display");
+    const PINNED: Key<bool> = Key::new("pinned");
     let card_form_action = use_state_eq(|| None);
     let card_form_hide = use_state_eq(|| true);
 
-    let card_name = match *card_form_action {
-        Some(CardFormAct::Rename(index)) => cards.inner[index].name.clone(),
-        _ => String::default(),
+    let card_form = |card: Option<&Card>| {
+        let modes = DisplayMode::ALL.map(|mode| (mode.to_string(), mode.label()));
+        vec![
+            Field::text(NAME, "Card name:")
+                .value(card.map(|card| card.name.clone()).unwrap_or_default())
+                .required(),
+            Field::select(DISPLAY, "Display:", modes).value(
+                card.map(|card| card.display)
+                    .unwrap_or_default()
+                    .to_string(),
+            ),
+            Field::checkbox(PINNED, "Pinned:").checked(card.is_some_and(|card| card.pinned)),
+        ]
+    };
+    // `None` when the card or group is gone
+    let card_fields = match *card_form_action {
+        Some(CardFormAct::AddGroup { card, ref parent }) => cards
+            .cards()
+            .get(card)
+            .filter(|card| parent.is_empty() || card.group(parent).is_some())
+            .map(|_| vec![Field::text(NAME, "New group name:").required()]),
+        Some(CardFormAct::RenameGroup { card, ref group }) => cards
+            .cards()
+            .get(card)
+            .and_then(|card| card.group(group))
+            .map(|group| {
+                vec![Field::text(NAME, "New group name:")
+                    .value(group.name.clone())
+                    .required()]
+            }),
+        Some(CardFormAct::Rename(card)) => {
+            cards.cards().get(card).map(|card| card_form(Some(card)))
+        }
+        Some(CardFormAct::Add) | None => Some(card_form(None)),
     };
-    let input = Input::new(AttrValue::from("New card name:")).value(AttrValue::from(card_name));
-    let card_inputs = vec![input];
+    use_close_when_gone(
+        card_fields.is_none(),
+        card_form_action.clone(),
+        card_form_hide.clone(),
+    );
+    let card_fields = card_fields.unwrap_or_default();
 
     let change_card = {
-        let cards = cards.clone();
         let hidden = card_form_hide.clone();
         use_callback(
-            move |inputs_values: Option<Vec<String>>, form_action| {
-                if let (Some(values), Some(action)) = (inputs_values, (**form_action).clone()) {
-                    // don't allow empty name
-                    let name = match values.into_iter().next() {
-                        Some(val) if !val.is_empty() => val,
-                        _ => return,
-                    };
+            move |data: Option<FormData>, (form_action, cards)| {
+                if let (Some(data), Some(action)) = (data, (**form_action).clone()) {
+                    let name = data.get(NAME);
+                    let display = data.get(DISPLAY).parse().unwrap_or_default();
+                    let pinned = data.get(PINNED);
+
+                    match action {
+                        CardFormAct::Add => {
+                            // the new card goes last
+                            let card = cards.cards().len();
+                            cards.dispatch(CardsHandler::Add(name));
+                            cards.dispatch(CardsHandler::SetDisplay {
+                                card,
+                                mode: display,
+                            });
+                            if pinned {
+                                cards.dispatch(CardsHandler::TogglePin(card));
+                            }
+                        }
+                        CardFormAct::Rename(card) => {
+                            cards.dispatch(CardsHandler::Rename {
+                                card,
+                                new_name: name,
+                            });
+                            cards.dispatch(CardsHandler::SetDisplay {
+                                card,
+                                mode: display,
+                            });
+                            if cards.cards().get(card).is_some_and(|c| c.pinned != pinned) {
+                                cards.dispatch(CardsHandler::TogglePin(card));
+                            }
+                        }
+                        CardFormAct::AddGroup { card, parent } => {
+                            cards.dispatch(CardsHandler::AddGroup { card, parent, name })
+                        }
+                        CardFormAct::RenameGroup { card, group } => {
+                            cards.dispatch(CardsHandler::RenameGroup {
+                                card,
+                                group,
+                                new_name: name,
+                            })
+                        }
+                    }
+                }
+                form_action.set(None);
+                hidden.set(true);
+            },
+            (card_form_action.clone(), cards.clone()),
+        )
+    };
+
+    // smart card form
+    let smart_form_action = use_state_eq(|| None);
+    let smart_form_hide = use_state_eq(|| true);
 
-                    let op = match action {
-                        CardFormAct::Add => CardsHandler::Add(name),
-                        CardFormAct::Rename(id) => CardsHandler::Rename {
-                            card: id,
-                            new_name: name,
-                        },
+    let (smart_name, smart_query, smart_gone) = match *smart_form_action {
+        Some(SmartFormAct::Edit(index)) => match cards.cards().get(index) {
+            Some(card) => {
+                let query = card.query.as_ref().map(Query::to_string);
+                (card.name.clone(), query.unwrap_or_default(), false)
+            }
+            None => (String::default(), String::default(), true),
+        },
+        _ => (String::default(), String::default(), false),
+    };
+    use_close_when_gone(
+        smart_gone,
+        smart_form_action.clone(),
+        smart_form_hide.clone(),
+    );
+    const QUERY: Key<String> = Key::new("query");
+    let smart_fields = vec![
+        Field::text(NAME, "Card name:").value(smart_name).required(),
+        Field::text(QUERY, "Query:")
+            .value(smart_query)
+            .place_holder("tag:oncall, url:*.example.com, top:10 or recent:7")
+            .required()
+            .validate(|query| query.parse::<Query>().map(|_| ())),
+    ];
+
+    let change_smart = {
+        let cards = cards.clone();
+        let hidden = smart_form_hide.clone();
+        use_callback(
+            move |data: Option<FormData>, form_action| {
+                if let (Some(data), Some(action)) = (data, (**form_action).clone()) {
+                    let name = data.get(NAME);
+                    // already validated
+                    let Ok(query) = data.get(QUERY).parse::<Query>() else {
+                        return;
                     };
-                    cards.dispatch(op);
+
+                    match action {
+                        SmartFormAct::Add => cards.dispatch(CardsHandler::AddSmart { name, query }),
+                        SmartFormAct::Edit(card) => {
+                            cards.dispatch(CardsHandler::Rename {
+                                card,
+                                new_name: name,
+                            });
+                            cards.dispatch(CardsHandler::SetQuery { card, query });
+                        }
+                    }
                 }
                 form_action.set(None);
                 hidden.set(true);
             },
-            card_form_action.clone(),
+            smart_form_action.clone(),
         )
     };
 
     // link form
     let link_form_action = use_state_eq(|| None);
     let link_form_hide = use_state_eq(|| true);
-
-    let (link_label, link_url) = match *link_form_action {
-        Some(LinkFormAct::Edit(LinkId { card, link })) => {
-            let link = &cards.inner[card].links[link];
-            (link.label.clone(), link.url.clone())
+    let link_form_error = use_state_eq(|| None::<AttrValue>);
+    // URL the user was warned is already saved
+    let link_form_warned = use_state_eq(|| None::<String>);
+
+    let new = || Anchor::new(String::default(), String::default());
+    let (editing, link_gone) = match *link_form_action {
+        Some(LinkFormAct::Edit(ref link)) => match cards.link(cards.current, link) {
+            Some(anchor) => (anchor.clone(), false),
+            None => (new(), true),
+        },
+        Some(LinkFormAct::Add(card, ref group)) => {
+            let card = cards.cards().get(card);
+            (new(), card.and_then(|card| card.links_at(group)).is_none())
         }
-        _ => (String::default(), String::default()),
+        None => (new(), false),
     };
-    let label_input = Input::new(AttrValue::from("Label:")).value(AttrValue::from(link_label));
-    let url_input = Input::new(AttrValue::from("URL:")).value(AttrValue::from(link_url));
-    let link_inputs = vec![label_input, url_input];
+    use_close_when_gone(link_gone, link_form_action.clone(), link_form_hide.clone());
+    // an edited link keeps its URL when the field is left empty
+    const LABEL: Key<String> = Key::new("label");
+    const URL: Key<Option<Url>> = Key::new("url");
+    const TAGS: Key<Vec<String>> = Key::new("tags");
+    const DESCRIPTION: Key<String> = Key::new("description");
+    const NOTES: Key<String> = Key::new("notes");
+    const ICON: Key<String> = Key::new("icon");
+    const COLOR: Key<Option<Color>> = Key::new("color");
+    let mut url_field = Field::url(URL, "URL:").value(editing.url);
+    if let Some(LinkFormAct::Add(..)) = *link_form_action {
+        url_field = url_field.required();
+    }
+    let link_fields = vec![
+        Field::text(LABEL, "Label:").value(editing.label),
+        url_field,
+        Field::tags(TAGS, "Tags:")
+            .value(editing.tags.join(", "))
+            .place_holder("work, docs"),
+        Field::text(DESCRIPTION, "Description:").value(editing.description.unwrap_or_default()),
+        Field::textarea(NOTES, "Notes (Markdown):").value(editing.notes.unwrap_or_default()),
+        Field::text(ICON, "Icon:")
+            .value(editing.icon.unwrap_or_default())
+            .place_holder("🦊"),
+        Field::color(COLOR, "Color:")
+            .value(editing.color.unwrap_or_default())
+            .place_holder("#ff8f40"),
+    ];
 
     let change_link = {
-        let cards = cards.clone();
         let hidden = link_form_hide.clone();
+        let error = link_form_error.clone();
         use_callback(
-            move |input_values: Option<Vec<String>>, form_action| {
-                if let (Some(mut values), Some(action)) = (input_values, (**form_action).clone()) {
-                    let url = values.pop().unwrap_or_default();
-                    let label = values.pop().unwrap_or_default();
-
-                    if url.is_empty() && label.is_empty() {
-                        return;
+            move |data: Option<FormData>, (form_action, cards, warned)| {
+                if let (Some(data), Some(action)) = (data, (**form_action).clone()) {
+                    let label = data.get(LABEL);
+                    let url = data.get(URL).map(String::from).unwrap_or_default();
+                    let tags = data.get(TAGS);
+                    // empty optional fields are cleared
+                    let description = data.optional(DESCRIPTION);
+                    let notes = data.optional(NOTES);
+                    let icon = data.optional(ICON);
+                    let color = data.get(COLOR).map(String::from);
+
+                    // saving the same URL again after the warning adds it anyway
+                    if let LinkFormAct::Add(..) = action {
+                        let duplicate = cards.duplicates_of(&url).into_iter().next();
+                        if let Some((page, link)) =
+                            duplicate.filter(|_| **warned != Some(url.clone()))
+                        {
+                            let location = cards.location(page, &link);
+                            error.set(Some(AttrValue::from(format!(
+                                "Already saved in {location}, save again to add it anyway"
+                            ))));
+                            warned.set(Some(url));
+                            return;
+                        }
                     }
 
-                    let op = match action {
-                        LinkFormAct::Add(index) => CardsHandler::AddLink {
+                    match action {
+                        LinkFormAct::Add(index, group) => cards.dispatch(CardsHandler::AddLink {
                             card_index: index,
-                            link: Anchor { label, url },
-                        },
-                        LinkFormAct::Edit(link_id) => CardsHandler::EditLink {
-                            link: link_id,
-                            new_label: if label.is_empty() { None } else { Some(label) },
-                            new_url: if url.is_empty() { None } else { Some(url) },
-                        },
-                    };
-                    cards.dispatch(op);
+                            group,
+                            link: Anchor {
+                                tags,
+                                description,
+                                notes,
+                                icon,
+                                color,
+                                ..Anchor::new(label, url).with_default_label()
+                            },
+                        }),
+                        LinkFormAct::Edit(link_id) => {
+                            cards.dispatch(CardsHandler::EditLink {
+                                link: link_id.clone(),
+                                new_label: if label.is_empty() { None } else { Some(label) },
+                                new_url: if url.is_empty() { None } else { Some(url) },
+                                new_tags: Some(tags),
+                            });
+                            cards.dispatch(CardsHandler::SetLinkDetails {
+                                link: link_id,
+                                description,
+                                notes,
+                                icon,
+                                color,
+                            });
+                        }
+                    }
                 }
                 form_action.set(None);
+                error.set(None);
+                warned.set(None);
                 hidden.set(true);
             },
-            link_form_action.clone(),
+            (
+                link_form_action.clone(),
+                cards.clone(),
+                link_form_warned.clone(),
+            ),
         )
     };
 
@@ -133,8 +359,26 @@ fn card_list() -> Html {
         })
     };
 
+    let add_smart_form = {
+        let hidden = smart_form_hide.clone();
+        let form_action = smart_form_action.clone();
+        Callback::from(move |_| {
+            form_action.set(Some(SmartFormAct::Add));
+            hidden.set(false);
+        })
+    };
+
+    let edit_query = {
+        let hidden = smart_form_hide.clone();
+        let form_action = smart_form_action;
+        Callback::from(move |card_id| {
+            form_action.set(Some(SmartFormAct::Edit(card_id)));
+            hidden.set(false);
+        })
+    };
+
     let rename_card = {
-        let form_action = card_form_action;
+        let form_action = card_form_action.clone();
         let hidden = card_form_hide.clone();
         Callback::from(move |card_id| {
             form_action.set(Some(CardFormAct::Rename(card_id)));
@@ -142,12 +386,30 @@ fn card_list() -> Html {
         })
     };
 
+    let add_group = {
+        let form_action = card_form_action.clone();
+        let hidden = card_form_hide.clone();
+        Callback::from(move |(card, parent)| {
+            form_action.set(Some(CardFormAct::AddGroup { card, parent }));
+            hidden.set(false);
+        })
+    };
+
+    let rename_group = {
+        let form_action = card_form_action;
+        let hidden = card_form_hide.clone();
+        Callback::from(move |(card, group)| {
+            form_action.set(Some(CardFormAct::RenameGroup { card, group }));
+            hidden.set(false);
+        })
+    };
+
     // link form callbacks
     let add_link = {
         let form_action = link_form_action.clone();
         let hidden = link_form_hide.clone();
-        Callback::from(move |card_id| {
-            form_action.set(Some(LinkFormAct::Add(card_id)));
+        Callback::from(move |(card_id, group)| {
+            form_action.set(Some(LinkFormAct::Add(card_id, group)));
             hidden.set(false);
         })
     };
@@ -155,32 +417,53 @@ fn card_list() -> Html {
     let edit_link = {
         let form_action = link_form_action;
         let hidden = link_form_hide.clone();
-        Callback::from(move |(card, link)| {
-            form_action.set(Some(LinkFormAct::Edit(LinkId { card, link })));
+        Callback::from(move |link_id| {
+            form_action.set(Some(LinkFormAct::Edit(link_id)));
             hidden.set(false);
         })
     };
 
     // convert cards into Html
-    let cards: Html = (0..cards.inner.len())
+    let filter = ctx.tag_filter.clone();
+    let cards: Html = cards
+        .display_order()
+        .into_iter()
+        .filter(|&id| {
+            filter
+                .as_ref()
+                .is_none_or(|tag| cards.cards()[id].has_tag(tag))
+        })
         .map(|id| {
             html! {
                 <LinkCard key={format!("card{id}")} {id} rename_card={rename_card.clone()}
                     add_link={add_link.clone()} edit_link={edit_link.clone()}
+                    add_group={add_group.clone()} rename_group={rename_group.clone()}
+                    edit_query={edit_query.clone()}
                 />
             }
         })
         .collect();
 
+    // a tag view replaces the cards
+    let cards = match (&ctx.tag_filter, ctx.tag_view) {
+        (Some(tag), true) => html! { <TagView tag={tag.clone()}/> },
+        _ => cards,
+    };
+
     html! {
         <div class={classes!("cards")}>
             {cards}
+            <SharedCards />
             <div class={classes!("buttons")} hidden={hide_buttons}>
                 <button class={classes!("add-card","icon")} onclick={add_card_form}>{"+"}</button>
+                <button class={classes!("add-card","icon")} onclick={add_smart_form}
+                    title="Smart card">{"⚡"}</button>
             </div>
             <div class={classes!("forms")}>
-                <EditForm inputs={card_inputs} hidden={*card_form_hide} save={change_card}/>
-                <EditForm inputs={link_inputs} hidden={*link_form_hide} save={change_link}/>
+                <EditForm fields={card_fields} hidden={*card_form_hide} save={change_card}/>
+                <EditForm fields={link_fields} hidden={*link_form_hide} save={change_link}
+                    error={(*link_form_error).clone()}/>
+                <EditForm fields={smart_fields} hidden={*smart_form_hide} save={change_smart}/>
             </div>
         </div>
     }
@@ -190,22 +473,58 @@ fn card_list() -> Html {
 struct LinkCardProps {
     id: usize,
     rename_card: Callback<usize>,
-    add_link: Callback<usize>,
-    edit_link: Callback<(usize, usize)>,
+    add_link: Callback<(usize, GroupPath)>,
+    edit_link: Callback<LinkId>,
+    add_group: Callback<(usize, GroupPath)>,
+    rename_group: Callback<(usize, GroupPath)>,
+    edit_query: Callback<usize>,
 }
 
 #[function_component(LinkCard)]
 fn link_card(props: &LinkCardProps) -> Html {
     let id = props.id;
-    let hide_buttons = !use_context::<GlobalCtx>().unwrap().editable;
+    let ctx = use_context::<GlobalCtx>().unwrap();
+    let hide_buttons = !ctx.editable;
     let cards = use_context::<CardsContext>().unwrap();
-    let card_name = &cards.inner[id].name;
-    let links = cards.inner[id].links.clone();
+    let confirm = use_context::<ConfirmContext>().unwrap();
+    let card_name = &cards.cards()[id].name;
+    let collapsed = cards.cards()[id].collapsed;
+    let pinned = cards.cards()[id].pinned;
+    let query = cards.cards()[id].query.clone();
+    let display = cards.cards()[id].display;
+    let current_page = cards.current;
 
     // callbacks
     let rm_card = {
         let cards = cards.clone();
-        Callback::from(move |_| cards.dispatch(CardsHandler::Remove(id)))
+        let links = cards.cards()[id].all_links().len();
+        let ask = ctx.confirm.container(card_name, links);
+        let message = format!("Remove the card \"{card_name}\" and its {links} link(s)?");
+        Callback::from(move |_| {
+            let cards = cards.clone();
+            let remove = Callback::from(move |_| cards.dispatch(CardsHandler::Remove(id)));
+            confirm.emit(Confirmation::new(ask.clone(), message.clone(), remove));
+        })
+    };
+
+    let toggle_collapse = {
+        let cards = cards.clone();
+        Callback::from(move |_| cards.dispatch(CardsHandler::ToggleCollapse(id)))
+    };
+
+    let next_display = {
+        let cards = cards.clone();
+        Callback::from(move |_| {
+            cards.dispatch(CardsHandler::SetDisplay {
+                card: id,
+                mode: display.next(),
+            })
+        })
+    };
+
+    let toggle_pin = {
+        let cards = cards.clone();
+        Callback::from(move |_| cards.dispatch(CardsHandler::TogglePin(id)))
     };
 
     let rename_card = {
@@ -215,7 +534,17 @@ fn link_card(props: &LinkCardProps) -> Html {
 
     let add_link = {
         let add_link = props.add_link.clone();
-        Callback::from(move |_| add_link.emit(id))
+        Callback::from(move |_| add_link.emit((id, GroupPath::new())))
+    };
+
+    let add_group = {
+        let add_group = props.add_group.clone();
+        Callback::from(move |_| add_group.emit((id, GroupPath::new())))
+    };
+
+    let edit_query = {
+        let edit_query = props.edit_query.clone();
+        Callback::from(move |_| edit_query.emit(id))
     };
 
     let move_left = {
@@ -234,7 +563,7 @@ fn link_card(props: &LinkCardProps) -> Html {
     let move_right = {
         let cards = cards.clone();
         Callback::from(move |_| {
-            if id >= cards.inner.len() - 1 {
+            if id >= cards.cards().len() - 1 {
                 return;
             }
             cards.dispatch(CardsHandler::Swap {
@@ -244,85 +573,97 @@ fn link_card(props: &LinkCardProps) -> Html {
         })
     };
 
-    // links into Html
-    let links_size = links.len();
-    let links: Html = links
-        .into_iter()
-        .enumerate()
-        .map(|(link_id, link)| {
-            let Anchor { label, url } = link;
-
-            // link callbacks
-            let edit_link = {
-                let edit_link = props.edit_link.clone();
-                Callback::from(move |_| edit_link.emit((id, link_id)))
-            };
-
-            let rm_link = {
-                let cards = cards.clone();
-                Callback::from(move |_| {
-                    let action = CardsHandler::RemoveLink(LinkId {
-                        card: id,
-                        link: link_id,
-                    });
-                    cards.dispatch(action);
-                })
-            };
-
-            let move_up = {
-                let cards = cards.clone();
-                Callback::from(move |_| {
-                    if link_id == 0 {
-                        return;
-                    }
-                    cards.dispatch(CardsHandler::SwapLinks {
-                        card: id,
-                        link1: link_id,
-                        link2: link_id - 1,
-                    })
-                })
+    let shared = use_state_eq(|| false);
+    let share = {
+        let (card, shared) = (cards.cards()[id].clone(), shared.clone());
+        Callback::from(move |_| {
+            let window = web_sys::window().unwrap();
+            let url = match share::url(&card) {
+                Ok(url) => url,
+                Err(err) => {
+                    window.alert_with_message(&err).ok();
+                    return;
+                }
             };
-
-            let move_down = {
-                let cards = cards.clone();
-                Callback::from(move |_| {
-                    if link_id >= links_size - 1 {
-                        return;
+            let shared = shared.clone();
+            spawn_local(async move {
+                let copy = window.navigator().clipboard().write_text(&url);
+                match JsFuture::from(copy).await {
+                    Ok(_) => shared.set(true),
+                    // no clipboard without https, the user can still copy it by hand
+                    Err(_) => {
+                        window
+                            .prompt_with_message_and_default("Link to the card:", &url)
+                            .ok();
                     }
-                    cards.dispatch(CardsHandler::SwapLinks {
-                        card: id,
-                        link1: link_id,
-                        link2: link_id + 1,
-                    })
-                })
-            };
+                }
+            });
+        })
+    };
 
-            // link to html
-            html! {
-                <div class={classes!("card-link")}>
-                    <a key={format!("link{link_id}")} href={url}>{label}</a>
-                    <div class={classes!("buttons")} hidden={hide_buttons}>
-                        <button onclick={edit_link}>{ "Edit" }</button>
-                        <button onclick={move_up} class={classes!("icon")}>{ "↑" }</button>
-                        <button onclick={move_down} class={classes!("icon")}>{ "↓" }</button>
-                        <button onclick={rm_link} class={classes!("icon")}>{ "🗙" }</button>
-                    </div>
-                </div>
+    let move_to_page = {
+        let cards = cards.clone();
+        Callback::from(move |e: Event| {
+            let select = e.target_unchecked_into::<HtmlSelectElement>();
+            if let Ok(page) = select.value().parse() {
+                cards.dispatch(CardsHandler::MoveCard { card: id, page });
             }
+            select.set_value("");
         })
+    };
+    let page_options: Html = cards
+        .pages
+        .iter()
+        .enumerate()
+        .filter(|(page, _)| *page != current_page)
+        .map(|(page, Page { name, .. })| html! { <option value={page.to_string()}>{ name }</option> })
         .collect();
 
+    let card_class = classes!(
+        "card",
+        collapsed.then_some("collapsed"),
+        query.is_some().then_some("smart"),
+        display.class()
+    );
+
     html! {
-        <div class={classes!("card")}>
-            <h3 class={classes!("card-name")}>{ card_name }</h3>
-            <div class={classes!("links")}>
-                { links }
-            </div>
+        <div class={card_class}>
+            <h3 class={classes!("card-name")} onclick={toggle_collapse}>
+                <span class={classes!("collapse")}>{ if collapsed { "▸" } else { "▾" } }</span>
+                { card_name }
+                <span class={classes!("pin")} hidden={!pinned}>{ "📌" }</span>
+                <span class={classes!("badge")} hidden={query.is_none()}
+                    title={query.as_ref().map(Query::to_string)}>{ "⚡" }</span>
+            </h3>
+            if collapsed {
+            } else if let Some(query) = query.clone() {
+                <SmartLinkList {query}/>
+            } else {
+                <LinkList card={id} group={GroupPath::new()} add_link={props.add_link.clone()}
+                    edit_link={props.edit_link.clone()} add_group={props.add_group.clone()}
+                    rename_group={props.rename_group.clone()}
+                />
+            }
             <div class={classes!("buttons")} hidden={hide_buttons}>
                 <button onclick={move_left} class={classes!("icon")}>{ "❰" }</button>
-                <button onclick={add_link} >{ "🔗 Link" }</button>
-                <button onclick={rename_card}>{ "✏️ Rename" }</button>
+                if query.is_some() {
+                    <button onclick={edit_query}>{ "⚡ Query" }</button>
+                } else {
+                    <button onclick={add_link} >{ "🔗 Link" }</button>
+                    <button onclick={add_group} >{ "📁 Group" }</button>
+                    <button onclick={rename_card}>{ "✏️ Rename" }</button>
+                }
+                <button onclick={next_display} title="Display mode">{ display.label() }</button>
+                <button onclick={share} title="Copy a link to this card">
+                    { if *shared { "📤 Copied" } else { "📤 Share" } }
+                </button>
+                <button onclick={toggle_pin}>{ if pinned { "📌 Unpin" } else { "📌 Pin" } }</button>
                 <button onclick={rm_card}>{ "🗑️ Remove" }</button>
+                <select class={classes!("move-page")} onchange={move_to_page}
+                    hidden={cards.pages.len() < 2}>
+                    <option value="" selected=true>{ "📄 Move to…" }</option>
+                    { page_options }
+                </select>
                 <button onclick={move_right} class={classes!("icon")}>{ "❱" }</button>
             </div>
         </div>
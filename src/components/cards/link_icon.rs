@@ -0,0 +1,142 @@
+use crate::{components::cards::cards_ctx::Anchor, idb, GlobalCtx, Theme};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::Event;
+use yew::{
+    classes, function_component, html, use_context, use_effect_with_deps, use_state_eq, Callback,
+    Html, Properties,
+};
+
+/// How long to wait before trying again to load a favicon that wasn't available.
+const RETRY_MS: f64 = 7.0 * 24.0 * 60.0 * 60.0 * 1000.0;
+
+/// An origin whose favicon couldn't be loaded.
+#[derive(Serialize, Deserialize)]
+struct MissingFavicon {
+    /// When loading it failed, in milliseconds since the epoch.
+    checked: f64,
+}
+
+/// Whether loading the favicon of `origin` failed recently, see [`RETRY_MS`].
+async fn recently_missing(origin: &str) -> bool {
+    idb::get(idb::FAVICONS, origin)
+        .await
+        .ok()
+        .and_then(|value| value.as_string())
+        .and_then(|json| serde_json::from_str::<MissingFavicon>(&json).ok())
+        .is_some_and(|missing| js_sys::Date::now() - missing.checked < RETRY_MS)
+}
+
+async fn remember_missing(origin: &str) {
+    let missing = MissingFavicon {
+        checked: js_sys::Date::now(),
+    };
+    if let Ok(json) = serde_json::to_string(&missing) {
+        if let Err(err) = idb::put(idb::FAVICONS, origin, &JsValue::from_str(&json)).await {
+            web_sys::console::log_1(&err);
+        }
+    }
+}
+
+/// What is known of the favicon of a link.
+#[derive(Clone, Copy, PartialEq)]
+enum Favicon {
+    /// Looking whether it was missing the last time.
+    Checking,
+    /// Loaded by the browser, which caches it like any image.
+    Loading,
+    Missing,
+}
+
+/// FNV-1a, a small stable hash so a domain always gets the same color.
+fn hash(text: &str) -> u32 {
+    text.bytes().fold(0x811c9dc5, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x01000193)
+    })
+}
+
+/// A letter on a colored square, derived from the domain of the link.
+fn monogram(host: &str, theme: &Theme) -> Html {
+    let name = host.trim_start_matches("www.");
+    let letter = name
+        .chars()
+        .find(|c| c.is_alphanumeric())
+        .map_or_else(|| "?".to_owned(), |c| c.to_uppercase().to_string());
+    let palette = theme.palette();
+    let color = palette[hash(name) as usize % palette.len()];
+
+    html! {
+        <svg class={classes!("link-icon", "monogram")} viewBox="0 0 16 16" width="1em" height="1em">
+            <rect width="16" height="16" rx="3" fill={color}/>
+            <text x="8" y="12" text-anchor="middle" font-size="11" font-family="sans-serif"
+                font-weight="bold" fill={theme.background()}>
+                { letter }
+            </text>
+        </svg>
+    }
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct LinkIconProps {
+    pub anchor: Anchor,
+}
+
+/// The icon of a link: an uploaded image, its emoji, the site favicon or a monogram.
+#[function_component(LinkIcon)]
+pub fn link_icon(LinkIconProps { anchor }: &LinkIconProps) -> Html {
+    let theme = use_context::<GlobalCtx>().unwrap().theme.clone();
+    let favicon = use_state_eq(|| Favicon::Checking);
+
+    // only look for a favicon when nothing was picked by the user
+    let origin = match (&anchor.image, &anchor.icon) {
+        (None, None) => anchor.origin(),
+        _ => None,
+    };
+    {
+        let favicon = favicon.clone();
+        use_effect_with_deps(
+            move |origin| {
+                favicon.set(Favicon::Checking);
+                if let Some(origin) = origin.clone() {
+                    spawn_local(async move {
+                        let missing = recently_missing(&origin).await;
+                        favicon.set(if missing {
+                            Favicon::Missing
+                        } else {
+                            Favicon::Loading
+                        });
+                    });
+                }
+                || ()
+            },
+            origin.clone(),
+        );
+    }
+
+    match (&anchor.image, &anchor.icon, &origin) {
+        (Some(image), _, _) => html! {
+            <img class={classes!("link-icon")} src={image.clone()} alt=""/>
+        },
+        (None, Some(icon), _) => html! {
+            <span class={classes!("link-icon")}>{ icon }</span>
+        },
+        // an image can be loaded from any site, unlike a fetch that CORS would block
+        (None, None, Some(origin)) if *favicon == Favicon::Loading => {
+            let onerror = {
+                let favicon = favicon.clone();
+                let origin = origin.clone();
+                Callback::from(move |_: Event| {
+                    favicon.set(Favicon::Missing);
+                    let origin = origin.clone();
+                    spawn_local(async move { remember_missing(&origin).await });
+                })
+            };
+            html! {
+                <img class={classes!("link-icon")} src={format!("{origin}/favicon.ico")} alt=""
+                    referrerpolicy="no-referrer" {onerror}/>
+            }
+        }
+        (None, None, _) => monogram(anchor.host(), &theme),
+    }
+}
@@ -0,0 +1,343 @@
+use crate::{
+    components::{
+        cards::{
+            cards_ctx::{Anchor, Card, CardId, CardsContext, CardsHandler, GroupPath, LinkId},
+            link_body::LinkBody,
+            query::{Query, QueryResult},
+        },
+        confirm::{ConfirmContext, Confirmation},
+    },
+    GlobalCtx,
+};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::{
+    classes, function_component, html, use_context, Callback, Event, Html, Properties, TargetCast,
+};
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct LinkListProps {
+    pub card: CardId,
+    /// The group whose links are listed, empty for the links of the card itself.
+    pub group: GroupPath,
+    pub add_link: Callback<(CardId, GroupPath)>,
+    pub edit_link: Callback<LinkId>,
+    pub add_group: Callback<(CardId, GroupPath)>,
+    pub rename_group: Callback<(CardId, GroupPath)>,
+}
+
+/// The links of a card or group followed by its subgroups as collapsible sections.
+#[function_component(LinkList)]
+pub fn link_list(props: &LinkListProps) -> Html {
+    let LinkListProps { card, group, .. } = props.clone();
+    let ctx = use_context::<GlobalCtx>().unwrap();
+    let hide_buttons = !ctx.editable;
+    let filter = ctx.tag_filter.clone();
+    let cards = use_context::<CardsContext>().unwrap();
+    let confirm = use_context::<ConfirmContext>().unwrap();
+    let links = cards.cards()[card].links_at(&group).unwrap().clone();
+    let groups = match group.as_slice() {
+        [] => cards.cards()[card].groups.clone(),
+        path => cards.cards()[card].group(path).unwrap().groups.clone(),
+    };
+
+    // links into Html
+    let links_size = links.len();
+    let links: Html = links
+        .into_iter()
+        .enumerate()
+        .filter(|(_, link)| filter.as_ref().is_none_or(|tag| link.has_tag(tag)))
+        .map(|(link_id, link)| {
+            let color = link.color.as_deref().and_then(Anchor::parse_color);
+            let color = color.map(|color| format!("--link-color: {color}"));
+            let id = LinkId {
+                card,
+                group: group.clone(),
+                link: link_id,
+            };
+
+            // link callbacks
+            let record_click = {
+                let cards = cards.clone();
+                let link = id.clone();
+                Callback::from(move |_| {
+                    cards.dispatch(CardsHandler::RecordClick {
+                        page: cards.current,
+                        link: link.clone(),
+                    })
+                })
+            };
+
+            let edit_link = {
+                let edit_link = props.edit_link.clone();
+                let id = id.clone();
+                Callback::from(move |_| edit_link.emit(id.clone()))
+            };
+
+            let upload_image = {
+                let cards = cards.clone();
+                let id = id.clone();
+                Callback::from(move |e: Event| {
+                    let input = e.target_unchecked_into::<HtmlInputElement>();
+                    let file = input.files().and_then(|files| files.get(0));
+                    input.set_value("");
+                    let Some(file) = file else { return };
+                    let cards = cards.clone();
+                    let link = id.clone();
+                    spawn_local(async move {
+                        match gloo_file::futures::read_as_data_url(&file.into()).await {
+                            Ok(image) if image.len() <= Anchor::MAX_IMAGE_SIZE => {
+                                cards.dispatch(CardsHandler::SetLinkImage {
+                                    link,
+                                    image: Some(image),
+                                })
+                            }
+                            Ok(_) => web_sys::console::log_1(&"Icon image is too large".into()),
+                            Err(err) => web_sys::console::log_1(&format!("{err}").into()),
+                        }
+                    });
+                })
+            };
+
+            let rm_image = {
+                let cards = cards.clone();
+                let link = id.clone();
+                Callback::from(move |_| {
+                    cards.dispatch(CardsHandler::SetLinkImage {
+                        link: link.clone(),
+                        image: None,
+                    })
+                })
+            };
+            let has_image = link.image.is_some();
+
+            let rm_link = {
+                let cards = cards.clone();
+                let confirm = confirm.clone();
+                let ask = ctx.confirm.link();
+                let message = format!("Remove the link \"{}\"?", link.label);
+                Callback::from(move |_| {
+                    let cards = cards.clone();
+                    let id = id.clone();
+                    let remove =
+                        Callback::from(move |_| cards.dispatch(CardsHandler::RemoveLink(id.clone())));
+                    confirm.emit(Confirmation::new(ask.clone(), message.clone(), remove));
+                })
+            };
+
+            let move_up = {
+                let cards = cards.clone();
+                let group = group.clone();
+                Callback::from(move |_| {
+                    if link_id == 0 {
+                        return;
+                    }
+                    cards.dispatch(CardsHandler::SwapLinks {
+                        card,
+                        group: group.clone(),
+                        link1: link_id,
+                        link2: link_id - 1,
+                    })
+                })
+            };
+
+            let move_down = {
+                let cards = cards.clone();
+                let group = group.clone();
+                Callback::from(move |_| {
+                    if link_id >= links_size - 1 {
+                        return;
+                    }
+                    cards.dispatch(CardsHandler::SwapLinks {
+                        card,
+                        group: group.clone(),
+                        link1: link_id,
+                        link2: link_id + 1,
+                    })
+                })
+            };
+
+            // link to html
+            html! {
+                <div key={format!("link{link_id}")} style={color.clone()}
+                    class={classes!("card-link", color.is_some().then_some("highlight"))}>
+                    <LinkBody anchor={link} onclick={record_click}/>
+                    <div class={classes!("buttons")} hidden={hide_buttons}>
+                        <button onclick={edit_link}>{ "Edit" }</button>
+                        <label class={classes!("upload")} title="Upload icon">
+                            <input type="file" accept="image/*" hidden=true onchange={upload_image}/>
+                            { "🖼" }
+                        </label>
+                        <button onclick={rm_image} hidden={!has_image} title="Remove icon">
+                            { "🚫" }
+                        </button>
+                        <button onclick={move_up} class={classes!("icon")}>{ "↑" }</button>
+                        <button onclick={move_down} class={classes!("icon")}>{ "↓" }</button>
+                        <button onclick={rm_link} class={classes!("icon")}>{ "🗙" }</button>
+                    </div>
+                </div>
+            }
+        })
+        .collect();
+
+    // groups into Html
+    let groups_size = groups.len();
+    let groups: Html = groups
+        .into_iter()
+        .enumerate()
+        .filter(|(_, group)| filter.as_ref().is_none_or(|tag| group.has_tag(tag)))
+        .map(|(group_id, subgroup)| {
+            let name = subgroup.name.clone();
+            let mut path = group.clone();
+            path.push(group_id);
+            let can_nest = path.len() < Card::MAX_GROUP_DEPTH;
+
+            // group callbacks
+            let add_link = {
+                let add_link = props.add_link.clone();
+                let path = path.clone();
+                Callback::from(move |_| add_link.emit((card, path.clone())))
+            };
+
+            let add_group = {
+                let add_group = props.add_group.clone();
+                let path = path.clone();
+                Callback::from(move |_| add_group.emit((card, path.clone())))
+            };
+
+            let rename_group = {
+                let rename_group = props.rename_group.clone();
+                let path = path.clone();
+                Callback::from(move |_| rename_group.emit((card, path.clone())))
+            };
+
+            let rm_group = {
+                let cards = cards.clone();
+                let confirm = confirm.clone();
+                let path = path.clone();
+                let links = subgroup.link_count();
+                let ask = ctx.confirm.container(&name, links);
+                let message = format!("Remove the group \"{name}\" and its {links} link(s)?");
+                Callback::from(move |_| {
+                    let cards = cards.clone();
+                    let path = path.clone();
+                    let remove = Callback::from(move |_| {
+                        cards.dispatch(CardsHandler::RemoveGroup {
+                            card,
+                            group: path.clone(),
+                        })
+                    });
+                    confirm.emit(Confirmation::new(ask.clone(), message.clone(), remove));
+                })
+            };
+
+            let move_up = {
+                let cards = cards.clone();
+                let parent = group.clone();
+                Callback::from(move |_| {
+                    if group_id == 0 {
+                        return;
+                    }
+                    cards.dispatch(CardsHandler::SwapGroups {
+                        card,
+                        parent: parent.clone(),
+                        group1: group_id,
+                        group2: group_id - 1,
+                    })
+                })
+            };
+
+            let move_down = {
+                let cards = cards.clone();
+                let parent = group.clone();
+                Callback::from(move |_| {
+                    if group_id >= groups_size - 1 {
+                        return;
+                    }
+                    cards.dispatch(CardsHandler::SwapGroups {
+                        card,
+                        parent: parent.clone(),
+                        group1: group_id,
+                        group2: group_id + 1,
+                    })
+                })
+            };
+
+            html! {
+                <details key={format!("group{group_id}")} class={classes!("group")} open=true>
+                    <summary>
+                        <span class={classes!("group-name")}>{ name }</span>
+                        <span class={classes!("buttons")} hidden={hide_buttons}>
+                            <button onclick={add_link}>{ "🔗" }</button>
+                            <button onclick={add_group} hidden={!can_nest}>{ "📁" }</button>
+                            <button onclick={rename_group}>{ "✏️" }</button>
+                            <button onclick={move_up} class={classes!("icon")}>{ "↑" }</button>
+                            <button onclick={move_down} class={classes!("icon")}>{ "↓" }</button>
+                            <button onclick={rm_group} class={classes!("icon")}>{ "🗙" }</button>
+                        </span>
+                    </summary>
+                    <LinkList {card} group={path} add_link={props.add_link.clone()}
+                        edit_link={props.edit_link.clone()} add_group={props.add_group.clone()}
+                        rename_group={props.rename_group.clone()}
+                    />
+                </details>
+            }
+        })
+        .collect();
+
+    html! {
+        <>
+            <div class={classes!("links")}>
+                { links }
+            </div>
+            { groups }
+        </>
+    }
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct SmartLinkListProps {
+    pub query: Query,
+}
+
+/// The read-only results of a smart card query.
+#[function_component(SmartLinkList)]
+pub fn smart_link_list(SmartLinkListProps { query }: &SmartLinkListProps) -> Html {
+    let cards = use_context::<CardsContext>().unwrap();
+    let results = query.run(&cards.pages, js_sys::Date::now());
+
+    let links: Html = results
+        .into_iter()
+        .enumerate()
+        .map(|(i, QueryResult { page, link, anchor })| {
+            let color = anchor.color.as_deref().and_then(Anchor::parse_color);
+            let color = color.map(|color| format!("--link-color: {color}"));
+            let source = {
+                let page = &cards.pages[page];
+                format!("{} › {}", page.name, page.cards[link.card].name)
+            };
+            let record_click = {
+                let cards = cards.clone();
+                Callback::from(move |_| {
+                    cards.dispatch(CardsHandler::RecordClick {
+                        page,
+                        link: link.clone(),
+                    })
+                })
+            };
+
+            html! {
+                <div key={format!("link{i}")} title={source} style={color.clone()}
+                    class={classes!("card-link", color.is_some().then_some("highlight"))}>
+                    <LinkBody {anchor} onclick={record_click}/>
+                </div>
+            }
+        })
+        .collect();
+
+    html! {
+        <div class={classes!("links")}>
+            { links }
+        </div>
+    }
+}
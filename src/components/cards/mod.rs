@@ -1,4 +1,17 @@
 pub mod cards_ctx;
+pub mod doc;
+mod incoming;
+mod link_body;
 pub mod link_cards;
+mod link_icon;
+mod link_list;
+mod page_tabs;
+mod quarantine;
+mod save_notice;
+mod shared;
+mod tags;
 
+pub use cards_ctx::CardsProvider;
+pub use foxhole_model::query;
 pub use link_cards::LinkCards;
+pub use page_tabs::PageTabs;
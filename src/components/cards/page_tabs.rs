@@ -0,0 +1,167 @@
+use yew::{
+    classes, function_component, html, use_callback, use_context, use_state_eq, Callback, Html,
+};
+
+use crate::{
+    components::{
+        cards::cards_ctx::{CardsContext, CardsHandler, Page, PageId},
+        confirm::{ConfirmContext, Confirmation},
+        edit::{use_close_when_gone, EditForm, Field, FormData, Key},
+    },
+    policy::Ask,
+    GlobalCtx,
+};
+
+#[derive(Clone, PartialEq)]
+enum PageFormAct {
+    Add,
+    Rename(PageId),
+}
+
+#[function_component(PageTabs)]
+pub fn page_tabs() -> Html {
+    let ctx = use_context::<GlobalCtx>().unwrap();
+    let hide_buttons = !ctx.editable;
+    let cards = use_context::<CardsContext>().unwrap();
+    let confirm = use_context::<ConfirmContext>().unwrap();
+    let current = cards.current;
+
+    // page form
+    let form_action = use_state_eq(|| None);
+    let form_hide = use_state_eq(|| true);
+
+    let (page_name, gone) = match *form_action {
+        Some(PageFormAct::Rename(page)) => match cards.pages.get(page) {
+            Some(page) => (page.name.clone(), false),
+            None => (String::default(), true),
+        },
+        _ => (String::default(), false),
+    };
+    use_close_when_gone(gone, form_action.clone(), form_hide.clone());
+    const NAME: Key<String> = Key::new("name");
+    let fields = vec![Field::text(NAME, "Page name:").value(page_name).required()];
+
+    let change_page = {
+        let cards = cards.clone();
+        let hidden = form_hide.clone();
+        use_callback(
+            move |data: Option<FormData>, form_action| {
+                if let (Some(data), Some(action)) = (data, (**form_action).clone()) {
+                    let name = data.get(NAME);
+
+                    let op = match action {
+                        PageFormAct::Add => CardsHandler::AddPage(name),
+                        PageFormAct::Rename(page) => CardsHandler::RenamePage {
+                            page,
+                            new_name: name,
+                        },
+                    };
+                    cards.dispatch(op);
+                }
+                form_action.set(None);
+                hidden.set(true);
+            },
+            form_action.clone(),
+        )
+    };
+
+    // page callbacks
+    let add_page = {
+        let form_action = form_action.clone();
+        let hidden = form_hide.clone();
+        Callback::from(move |_| {
+            form_action.set(Some(PageFormAct::Add));
+            hidden.set(false);
+        })
+    };
+
+    let rename_page = {
+        let form_action = form_action;
+        let hidden = form_hide.clone();
+        Callback::from(move |_| {
+            form_action.set(Some(PageFormAct::Rename(current)));
+            hidden.set(false);
+        })
+    };
+
+    let rm_page = {
+        let cards = cards.clone();
+        let page = cards.page();
+        let ask = match page.cards.len() {
+            0 => Ask::Nothing,
+            _ => ctx.confirm.bulk(),
+        };
+        let message = format!(
+            "Remove the page \"{}\" with its {} card(s)?",
+            page.name,
+            page.cards.len()
+        );
+        Callback::from(move |_| {
+            let cards = cards.clone();
+            let remove = Callback::from(move |_| cards.dispatch(CardsHandler::RemovePage(current)));
+            confirm.emit(Confirmation::new(ask.clone(), message.clone(), remove));
+        })
+    };
+
+    let move_left = {
+        let cards = cards.clone();
+        Callback::from(move |_| {
+            if current == 0 {
+                return;
+            }
+            cards.dispatch(CardsHandler::SwapPages {
+                page1: current,
+                page2: current - 1,
+            })
+        })
+    };
+
+    let move_right = {
+        let cards = cards.clone();
+        Callback::from(move |_| {
+            if current >= cards.pages.len() - 1 {
+                return;
+            }
+            cards.dispatch(CardsHandler::SwapPages {
+                page1: current,
+                page2: current + 1,
+            })
+        })
+    };
+
+    // pages into Html
+    let tabs: Html = cards
+        .pages
+        .iter()
+        .enumerate()
+        .map(|(page, Page { name, .. })| {
+            let select = {
+                let cards = cards.clone();
+                Callback::from(move |_| cards.dispatch(CardsHandler::SelectPage(page)))
+            };
+            let active = (page == current).then_some("active");
+            html! {
+                <button key={format!("page{page}")} class={classes!("tab", active)} onclick={select}>
+                    { name }
+                </button>
+            }
+        })
+        .collect();
+
+    // a single page needs no tabs unless it is being edited
+    let hide_tabs = hide_buttons && cards.pages.len() < 2;
+
+    html! {
+        <div class={classes!("tabs")}>
+            <div hidden={hide_tabs}>{ tabs }</div>
+            <div class={classes!("buttons")} hidden={hide_buttons}>
+                <button onclick={move_left} class={classes!("icon")}>{ "❰" }</button>
+                <button onclick={add_page}>{ "📄 Page" }</button>
+                <button onclick={rename_page}>{ "✏️ Rename" }</button>
+                <button onclick={rm_page} disabled={cards.pages.len() < 2}>{ "🗑️ Remove" }</button>
+                <button onclick={move_right} class={classes!("icon")}>{ "❱" }</button>
+            </div>
+            <EditForm {fields} hidden={*form_hide} save={change_page}/>
+        </div>
+    }
+}
@@ -0,0 +1,49 @@
+use crate::{
+    components::{
+        cards::cards_ctx::{CardsContext, CardsHandler},
+        confirm::{ConfirmContext, Confirmation},
+    },
+    GlobalCtx,
+};
+use yew::{classes, function_component, html, use_context, use_state_eq, Callback, Html};
+
+/// Warns about stored links whose URL scheme is blocked, these are shown without `href`
+/// until removed or their scheme is approved. Links merged from other devices are included,
+/// the warning shows again when more arrive after it was dismissed.
+#[function_component(QuarantineNotice)]
+pub fn quarantine_notice() -> Html {
+    let ctx = use_context::<GlobalCtx>().unwrap();
+    let cards = use_context::<CardsContext>().unwrap();
+    let confirm = use_context::<ConfirmContext>().unwrap();
+    // how many there were when dismissed
+    let dismissed = use_state_eq(|| 0);
+    let count = cards.unsafe_links(&ctx.schemes);
+
+    let remove = {
+        let cards = cards.clone();
+        let ask = ctx.confirm.bulk();
+        let schemes = ctx.schemes.clone();
+        Callback::from(move |_| {
+            let (cards, schemes) = (cards.clone(), schemes.clone());
+            let remove = Callback::from(move |_| {
+                cards.dispatch(CardsHandler::RemoveUnsafe(schemes.clone()))
+            });
+            let message = format!("Remove the {count} link(s) with a blocked URL scheme?");
+            confirm.emit(Confirmation::new(ask.clone(), message, remove));
+        })
+    };
+    let dismiss = {
+        let dismissed = dismissed.clone();
+        Callback::from(move |_| dismissed.set(count))
+    };
+
+    html! {
+        <div class={classes!("notice")} hidden={count <= *dismissed}>
+            <span>
+                { format!("⚠ {count} link(s) use a blocked URL scheme and were disabled.") }
+            </span>
+            <button onclick={remove}>{ "Remove them" }</button>
+            <button onclick={dismiss} class={classes!("icon")}>{ "🗙" }</button>
+        </div>
+    }
+}
@@ -0,0 +1,22 @@
+use yew::{classes, function_component, html, use_context, Html};
+
+use super::cards_ctx::CardsContext;
+
+/// Warns when the cards can't be saved, the browser storage may be full.
+#[function_component(SaveNotice)]
+pub fn save_notice() -> Html {
+    let cards = use_context::<CardsContext>().unwrap();
+    let Some(err) = &cards.unsaved else {
+        return Html::default();
+    };
+    html! {
+        <div class={classes!("notice")}>
+            <span>
+                { format!(
+                    "⚠ Your changes can't be saved ({err}). Remove large images or pages you \
+                     don't need, then try again."
+                ) }
+            </span>
+        </div>
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use js_sys::Function;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::spawn_local;
+use yew::{
+    classes, function_component, html, use_context, use_effect_with_deps, use_state, use_state_eq,
+    Callback, Html,
+};
+
+use crate::{
+    collections::{self, Subscription},
+    components::cards::{
+        cards_ctx::{Anchor, CardsContext, CardsHandler, Group},
+        link_body::LinkBody,
+        link_list::SmartLinkList,
+    },
+    GlobalCtx,
+};
+
+/// How often the collections are loaded again, in milliseconds.
+const REFRESH_EVERY: i32 = 15 * 60 * 1000;
+
+/// The cards of the subscribed collections on the current page, read-only. They can be
+/// copied to the personal cards.
+#[function_component(SharedCards)]
+pub fn shared_cards() -> Html {
+    let ctx = use_context::<GlobalCtx>().unwrap();
+    let cards = use_context::<CardsContext>().unwrap();
+    let loaded = use_state(collections::cached);
+    let errors = use_state_eq(HashMap::<String, String>::new);
+    let unsaved_error = use_state_eq(|| None::<String>);
+
+    use_effect_with_deps(
+        {
+            let (loaded, errors, unsaved_error) =
+                (loaded.clone(), errors.clone(), unsaved_error.clone());
+            move |subscriptions: &Vec<Subscription>| {
+                let subscriptions = subscriptions.clone();
+                let refresh = Closure::<dyn Fn()>::new(move || {
+                    let (subscriptions, loaded, errors, unsaved_error) = (
+                        subscriptions.clone(),
+                        loaded.clone(),
+                        errors.clone(),
+                        unsaved_error.clone(),
+                    );
+                    spawn_local(async move {
+                        let mut pages = (*loaded).clone();
+                        let mut failed = HashMap::new();
+                        for Subscription { url, .. } in &subscriptions {
+                            match collections::fetch(url).await {
+                                Ok(fetched) => {
+                                    pages.insert(url.clone(), fetched);
+                                }
+                                Err(err) => {
+                                    failed.insert(url.clone(), err);
+                                }
+                            }
+                        }
+                        // shown even when the storage is full, only not kept offline
+                        let unsaved = collections::cache(&mut pages, &subscriptions).err();
+                        loaded.set(pages);
+                        errors.set(failed);
+                        unsaved_error.set(unsaved);
+                    });
+                });
+                let window = web_sys::window().unwrap();
+                let callback: &Function = refresh.as_ref().unchecked_ref();
+                callback.call0(&JsValue::NULL).ok();
+                let interval = window
+                    .set_interval_with_callback_and_timeout_and_arguments_0(callback, REFRESH_EVERY)
+                    .ok();
+                move || {
+                    if let Some(interval) = interval {
+                        window.clear_interval_with_handle(interval);
+                    }
+                    drop(refresh);
+                }
+            }
+        },
+        ctx.collections.clone(),
+    );
+
+    let hide_buttons = !ctx.editable;
+    let filter = ctx.tag_filter.as_deref();
+    ctx.collections
+        .iter()
+        .flat_map(|sub| {
+            let error = errors.get(&sub.url);
+            let Some(pages) = loaded.get(&sub.url) else {
+                // never loaded, there is nothing to show but why
+                let error = error.map_or("Loading…", String::as_str);
+                return vec![html! {
+                    <div key={sub.url.clone()} class={classes!("card", "shared")}>
+                        <h3 class={classes!("card-name")}>
+                            { &sub.name }
+                            <span class={classes!("badge")} title={sub.url.clone()}>{ "👥" }</span>
+                        </h3>
+                        <small class={classes!("description")}>{ error }</small>
+                    </div>
+                }];
+            };
+            collections::cards_for(pages, &cards.pages, cards.current)
+                .into_iter()
+                .filter(|card| filter.is_none_or(|tag| card.has_tag(tag)))
+                .enumerate()
+                .map(|(i, card)| {
+                    let copy = {
+                        let (cards, card) = (cards.clone(), card.clone());
+                        Callback::from(move |_| cards.dispatch(CardsHandler::Copy(card.clone())))
+                    };
+                    let badge = if error.is_some() || unsaved_error.is_some() {
+                        format!("👥 {} ⚠", sub.name)
+                    } else {
+                        format!("👥 {}", sub.name)
+                    };
+                    let mut title = match error {
+                        Some(error) => format!("Shared from {}, not updated: {error}", sub.url),
+                        None => format!("Shared from {}", sub.url),
+                    };
+                    if let Some(unsaved) = &*unsaved_error {
+                        title.push_str(&format!(", not kept offline: {unsaved}"));
+                    }
+                    let card_class = classes!(
+                        "card",
+                        "shared",
+                        card.query.is_some().then_some("smart"),
+                        card.display.class()
+                    );
+                    html! {
+                        <div key={format!("{}#{i}", sub.url)} class={card_class}>
+                            <h3 class={classes!("card-name")}>
+                                { &card.name }
+                                <span class={classes!("badge")} {title}>{ badge }</span>
+                            </h3>
+                            if let Some(query) = card.query.clone() {
+                                <SmartLinkList {query}/>
+                            } else {
+                                { links(&card.links, &card.groups, filter) }
+                            }
+                            <div class={classes!("buttons")} hidden={hide_buttons}>
+                                <button onclick={copy} title="Copy to your cards">{ "📋 Copy" }</button>
+                            </div>
+                        </div>
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The links of a shared card or group followed by its groups, without buttons.
+pub(super) fn links(links: &[Anchor], groups: &[Group], filter: Option<&str>) -> Html {
+    let links: Html = links
+        .iter()
+        .filter(|link| filter.is_none_or(|tag| link.has_tag(tag)))
+        .enumerate()
+        .map(|(i, link)| {
+            let color = link.color.as_deref().and_then(Anchor::parse_color);
+            let color = color.map(|color| format!("--link-color: {color}"));
+            html! {
+                <div key={format!("link{i}")} style={color.clone()}
+                    class={classes!("card-link", color.is_some().then_some("highlight"))}>
+                    <LinkBody anchor={link.clone()}/>
+                </div>
+            }
+        })
+        .collect();
+    let groups: Html = groups
+        .iter()
+        .filter(|group| filter.is_none_or(|tag| group.has_tag(tag)))
+        .enumerate()
+        .map(|(i, group)| {
+            html! {
+                <details key={format!("group{i}")} class={classes!("group")} open=true>
+                    <summary>
+                        <span class={classes!("group-name")}>{ &group.name }</span>
+                    </summary>
+                    { self::links(&group.links, &group.groups, filter) }
+                </details>
+            }
+        })
+        .collect();
+    html! {
+        <>
+            <div class={classes!("links")}>
+                { links }
+            </div>
+            { groups }
+        </>
+    }
+}
@@ -0,0 +1,124 @@
+use crate::{
+    components::cards::{cards_ctx::CardsContext, link_body::LinkBody},
+    CtxAction, GlobalCtx,
+};
+use yew::{classes, function_component, html, use_context, Callback, Html, Properties};
+
+/// Every tag in use, selecting one narrows the cards to its links.
+#[function_component(TagBar)]
+pub fn tag_bar() -> Html {
+    let ctx = use_context::<GlobalCtx>().unwrap();
+    let cards = use_context::<CardsContext>().unwrap();
+    let tags = cards.tags();
+    let filter = ctx.tag_filter.clone();
+
+    let chips: Html = tags
+        .into_iter()
+        .map(|(tag, count)| {
+            let active = filter
+                .as_ref()
+                .is_some_and(|filter| filter.eq_ignore_ascii_case(&tag));
+            let select = {
+                let ctx = ctx.clone();
+                let tag = tag.clone();
+                Callback::from(move |_| {
+                    let tag = if active { None } else { Some(tag.clone()) };
+                    ctx.dispatch(CtxAction::FilterTag(tag))
+                })
+            };
+            html! {
+                <button key={tag.clone()} class={classes!("tag", active.then_some("active"))}
+                    onclick={select}>
+                    { format!("#{tag} ") }<small>{ count }</small>
+                </button>
+            }
+        })
+        .collect();
+
+    let clear = {
+        let ctx = ctx.clone();
+        Callback::from(move |_| ctx.dispatch(CtxAction::FilterTag(None)))
+    };
+    let toggle_view = {
+        let ctx = ctx.clone();
+        Callback::from(move |_| ctx.dispatch(CtxAction::ToggleTagView))
+    };
+
+    html! {
+        <div class={classes!("tag-bar")} hidden={cards.tags().is_empty()}>
+            { chips }
+            if filter.is_some() {
+                <button onclick={toggle_view}>
+                    { if ctx.tag_view { "🗂️ Back to cards" } else { "📑 Collect from all pages" } }
+                </button>
+                <button onclick={clear} class={classes!("icon")}>{ "🗙" }</button>
+            }
+        </div>
+    }
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct TagChipsProps {
+    pub tags: Vec<String>,
+}
+
+/// The tags of a link, selecting one filters by it.
+#[function_component(TagChips)]
+pub fn tag_chips(TagChipsProps { tags }: &TagChipsProps) -> Html {
+    let ctx = use_context::<GlobalCtx>().unwrap();
+    let chips: Html = tags
+        .iter()
+        .map(|tag| {
+            let select = {
+                let ctx = ctx.clone();
+                let tag = tag.clone();
+                Callback::from(move |_| ctx.dispatch(CtxAction::FilterTag(Some(tag.clone()))))
+            };
+            html! { <span key={tag.clone()} class={classes!("tag")} onclick={select}>{ format!("#{tag}") }</span> }
+        })
+        .collect();
+
+    html! {
+        <span class={classes!("tags")}>{ chips }</span>
+    }
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct TagViewProps {
+    pub tag: String,
+}
+
+/// A virtual card with the links having `tag` from every card of every page.
+#[function_component(TagView)]
+pub fn tag_view(TagViewProps { tag }: &TagViewProps) -> Html {
+    let cards = use_context::<CardsContext>().unwrap();
+
+    let links: Html = cards
+        .pages
+        .iter()
+        .flat_map(|page| page.cards.iter().map(move |card| (page, card)))
+        .flat_map(|(page, card)| {
+            card.all_links()
+                .into_iter()
+                .filter(|(_, _, link)| link.has_tag(tag))
+                .map(move |(_, _, link)| (page, card, link.clone()))
+        })
+        .map(|(page, card, anchor)| {
+            html! {
+                <div class={classes!("card-link")}>
+                    <LinkBody {anchor}/>
+                    <small class={classes!("source")}>{ format!("{} › {}", page.name, card.name) }</small>
+                </div>
+            }
+        })
+        .collect();
+
+    html! {
+        <div class={classes!("card", "tag-view")}>
+            <h3 class={classes!("card-name")}>{ format!("#{tag}") }</h3>
+            <div class={classes!("links")}>
+                { links }
+            </div>
+        </div>
+    }
+}
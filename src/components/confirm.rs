@@ -0,0 +1,159 @@
+use web_sys::{HtmlElement, HtmlInputElement};
+use yew::{
+    classes, function_component, html, use_effect_with_deps, use_state_eq, AttrValue, Callback,
+    Children, ContextProvider, Html, InputEvent, KeyboardEvent, NodeRef, Properties, TargetCast,
+};
+
+use crate::policy::Ask;
+
+/// A destructive action waiting for the user's approval.
+#[derive(Clone, PartialEq)]
+pub struct Confirmation {
+    pub ask: Ask,
+    pub message: AttrValue,
+    pub action: Callback<()>,
+}
+
+impl Confirmation {
+    pub fn new(ask: Ask, message: impl Into<AttrValue>, action: Callback<()>) -> Self {
+        Self {
+            ask,
+            message: message.into(),
+            action,
+        }
+    }
+}
+
+/// Runs a [`Confirmation`] once the user approves it, or right away if there is nothing
+/// to ask.
+pub type ConfirmContext = Callback<Confirmation>;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct ConfirmProviderProps {
+    pub children: Children,
+}
+
+/// Provides a [`ConfirmContext`] and shows its dialog.
+#[function_component(ConfirmProvider)]
+pub fn confirm_provider(ConfirmProviderProps { children }: &ConfirmProviderProps) -> Html {
+    let pending = use_state_eq(|| None::<Confirmation>);
+
+    let request = {
+        let pending = pending.clone();
+        Callback::from(move |confirmation: Confirmation| match confirmation.ask {
+            Ask::Nothing => confirmation.action.emit(()),
+            _ => pending.set(Some(confirmation)),
+        })
+    };
+    let close = {
+        let pending = pending.clone();
+        Callback::from(move |_| pending.set(None))
+    };
+
+    html! {
+        <ContextProvider<ConfirmContext> context={request}>
+            { children.iter().collect::<Html>() }
+            <ConfirmDialog confirmation={(*pending).clone()} {close}/>
+        </ContextProvider<ConfirmContext>>
+    }
+}
+
+#[derive(Clone, PartialEq, Properties)]
+struct ConfirmDialogProps {
+    confirmation: Option<Confirmation>,
+    close: Callback<()>,
+}
+
+/// A yes/no dialog, or one where the user has to type a name, Enter confirms and Escape
+/// cancels.
+#[function_component(ConfirmDialog)]
+fn confirm_dialog(
+    ConfirmDialogProps {
+        confirmation,
+        close,
+    }: &ConfirmDialogProps,
+) -> Html {
+    let typed = use_state_eq(String::new);
+    let input_ref = NodeRef::default();
+    let button_ref = NodeRef::default();
+
+    {
+        let typed = typed.clone();
+        let refs = (input_ref.clone(), button_ref.clone());
+        use_effect_with_deps(
+            move |open| {
+                if *open {
+                    typed.set(String::new());
+                    // the name input if there is one, else the confirm button
+                    let element = refs.0.cast::<HtmlElement>();
+                    if let Some(element) = element.or_else(|| refs.1.cast::<HtmlElement>()) {
+                        element.focus().ok();
+                    }
+                }
+                || ()
+            },
+            confirmation.is_some(),
+        );
+    }
+
+    let expected = match confirmation.as_ref().map(|c| &c.ask) {
+        Some(Ask::Type(expected)) => Some(expected.clone()),
+        _ => None,
+    };
+    let allowed = expected
+        .as_ref()
+        .is_none_or(|expected| **typed == *expected);
+
+    let confirm = {
+        let confirmation = confirmation.clone();
+        let close = close.clone();
+        Callback::from(move |_| {
+            if let (Some(confirmation), true) = (&confirmation, allowed) {
+                close.emit(());
+                confirmation.action.emit(());
+            }
+        })
+    };
+    let cancel = close.reform(|_| ());
+    let oninput = {
+        let typed = typed.clone();
+        Callback::from(move |e: InputEvent| {
+            typed.set(e.target_unchecked_into::<HtmlInputElement>().value())
+        })
+    };
+    let onkeydown = {
+        let confirm = confirm.clone();
+        let cancel = cancel.clone();
+        Callback::from(move |e: KeyboardEvent| match e.key().as_str() {
+            "Escape" => cancel.emit(()),
+            "Enter" => {
+                e.prevent_default();
+                confirm.emit(());
+            }
+            _ => (),
+        })
+    };
+
+    let hidden_class = confirmation.is_none().then_some("hidden");
+    let message = confirmation.as_ref().map(|c| c.message.clone());
+    html! {
+        <div class={classes!("edit-screen", hidden_class)}>
+            <div class={classes!("edit-form", "confirm")} {onkeydown}>
+                <p>{ message }</p>
+                if let Some(expected) = expected {
+                    <div>
+                        <label>{ format!("Type \"{expected}\" to confirm:") }</label>
+                        <input type="text" value={(*typed).clone()} {oninput} ref={input_ref}/>
+                    </div>
+                }
+                <div class={classes!("buttons")}>
+                    <button onclick={confirm.reform(|_| ())} disabled={!allowed} type="button"
+                        ref={button_ref}>
+                        { "Confirm" }
+                    </button>
+                    <button onclick={cancel.reform(|_| ())} type="button">{ "Cancel" }</button>
+                </div>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,165 @@
+use yew::{
+    classes, function_component, html, use_context, use_effect_with_deps, use_state_eq, Callback,
+    Html, Properties,
+};
+
+use crate::{urls, GlobalCtx};
+
+use super::{
+    cards::cards_ctx::{CardsContext, CardsHandler, LinkId, PageId},
+    confirm::{ConfirmContext, Confirmation},
+};
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct DuplicatesProps {
+    pub hidden: bool,
+    pub close: Callback<()>,
+}
+
+/// Lists the links saved more than once, on any page, to clean them up.
+#[function_component(Duplicates)]
+pub fn duplicates(DuplicatesProps { hidden, close }: &DuplicatesProps) -> Html {
+    let cards = use_context::<CardsContext>().unwrap();
+    // only look for them while shown
+    let sets = if *hidden {
+        Vec::new()
+    } else {
+        cards.duplicates()
+    };
+
+    // merges done since the dialog was opened, only those are undone here
+    let merges = use_state_eq(|| 0usize);
+    {
+        let merges = merges.clone();
+        use_effect_with_deps(
+            move |_| {
+                merges.set(0);
+                || ()
+            },
+            *hidden,
+        );
+    }
+
+    let found = !sets.is_empty();
+    let merged = {
+        let merges = merges.clone();
+        Callback::from(move |_| merges.set(*merges + 1))
+    };
+    let sets: Html = sets
+        .into_iter()
+        .map(|links| {
+            let (page, link) = &links[0];
+            let url = cards.link(*page, link).unwrap().url.clone();
+            html! { <DuplicateSet key={urls::comparable(&url)} {links} merged={merged.clone()}/> }
+        })
+        .collect();
+
+    let close = close.reform(|_| ());
+    let undo = {
+        let (cards, merges) = (cards.clone(), merges.clone());
+        Callback::from(move |_| {
+            cards.dispatch(CardsHandler::Undo);
+            merges.set((*merges).saturating_sub(1));
+        })
+    };
+    let can_undo = *merges > 0 && !cards.history.is_empty();
+
+    let hidden_class = if *hidden { Some("hidden") } else { None };
+    html! {
+        <div class={classes!("edit-screen", hidden_class)}>
+            <div class={classes!("edit-form", "settings", "duplicates")}>
+                <h3>{ "Duplicates" }</h3>
+                if found {
+                    { sets }
+                } else {
+                    <p>{ "No link is saved twice." }</p>
+                }
+                <div class={classes!("buttons")}>
+                    <button onclick={undo} disabled={!can_undo}>
+                        { "↶ Undo" }
+                    </button>
+                    <button onclick={close}>{ "Close" }</button>
+                </div>
+            </div>
+        </div>
+    }
+}
+
+#[derive(Clone, PartialEq, Properties)]
+struct DuplicateSetProps {
+    links: Vec<(PageId, LinkId)>,
+    /// Called when the links are merged or removed.
+    merged: Callback<()>,
+}
+
+/// Links with the same URL, the chosen one is kept.
+#[function_component(DuplicateSet)]
+fn duplicate_set(DuplicateSetProps { links, merged }: &DuplicateSetProps) -> Html {
+    let ctx = use_context::<GlobalCtx>().unwrap();
+    let cards = use_context::<CardsContext>().unwrap();
+    let confirm = use_context::<ConfirmContext>().unwrap();
+    let keep = use_state_eq(|| 0);
+    // the set may shrink after links are removed elsewhere
+    let kept = (*keep).min(links.len() - 1);
+
+    let resolve = |merge: bool| {
+        let (cards, confirm, merged) = (cards.clone(), confirm.clone(), merged.clone());
+        let mut others = links.clone();
+        let keep = others.remove(kept);
+        let ask = ctx.confirm.duplicates(others.len());
+        let message = format!(
+            "{} the {} other link(s) to {}?",
+            if merge { "Merge and remove" } else { "Remove" },
+            others.len(),
+            cards.link(keep.0, &keep.1).unwrap().url,
+        );
+        Callback::from(move |_| {
+            let (cards, keep, others) = (cards.clone(), keep.clone(), others.clone());
+            let merged = merged.clone();
+            let action = Callback::from(move |_| {
+                cards.dispatch(CardsHandler::MergeDuplicates {
+                    keep: keep.clone(),
+                    others: others.clone(),
+                    merge,
+                });
+                merged.emit(());
+            });
+            confirm.emit(Confirmation::new(ask.clone(), message.clone(), action));
+        })
+    };
+
+    let rows: Html = links
+        .iter()
+        .enumerate()
+        .map(|(i, (page, link))| {
+            let anchor = cards.link(*page, link).unwrap();
+            let select = {
+                let keep = keep.clone();
+                Callback::from(move |_| keep.set(i))
+            };
+            html! {
+                <label class={classes!("duplicate")}>
+                    <input type="radio" checked={i == kept} onchange={select}/>
+                    <span>{ &anchor.label }</span>
+                    <small>{ cards.location(*page, link) }</small>
+                    <small class={classes!("url")}>{ &anchor.url }</small>
+                </label>
+            }
+        })
+        .collect();
+
+    html! {
+        <div class={classes!("duplicate-set")}>
+            { rows }
+            <div class={classes!("buttons")}>
+                <button onclick={resolve(false)} title="Delete the other links">
+                    { "Keep only this" }
+                </button>
+                <button onclick={resolve(true)}
+                    title="Copy tags, clicks and details of the others into this one, then delete them">
+                    { "Merge into this" }
+                </button>
+            </div>
+        </div>
+    }
+}
@@ -1,93 +1,586 @@
-use web_sys::HtmlInputElement;
-use yew::{classes, function_component, html, AttrValue, Callback, Html, NodeRef, Properties};
+use std::{collections::HashMap, marker::PhantomData};
 
+use web_sys::{HtmlElement, HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
+use yew::{
+    classes, function_component, hook, html, use_context, use_effect_with_deps, use_state_eq,
+    AttrValue, Callback, Event, Html, KeyboardEvent, NodeRef, Properties, TargetCast,
+    UseStateHandle,
+};
+
+use crate::{components::cards::cards_ctx::Anchor, urls, GlobalCtx};
+
+/// The kind of input of a field and how its value is parsed.
 #[derive(Clone, PartialEq)]
-pub struct Input {
+enum Kind {
+    Text,
+    /// Normalized with [`urls::parse`].
+    Url,
+    TextArea,
+    /// Text shown masked.
+    Password,
+    /// Options as `(value, label)`.
+    Select(Vec<(AttrValue, AttrValue)>),
+    Checkbox,
+    /// Validated with [`Anchor::parse_color`].
+    Color,
+    /// Comma separated, see [`Anchor::parse_tags`].
+    Tags,
+}
+
+/// Names a field of a form and gives the type of its value. Fields are made with the key
+/// of their kind, so [`FormData::get`] gives the value of a field as it was parsed.
+pub struct Key<T> {
+    name: &'static str,
+    value: PhantomData<fn() -> T>,
+}
+
+impl<T> Key<T> {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            value: PhantomData,
+        }
+    }
+}
+
+impl<T> Clone for Key<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Key<T> {}
+
+/// A URL normalized with [`urls::parse`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct Url(String);
+
+/// A color validated with [`Anchor::parse_color`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct Color(String);
+
+impl From<Url> for String {
+    fn from(url: Url) -> Self {
+        url.0
+    }
+}
+
+impl From<Color> for String {
+    fn from(color: Color) -> Self {
+        color.0
+    }
+}
+
+/// A field of an [`EditForm`], its value is found by its [`Key`] in the submitted
+/// [`FormData`].
+#[derive(Clone, PartialEq)]
+pub struct Field {
+    name: &'static str,
     label: AttrValue,
-    value: Option<AttrValue>,
+    kind: Kind,
+    value: AttrValue,
+    checked: bool,
     place_holder: Option<AttrValue>,
+    required: bool,
+    validator: Option<Callback<String, Result<(), String>>>,
 }
 
-impl Input {
-    pub fn new(label: AttrValue) -> Self {
-        Input {
-            label,
-            value: None,
+impl Field {
+    fn new(name: &'static str, label: &'static str, kind: Kind) -> Self {
+        Self {
+            name,
+            label: AttrValue::from(label),
+            kind,
+            value: AttrValue::default(),
+            checked: false,
             place_holder: None,
+            required: false,
+            validator: None,
         }
     }
 
-    pub fn value(mut self, value: AttrValue) -> Self {
-        self.value = Some(value);
+    pub fn text(key: Key<String>, label: &'static str) -> Self {
+        Self::new(key.name, label, Kind::Text)
+    }
+
+    /// A URL, `None` when left empty.
+    pub fn url(key: Key<Option<Url>>, label: &'static str) -> Self {
+        Self::new(key.name, label, Kind::Url)
+    }
+
+    /// Multiline text.
+    pub fn textarea(key: Key<String>, label: &'static str) -> Self {
+        Self::new(key.name, label, Kind::TextArea)
+    }
+
+    pub fn password(key: Key<String>, label: &'static str) -> Self {
+        Self::new(key.name, label, Kind::Password)
+    }
+
+    /// One of `options`, given as `(value, label)`.
+    pub fn select<V, L>(
+        key: Key<String>,
+        label: &'static str,
+        options: impl IntoIterator<Item = (V, L)>,
+    ) -> Self
+    where
+        V: Into<AttrValue>,
+        L: Into<AttrValue>,
+    {
+        let options = options
+            .into_iter()
+            .map(|(value, label)| (value.into(), label.into()))
+            .collect();
+        Self::new(key.name, label, Kind::Select(options))
+    }
+
+    pub fn checkbox(key: Key<bool>, label: &'static str) -> Self {
+        Self::new(key.name, label, Kind::Checkbox)
+    }
+
+    /// A color name or hex code with a picker, `None` when left empty.
+    pub fn color(key: Key<Option<Color>>, label: &'static str) -> Self {
+        Self::new(key.name, label, Kind::Color)
+    }
+
+    /// A comma separated list of tags.
+    pub fn tags(key: Key<Vec<String>>, label: &'static str) -> Self {
+        Self::new(key.name, label, Kind::Tags)
+    }
+
+    pub fn value(mut self, value: impl Into<AttrValue>) -> Self {
+        self.value = value.into();
         self
     }
 
-    #[allow(dead_code)]
-    pub fn place_holder(mut self, place_holder: AttrValue) -> Self {
-        self.place_holder = Some(place_holder);
+    /// Initial state of a checkbox.
+    pub fn checked(mut self, checked: bool) -> Self {
+        self.checked = checked;
         self
     }
+
+    pub fn place_holder(mut self, place_holder: impl Into<AttrValue>) -> Self {
+        self.place_holder = Some(place_holder.into());
+        self
+    }
+
+    /// Rejects empty values and marks the label.
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Checks non-empty values after they are trimmed, an error is shown under the field.
+    pub fn validate(mut self, validator: impl Fn(&str) -> Result<(), String> + 'static) -> Self {
+        self.validator = Some(Callback::from(move |value: String| validator(&value)));
+        self
+    }
+
+    /// Reads the value of the field from its element.
+    fn read(&self, node: &NodeRef, schemes: &[String]) -> Result<Value, String> {
+        let raw = match self.kind {
+            Kind::Checkbox => {
+                let checked = node.cast::<HtmlInputElement>().is_some_and(|i| i.checked());
+                return Ok(Value::Flag(checked));
+            }
+            Kind::TextArea => node.cast::<HtmlTextAreaElement>().map(|t| t.value()),
+            Kind::Select(_) => node.cast::<HtmlSelectElement>().map(|s| s.value()),
+            _ => node.cast::<HtmlInputElement>().map(|i| i.value()),
+        };
+        self.parse(&raw.unwrap_or_default(), schemes)
+    }
+
+    /// Parses `raw`, the text of the field, as its kind says once trimmed. A checkbox is
+    /// checked when it isn't empty. `schemes` are the URL schemes allowed besides the default
+    /// ones.
+    fn parse(&self, raw: &str, schemes: &[String]) -> Result<Value, String> {
+        let raw = raw.trim();
+        if raw.is_empty() {
+            if self.required {
+                return Err("This field is required".to_owned());
+            }
+            return Ok(match self.kind {
+                Kind::Url => Value::Url(None),
+                Kind::Color => Value::Color(None),
+                Kind::Tags => Value::Tags(Vec::new()),
+                Kind::Checkbox => Value::Flag(false),
+                _ => Value::Text(String::new()),
+            });
+        }
+        if let Some(validator) = &self.validator {
+            validator.emit(raw.to_owned())?;
+        }
+        match self.kind {
+            Kind::Url => urls::parse(raw, schemes)
+                .map(|url| Value::Url(Some(Url(url))))
+                .map_err(|err| err.to_string()),
+            Kind::Color => Anchor::parse_color(raw)
+                .map(|color| Value::Color(Some(Color(color))))
+                .ok_or_else(|| "Use a #hex code or a color name".to_owned()),
+            Kind::Tags => Ok(Value::Tags(Anchor::parse_tags(raw))),
+            Kind::Checkbox => Ok(Value::Flag(true)),
+            _ => Ok(Value::Text(raw.to_owned())),
+        }
+    }
+}
+
+/// The parsed value of a field.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Value {
+    Text(String),
+    Flag(bool),
+    Tags(Vec<String>),
+    Url(Option<Url>),
+    Color(Option<Color>),
+}
+
+/// The type of the values of a kind of field, see [`Key`].
+pub trait FieldValue: Sized {
+    fn from_value(value: &Value) -> Option<Self>;
+}
+
+impl FieldValue for String {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Text(text) => Some(text.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl FieldValue for bool {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Flag(flag) => Some(*flag),
+            _ => None,
+        }
+    }
+}
+
+impl FieldValue for Vec<String> {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Tags(tags) => Some(tags.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl FieldValue for Option<Url> {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Url(url) => Some(url.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl FieldValue for Option<Color> {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Color(color) => Some(color.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// The parsed and validated values of a submitted form, by field. Text values are trimmed.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct FormData(HashMap<&'static str, Value>);
+
+impl FormData {
+    /// The value of the field of `key`, which must be a field of the form.
+    pub fn get<T: FieldValue>(&self, key: Key<T>) -> T {
+        self.0
+            .get(key.name)
+            .and_then(T::from_value)
+            .unwrap_or_else(|| panic!("the form has no field {:?}", key.name))
+    }
+
+    /// The value of a text field, `None` if it was left empty.
+    pub fn optional(&self, key: Key<String>) -> Option<String> {
+        Some(self.get(key)).filter(|text| !text.is_empty())
+    }
 }
 
 #[derive(Clone, PartialEq, Properties)]
 pub struct EditFormProps {
-    pub inputs: Vec<Input>,
+    pub fields: Vec<Field>,
     pub hidden: bool,
-    pub save: Callback<Option<Vec<String>>>,
+    /// Receives the values when they are all valid, or `None` when the form is cancelled.
+    pub save: Callback<Option<FormData>>,
+    /// Shown above the buttons when the last save was rejected.
+    #[prop_or_default]
+    pub error: Option<AttrValue>,
 }
 
+/// A modal form. Enter saves (Ctrl+Enter inside a textarea), Escape cancels and the first
+/// field is focused when it opens.
 #[function_component(EditForm)]
 pub fn edit_form(
     EditFormProps {
-        inputs,
+        fields,
         hidden,
         save,
+        error,
     }: &EditFormProps,
 ) -> Html {
-    let input_refs: Vec<_> = (0..inputs.len()).map(|_| NodeRef::default()).collect();
+    let schemes = use_context::<GlobalCtx>().unwrap().schemes.clone();
+    let refs: Vec<_> = (0..fields.len()).map(|_| NodeRef::default()).collect();
+    let errors = use_state_eq(Vec::<Option<String>>::new);
+
+    {
+        let first = refs.first().cloned();
+        let errors = errors.clone();
+        use_effect_with_deps(
+            move |hidden| {
+                if !*hidden {
+                    errors.set(Vec::new());
+                    if let Some(field) = first.and_then(|first| first.cast::<HtmlElement>()) {
+                        field.focus().ok();
+                    }
+                }
+                || ()
+            },
+            *hidden,
+        );
+    }
 
-    let inputs: Html = inputs
+    let submit = {
+        let fields = fields.clone();
+        let refs = refs.clone();
+        let errors = errors.clone();
+        let save = save.clone();
+        Callback::from(move |_| {
+            let mut data = FormData::default();
+            let mut field_errors = Vec::new();
+            for (field, node) in fields.iter().zip(&refs) {
+                match field.read(node, &schemes) {
+                    Ok(value) => {
+                        data.0.insert(field.name, value);
+                        field_errors.push(None);
+                    }
+                    Err(err) => field_errors.push(Some(err)),
+                }
+            }
+            let valid = field_errors.iter().all(Option::is_none);
+            errors.set(field_errors);
+            if valid {
+                save.emit(Some(data));
+            }
+        })
+    };
+    let cancel = save.reform(|_| None);
+
+    let onkeydown = {
+        let submit = submit.clone();
+        let cancel = cancel.clone();
+        Callback::from(move |e: KeyboardEvent| match e.key().as_str() {
+            "Escape" => cancel.emit(()),
+            "Enter" if e.ctrl_key() || e.target_dyn_into::<HtmlTextAreaElement>().is_none() => {
+                e.prevent_default();
+                submit.emit(());
+            }
+            _ => (),
+        })
+    };
+
+    let fields: Html = fields
         .iter()
+        .zip(&refs)
         .enumerate()
-        .map(|(i, input)| {
-            let name = format!("input{i}");
-            let placeholder = &input.place_holder;
-            let value = &input.value;
-            let input_ref = &input_refs[i];
+        .map(|(i, (field, node))| {
+            let error = errors.get(i).cloned().flatten();
             html! {
-                <div key={format!("form-item-{i}")}>
-                    <label>{ &input.label }</label>
-                    <input type="text" {name} {placeholder} {value} ref={input_ref}/>
+                <div key={field.name} class={classes!("field", error.is_some().then_some("invalid"))}>
+                    <label>
+                        { &field.label }
+                        if field.required {
+                            <span class={classes!("required")} title="Required">{ "*" }</span>
+                        }
+                    </label>
+                    { field_input(field, node) }
+                    if let Some(error) = error {
+                        <p class={classes!("error")}>{ error }</p>
+                    }
                 </div>
             }
         })
         .collect();
 
-    let save_on_click = {
-        let save = save.clone();
-        Callback::from(move |_| {
-            let input_values: Vec<String> = input_refs
-                .iter()
-                .map(|input| input.cast::<HtmlInputElement>().unwrap().value())
-                .collect();
-            save.emit(Some(input_values));
-        })
-    };
-    let cancel_on_click = {
-        let save = save.clone();
-        Callback::from(move |_| save.emit(None))
-    };
-
     let hidden_class = if *hidden { Some("hidden") } else { None };
     html! {
         <div class={classes!("edit-screen", hidden_class)}>
-            <div class={classes!("edit-form")}>
-                { inputs }
+            <div class={classes!("edit-form")} {onkeydown}>
+                { fields }
+                if let Some(error) = error {
+                    <p class={classes!("error")}>{ error }</p>
+                }
                 <div class={classes!("buttons")}>
-                    <button onclick={save_on_click} type="button">{"Save"}</button>
-                    <button onclick={cancel_on_click} type="button">{"Cancel"}</button>
+                    <button onclick={submit.reform(|_| ())} type="button">{"Save"}</button>
+                    <button onclick={cancel.reform(|_| ())} type="button">{"Cancel"}</button>
                 </div>
             </div>
         </div>
     }
 }
+
+/// Closes a form, clearing its `action` and hiding it, once what it edits is `gone`: a
+/// reload or a merge from another device removed it while the form was open.
+#[hook]
+pub fn use_close_when_gone<T>(
+    gone: bool,
+    action: UseStateHandle<Option<T>>,
+    hidden: UseStateHandle<bool>,
+) where
+    T: 'static,
+{
+    use_effect_with_deps(
+        move |gone| {
+            if *gone {
+                action.set(None);
+                hidden.set(true);
+            }
+            || ()
+        },
+        gone,
+    );
+}
+
+fn field_input(field: &Field, node: &NodeRef) -> Html {
+    let name = field.name;
+    let placeholder = field.place_holder.clone();
+    let value = field.value.clone();
+    match &field.kind {
+        Kind::Text | Kind::Tags => html! {
+            <input type="text" {name} {placeholder} {value} ref={node}/>
+        },
+        Kind::Url => html! {
+            <input type="text" inputmode="url" {name} {placeholder} {value} ref={node}/>
+        },
+        Kind::Password => html! {
+            <input type="password" autocomplete="off" {name} {placeholder} {value} ref={node}/>
+        },
+        Kind::TextArea => html! {
+            <textarea {name} {placeholder} {value} ref={node} rows="4"/>
+        },
+        Kind::Select(options) => {
+            let options: Html = options
+                .iter()
+                .map(|(option, label)| {
+                    let selected = *option == value;
+                    html! { <option value={option.clone()} {selected}>{ label }</option> }
+                })
+                .collect();
+            html! { <select {name} ref={node}>{ options }</select> }
+        }
+        Kind::Checkbox => html! {
+            <input type="checkbox" {name} checked={field.checked} ref={node}/>
+        },
+        Kind::Color => {
+            // the picker only knows `#rrggbb`
+            let picked = Some(value.clone()).filter(|v| v.len() == 7 && v.starts_with('#'));
+            let pick = {
+                let node = node.clone();
+                Callback::from(move |e: Event| {
+                    let picker = e.target_unchecked_into::<HtmlInputElement>();
+                    if let Some(input) = node.cast::<HtmlInputElement>() {
+                        input.set_value(&picker.value());
+                    }
+                })
+            };
+            html! {
+                <>
+                    <input type="text" {name} {placeholder} {value} ref={node}/>
+                    <input type="color" class={classes!("picker")} value={picked} onchange={pick}/>
+                </>
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAME: Key<String> = Key::new("name");
+    const LINK: Key<Option<Url>> = Key::new("link");
+    const COLOR: Key<Option<Color>> = Key::new("color");
+    const TAGS: Key<Vec<String>> = Key::new("tags");
+
+    fn parse(field: Field, raw: &str) -> Result<Value, String> {
+        field.parse(raw, &[])
+    }
+
+    #[test]
+    fn texts_are_trimmed_and_checked() {
+        assert_eq!(
+            parse(Field::text(NAME, "Name:"), "  Work "),
+            Ok(Value::Text("Work".to_owned()))
+        );
+        let required = Field::text(NAME, "Name:").required();
+        assert_eq!(
+            parse(required.clone(), " "),
+            Err("This field is required".to_owned())
+        );
+        let validated = required.validate(|name| {
+            if name.contains('/') {
+                Err("No slashes".to_owned())
+            } else {
+                Ok(())
+            }
+        });
+        assert_eq!(
+            parse(validated.clone(), "a/b"),
+            Err("No slashes".to_owned())
+        );
+        assert!(parse(validated, "ab").is_ok());
+    }
+
+    #[test]
+    fn urls_colors_and_tags_are_parsed() {
+        let url = Field::url(LINK, "URL:");
+        assert_eq!(parse(url.clone(), ""), Ok(Value::Url(None)));
+        assert_eq!(
+            parse(url.clone(), "example.com/wiki"),
+            Ok(Value::Url(Some(Url("https://example.com/wiki".to_owned()))))
+        );
+        assert!(parse(url.clone(), "javascript:alert(1)").is_err());
+        assert!(parse(url.clone(), "obsidian://open").is_err());
+        assert!(url
+            .parse("obsidian://open", &["obsidian".to_owned()])
+            .is_ok());
+
+        let color = Field::color(COLOR, "Color:");
+        assert_eq!(parse(color.clone(), ""), Ok(Value::Color(None)));
+        assert_eq!(
+            parse(color.clone(), "#FF8F40"),
+            Ok(Value::Color(Some(Color("#ff8f40".to_owned()))))
+        );
+        assert!(parse(color, "#ff8f4").is_err());
+
+        assert_eq!(
+            parse(Field::tags(TAGS, "Tags:"), "ops, #docs, OPS,"),
+            Ok(Value::Tags(vec!["ops".to_owned(), "docs".to_owned()]))
+        );
+    }
+
+    #[test]
+    fn values_are_read_by_key() {
+        let data = FormData(HashMap::from([
+            ("name", Value::Text("Work".to_owned())),
+            ("link", Value::Url(None)),
+            ("tags", Value::Tags(vec!["ops".to_owned()])),
+        ]));
+        assert_eq!(data.get(NAME), "Work");
+        assert_eq!(data.optional(NAME).as_deref(), Some("Work"));
+        assert_eq!(data.get(LINK), None);
+        assert_eq!(data.get(TAGS), ["ops"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "no field \"color\"")]
+    fn keys_of_other_forms_are_caught() {
+        FormData::default().get(COLOR);
+    }
+}
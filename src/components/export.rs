@@ -0,0 +1,127 @@
+use foxhole_model::export::Format;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::HtmlSelectElement;
+use yew::{
+    classes, function_component, html, use_context, use_state_eq, Callback, Event, Html,
+    Properties, TargetCast,
+};
+
+use crate::components::cards::cards_ctx::CardsContext;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct ExportProps {
+    pub hidden: bool,
+    pub close: Callback<()>,
+}
+
+/// Exports the current page, or all of them, as Markdown, OPML or CSV to download or copy.
+#[function_component(Export)]
+pub fn export(ExportProps { hidden, close }: &ExportProps) -> Html {
+    let cards = use_context::<CardsContext>().unwrap();
+    let format = use_state_eq(|| Format::Markdown);
+    let all_pages = use_state_eq(|| false);
+    let copied = use_state_eq(|| false);
+
+    let pages = if *all_pages {
+        cards.pages.as_slice()
+    } else {
+        std::slice::from_ref(cards.page())
+    };
+    // rendered only while shown, every page may be long
+    let text = if *hidden {
+        String::new()
+    } else {
+        format.render(pages)
+    };
+
+    let choose_format = {
+        let (format, copied) = (format.clone(), copied.clone());
+        Callback::from(move |e: Event| {
+            let select = e.target_unchecked_into::<HtmlSelectElement>();
+            let name = select.value();
+            if let Some(chosen) = Format::ALL.into_iter().find(|f| f.name() == name) {
+                format.set(chosen);
+                copied.set(false);
+            }
+        })
+    };
+    let choose_pages = {
+        let (all_pages, copied) = (all_pages.clone(), copied.clone());
+        Callback::from(move |e: Event| {
+            let select = e.target_unchecked_into::<HtmlSelectElement>();
+            all_pages.set(select.value() == "all");
+            copied.set(false);
+        })
+    };
+    let copy = {
+        let (text, copied) = (text.clone(), copied.clone());
+        Callback::from(move |_| {
+            let copy = web_sys::window()
+                .unwrap()
+                .navigator()
+                .clipboard()
+                .write_text(&text);
+            let copied = copied.clone();
+            // no clipboard without https, the text can still be copied by hand
+            spawn_local(async move { copied.set(JsFuture::from(copy).await.is_ok()) });
+        })
+    };
+    let close = {
+        let (copied, close) = (copied.clone(), close.clone());
+        Callback::from(move |_| {
+            copied.set(false);
+            close.emit(());
+        })
+    };
+
+    let formats: Html = Format::ALL
+        .into_iter()
+        .map(|option| {
+            html! {
+                <option value={option.name()} selected={*format == option}>
+                    { option.label() }
+                </option>
+            }
+        })
+        .collect();
+    let href = format!(
+        "data:{};charset=utf-8,{}",
+        format.mime(),
+        js_sys::encode_uri_component(&text)
+    );
+    let file_name = if *all_pages {
+        "foxhole".to_owned()
+    } else {
+        cards.page().name.clone()
+    };
+    let download = format!("{file_name}.{}", format.extension());
+
+    let hidden_class = if *hidden { Some("hidden") } else { None };
+    html! {
+        <div class={classes!("edit-screen", hidden_class)}>
+            <div class={classes!("edit-form", "settings", "export")}>
+                <h3>{ "Export" }</h3>
+                <label>
+                    <span>{ "Format:" }</span>
+                    <select onchange={choose_format}>{ formats }</select>
+                </label>
+                <label>
+                    <span>{ "Pages:" }</span>
+                    <select onchange={choose_pages}>
+                        <option value="current" selected={!*all_pages}>
+                            { format!("This page ({})", cards.page().name) }
+                        </option>
+                        <option value="all" selected={*all_pages}>{ "All pages" }</option>
+                    </select>
+                </label>
+                <textarea readonly=true value={text}/>
+                <p class={classes!("details")}>{ "Smart cards are left out." }</p>
+                <div class={classes!("buttons")}>
+                    <a class={classes!("upload")} {href} {download}>{ "💾 Download" }</a>
+                    <button onclick={copy}>{ if *copied { "📋 Copied" } else { "📋 Copy" } }</button>
+                    <button onclick={close.reform(|_| ())}>{ "Close" }</button>
+                </div>
+            </div>
+        </div>
+    }
+}
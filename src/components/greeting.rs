@@ -1,26 +1,47 @@
-use gloo_storage::{LocalStorage, Storage};
 use yew::{
-    classes, function_component, html, use_context, use_state_eq, AttrValue, Callback, Html,
+    classes, function_component, html, use_context, use_effect_with_deps, use_state_eq, AttrValue,
+    Callback, Html,
 };
 
-use crate::GlobalCtx;
+use crate::{storage, GlobalCtx};
 
-use super::edit::{EditForm, Input};
+use super::edit::{EditForm, Field, FormData, Key};
 
 #[function_component(Greeting)]
 pub fn greeting() -> Html {
     let editable = use_context::<GlobalCtx>().unwrap().editable;
     let user_name = use_state_eq(|| {
         // try to get user name from browser local data
-        match LocalStorage::get::<String>("user_name") {
+        match storage::get::<String>("user_name") {
             Ok(name) => AttrValue::from(name),
             Err(_) => AttrValue::from("UserName"),
         }
     });
 
+    // follow the name changed in other tabs
+    {
+        let user_name = user_name.clone();
+        use_effect_with_deps(
+            move |_| {
+                let listener = storage::listen(move |key| {
+                    if key != "user_name" {
+                        return;
+                    }
+                    if let Ok(name) = storage::get::<String>("user_name") {
+                        user_name.set(AttrValue::from(name));
+                    }
+                });
+                move || drop(listener)
+            },
+            (),
+        );
+    }
+
     // setup edit name form
-    let input = Input::new(AttrValue::from("Your user name:")).value((*user_name).clone());
-    let inputs = vec![input];
+    const NAME: Key<String> = Key::new("name");
+    let fields = vec![Field::text(NAME, "Your user name:")
+        .value((*user_name).clone())
+        .required()];
 
     let hide_state = use_state_eq(|| true);
     let on_name_click = {
@@ -35,19 +56,19 @@ pub fn greeting() -> Html {
     let save_name = {
         let name = user_name.clone();
         let hide = hide_state.clone();
-        Callback::from(move |input_values: Option<Vec<String>>| {
+        Callback::from(move |data: Option<FormData>| {
             // hide when user cancel
-            let values = match input_values {
-                Some(vals) => vals,
+            let data = match data {
+                Some(data) => data,
                 None => {
                     hide.set(true);
                     return;
                 }
             };
 
-            let value = values.into_iter().next().unwrap_or_default();
+            let value = data.get(NAME);
             // store name on browser local storage
-            if let Err(err) = LocalStorage::set("user_name", value.clone()) {
+            if let Err(err) = storage::set("user_name", value.clone()) {
                 web_sys::console::log_1(&format!("{err}").into());
             };
             name.set(AttrValue::from(value));
@@ -64,7 +85,7 @@ pub fn greeting() -> Html {
                 <span class={classes!(name_class)} onclick={on_name_click}>{ &(*user_name) }</span>
                 {"!"}
             </p>
-            <EditForm {inputs} hidden={*hide_state} save={save_name}/>
+            <EditForm {fields} hidden={*hide_state} save={save_name}/>
         </div>
     }
 }
@@ -0,0 +1,209 @@
+use js_sys::Function;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::spawn_local;
+use yew::{
+    classes, function_component, html, use_context, use_effect_with_deps, use_state_eq, Callback,
+    Html, Properties,
+};
+
+use crate::{
+    snapshots::{self, Change, Entry},
+    GlobalCtx,
+};
+
+use super::{
+    cards::cards_ctx::{Card, Cards, CardsContext, CardsHandler},
+    confirm::{ConfirmContext, Confirmation},
+};
+
+/// How often to check whether a new day or week started, in milliseconds.
+const CHECK_EVERY: i32 = 60 * 60 * 1000;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct HistoryProps {
+    pub hidden: bool,
+    pub close: Callback<()>,
+}
+
+/// The daily and weekly snapshots of the pages, to restore all of them or a single card.
+/// Also takes the snapshots while the page is open.
+#[function_component(History)]
+pub fn history(HistoryProps { hidden, close }: &HistoryProps) -> Html {
+    let ctx = use_context::<GlobalCtx>().unwrap();
+    let cards = use_context::<CardsContext>().unwrap();
+    let confirm = use_context::<ConfirmContext>().unwrap();
+    let entries = use_state_eq(|| None::<Vec<Entry>>);
+    let status = use_state_eq(|| None::<String>);
+    let expanded = use_state_eq(|| None::<String>);
+
+    // snapshots of the saved cards, the ones every tab agrees on
+    use_effect_with_deps(
+        |_| {
+            let take = Closure::<dyn Fn()>::new(|| {
+                spawn_local(async {
+                    if let Err(err) = snapshots::take(&Cards::load().pages).await {
+                        web_sys::console::log_1(&err);
+                    }
+                });
+            });
+            let window = web_sys::window().unwrap();
+            let callback: &Function = take.as_ref().unchecked_ref();
+            callback.call0(&JsValue::NULL).ok();
+            let interval = window
+                .set_interval_with_callback_and_timeout_and_arguments_0(callback, CHECK_EVERY)
+                .ok();
+            move || {
+                if let Some(interval) = interval {
+                    window.clear_interval_with_handle(interval);
+                }
+                drop(take);
+            }
+        },
+        (),
+    );
+
+    // list the snapshots when opened
+    {
+        let (entries, status) = (entries.clone(), status.clone());
+        use_effect_with_deps(
+            move |hidden| {
+                if !*hidden {
+                    spawn_local(async move {
+                        match snapshots::list().await {
+                            Ok(list) => entries.set(Some(list)),
+                            Err(err) => status.set(Some(format!("Listing failed: {err:?}"))),
+                        }
+                    });
+                }
+                || ()
+            },
+            *hidden,
+        );
+    }
+
+    let restore = {
+        let (cards, status) = (cards.clone(), status.clone());
+        let ask = ctx.confirm.bulk();
+        Callback::from(move |entry: Entry| {
+            let (cards, status) = (cards.clone(), status.clone());
+            let name = format!("{} snapshot of {}", entry.kind.label(), entry.date);
+            let message = format!("Replace all your cards with the ones of the {name}?");
+            let action = Callback::from(move |_| {
+                cards.dispatch(CardsHandler::Restore(entry.snapshot.pages.clone()));
+                status.set(Some(format!("Restored the {name}, undo to go back")));
+            });
+            confirm.emit(Confirmation::new(ask.clone(), message, action));
+        })
+    };
+    let restore_card = {
+        let (cards, status) = (cards.clone(), status.clone());
+        Callback::from(move |(page, card): (String, Card)| {
+            status.set(Some(format!("Restored {}, undo to go back", card.name)));
+            cards.dispatch(CardsHandler::RestoreCard { page, card });
+        })
+    };
+
+    let card_list = |entry: &Entry| -> Html {
+        entry
+            .snapshot
+            .pages
+            .iter()
+            .flat_map(|page| page.cards.iter().map(move |card| (page, card)))
+            .map(|(page, card)| {
+                let change = snapshots::change(&page.name, card, &cards.pages);
+                let label = match change {
+                    Change::Same => "unchanged",
+                    Change::Changed => "changed since",
+                    Change::Removed => "removed since",
+                };
+                let onclick = restore_card.reform({
+                    let (page, card) = (page.name.clone(), card.clone());
+                    move |_| (page.clone(), card.clone())
+                });
+                html! {
+                    <li>
+                        <span>{ format!("{} › {}", page.name, card.name) }</span>
+                        { " " }
+                        <span class={classes!("details")}>{ label }</span>
+                        if change != Change::Same {
+                            { " " }
+                            <button {onclick}>{ "Restore" }</button>
+                        }
+                    </li>
+                }
+            })
+            .collect()
+    };
+
+    let list: Html = match &*entries {
+        None => Html::default(),
+        Some(list) if list.is_empty() => html! { <p>{ "No snapshot yet." }</p> },
+        Some(list) => list
+            .iter()
+            .map(|entry| {
+                let pages = &entry.snapshot.pages;
+                let card_count: usize = pages.iter().map(|page| page.cards.len()).sum();
+                let link_count: usize = pages
+                    .iter()
+                    .flat_map(|page| &page.cards)
+                    .map(|card| card.all_links().len())
+                    .sum();
+                let diff = snapshots::diff(pages, &cards.pages);
+                let diff = if diff == Default::default() {
+                    "same as now".to_owned()
+                } else {
+                    format!(
+                        "since: {} removed, {} added, {} changed",
+                        diff.removed, diff.added, diff.changed
+                    )
+                };
+                let taken = js_sys::Date::new(&JsValue::from_f64(entry.snapshot.taken))
+                    .to_locale_string("default", &JsValue::UNDEFINED);
+                let open = expanded.as_deref() == Some(entry.key.as_str());
+                let toggle = {
+                    let (expanded, key) = (expanded.clone(), entry.key.clone());
+                    Callback::from(move |_| expanded.set((!open).then(|| key.clone())))
+                };
+                let onclick = restore.reform({
+                    let entry = entry.clone();
+                    move |_| entry.clone()
+                });
+                html! {
+                    <div key={entry.key.clone()}>
+                        <div class={classes!("snapshot")}>
+                            <span>{ format!("{} {}", entry.kind.label(), entry.date) }</span>
+                            <span class={classes!("details")} title={String::from(taken)}>
+                                { format!("{card_count} cards, {link_count} links, {diff}") }
+                            </span>
+                            <button onclick={toggle}>{ if open { "Hide cards" } else { "Cards" } }</button>
+                            <button {onclick}>{ "Restore" }</button>
+                        </div>
+                        if open {
+                            <ul>{ card_list(entry) }</ul>
+                        }
+                    </div>
+                }
+            })
+            .collect(),
+    };
+
+    let close = close.reform(|_| ());
+    let hidden_class = if *hidden { Some("hidden") } else { None };
+    html! {
+        <div class={classes!("edit-screen", hidden_class)}>
+            <div class={classes!("edit-form", "settings", "history")}>
+                <h3>{ "History" }</h3>
+                <p class={classes!("details")}>
+                    { "The cards as they were at the start of each of the last 7 days and 4 weeks." }
+                </p>
+                { list }
+                if let Some(status) = &*status {
+                    <p class={classes!("details")}>{ status }</p>
+                }
+                <div class={classes!("buttons")}>
+                    <button onclick={close}>{ "Close" }</button>
+                </div>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,188 @@
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::{
+    classes, function_component, html, use_context, use_state_eq, Callback, Event, Html,
+    Properties, TargetCast,
+};
+
+use crate::{
+    components::cards::cards_ctx::{CardsContext, CardsHandler, Page},
+    import::{self, Format},
+    GlobalCtx,
+};
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct ImportProps {
+    pub hidden: bool,
+    pub close: Callback<()>,
+}
+
+/// Imports the links of another start page as a new page, after a preview.
+#[function_component(Import)]
+pub fn import(ImportProps { hidden, close }: &ImportProps) -> Html {
+    let ctx = use_context::<GlobalCtx>().unwrap();
+    let cards = use_context::<CardsContext>().unwrap();
+    // the format picked, `None` detects it
+    let format = use_state_eq(|| None::<Format>);
+    let text = use_state_eq(|| None::<String>);
+    // the bookmark folders picked, in `import::walk` order, `None` until changed
+    let picked = use_state_eq(|| None::<Vec<bool>>);
+    let bookmarks = (*text).as_ref().and_then(|text| {
+        let format = (*format)
+            .or_else(|| Format::detect(text))
+            .filter(|format| format.is_bookmarks())?;
+        Some((format, format.folders(text, &ctx.schemes)))
+    });
+    let folders = match &bookmarks {
+        Some((_, Ok(folders))) => Some(folders.as_slice()),
+        _ => None,
+    };
+    let picks = folders.map(|folders| {
+        (*picked)
+            .clone()
+            .unwrap_or_else(|| import::picked_by_default(folders))
+    });
+    let preview = (*text).as_ref().map(|text| match &bookmarks {
+        Some((format, Ok(folders))) => {
+            import::bookmarks(*format, folders, picks.as_deref().unwrap())
+        }
+        Some((_, Err(err))) => Err(err.clone()),
+        None => import::import(text, *format, &ctx.schemes),
+    });
+
+    let choose_file = {
+        let (text, picked) = (text.clone(), picked.clone());
+        Callback::from(move |e: Event| {
+            let input = e.target_unchecked_into::<HtmlInputElement>();
+            let file = input.files().and_then(|files| files.get(0));
+            input.set_value("");
+            let Some(file) = file else { return };
+            picked.set(None);
+            let text = text.clone();
+            spawn_local(async move {
+                match gloo_file::futures::read_as_text(&file.into()).await {
+                    Ok(content) => text.set(Some(content)),
+                    Err(err) => web_sys::console::log_1(&format!("{err}").into()),
+                }
+            });
+        })
+    };
+    let choose_format = {
+        let (format, picked) = (format.clone(), picked.clone());
+        Callback::from(move |e: Event| {
+            let select = e.target_unchecked_into::<HtmlSelectElement>();
+            let name = select.value();
+            picked.set(None);
+            format.set(Format::ALL.into_iter().find(|format| format.name() == name));
+        })
+    };
+    let close = {
+        let (text, close) = (text.clone(), close.clone());
+        Callback::from(move |_| {
+            text.set(None);
+            close.emit(());
+        })
+    };
+    let add = {
+        let (cards, close) = (cards.clone(), close.clone());
+        let page = preview.clone().and_then(Result::ok);
+        Callback::from(move |_| {
+            if let Some(page) = page.clone() {
+                cards.dispatch(CardsHandler::Import(page));
+            }
+            close.emit(());
+        })
+    };
+
+    let formats: Html = Format::ALL
+        .into_iter()
+        .map(|option| {
+            html! {
+                <option value={option.name()} selected={*format == Some(option)}>
+                    { option.label() }
+                </option>
+            }
+        })
+        .collect();
+    let checklist: Html = match (folders, &picks) {
+        (Some(folders), Some(picks)) => import::walk(folders)
+            .into_iter()
+            .enumerate()
+            .map(|(i, (depth, folder))| {
+                let toggle = {
+                    let (picked, picks) = (picked.clone(), picks.clone());
+                    Callback::from(move |_: Event| {
+                        let mut picks = picks.clone();
+                        picks[i] = !picks[i];
+                        picked.set(Some(picks));
+                    })
+                };
+                let indent = format!("padding-left: {depth}em");
+                html! {
+                    <label class={classes!("folder")} style={indent}>
+                        <input type="checkbox" checked={picks[i]} onchange={toggle}/>
+                        { &folder.name }
+                        <span class={classes!("details")}>
+                            { format!("{} link(s)", folder.links.len()) }
+                        </span>
+                    </label>
+                }
+            })
+            .collect(),
+        _ => Html::default(),
+    };
+    let summary = match &preview {
+        None => html! {
+            <p class={classes!("details")}>
+                { "Homer and Dashy YAML configurations, Flame and Heimdall JSON exports, \
+                   Firefox bookmark backups and Chromium Bookmarks files." }
+            </p>
+        },
+        Some(Err(err)) => html! { <p>{ err }</p> },
+        Some(Ok(Page { name, cards })) => {
+            let links: usize = cards.iter().map(|card| card.all_links().len()).sum();
+            let names = cards
+                .iter()
+                .map(|card| card.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            html! {
+                <>
+                    <p>{ format!("A new page \"{name}\" with {} card(s) and {links} link(s):", cards.len()) }</p>
+                    <p class={classes!("details")}>{ names }</p>
+                </>
+            }
+        }
+    };
+
+    let ready = matches!(preview, Some(Ok(_)));
+    let close = close.reform(|_| ());
+    let hidden_class = if *hidden { Some("hidden") } else { None };
+    html! {
+        <div class={classes!("edit-screen", hidden_class)}>
+            <div class={classes!("edit-form", "settings", "import")}>
+                <h3>{ "Import" }</h3>
+                <label>
+                    <span>{ "File:" }</span>
+                    <input type="file" onchange={choose_file}/>
+                </label>
+                <label>
+                    <span>{ "Format:" }</span>
+                    <select onchange={choose_format}>
+                        <option value="" selected={format.is_none()}>{ "Detect" }</option>
+                        { formats }
+                    </select>
+                </label>
+                if folders.is_some() {
+                    <p>{ "Each folder picked becomes a card, the folders in it become its groups:" }</p>
+                    <div class={classes!("folders")}>{ checklist }</div>
+                }
+                { summary }
+                <div class={classes!("buttons")}>
+                    <button onclick={add} disabled={!ready}>{ "📥 Import" }</button>
+                    <button onclick={close}>{ "Close" }</button>
+                </div>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,60 @@
+use pulldown_cmark::{html::push_html, CowStr, Event, Parser, Tag};
+use yew::{function_component, html, AttrValue, Html, Properties};
+
+/// Link destinations allowed in rendered Markdown, anything else becomes inert.
+const SAFE_SCHEMES: [&str; 3] = ["http:", "https:", "mailto:"];
+
+fn safe_destination(dest: CowStr<'_>) -> CowStr<'_> {
+    let lower = dest.trim().to_ascii_lowercase();
+    let has_scheme = lower
+        .split_once(':')
+        .is_some_and(|(scheme, _)| !scheme.contains(['/', '?', '#']));
+    if !has_scheme || SAFE_SCHEMES.iter().any(|scheme| lower.starts_with(scheme)) {
+        dest
+    } else {
+        CowStr::Borrowed("#")
+    }
+}
+
+/// Renders Markdown to HTML, raw HTML in the source is escaped and unsafe links are disabled.
+pub fn to_html(source: &str) -> String {
+    let events = Parser::new(source).map(|event| match event {
+        Event::Html(html) => Event::Text(html),
+        Event::Start(Tag::Link(kind, dest, title)) => {
+            Event::Start(Tag::Link(kind, safe_destination(dest), title))
+        }
+        Event::End(Tag::Link(kind, dest, title)) => {
+            Event::End(Tag::Link(kind, safe_destination(dest), title))
+        }
+        Event::Start(Tag::Image(kind, dest, title)) => {
+            Event::Start(Tag::Image(kind, safe_destination(dest), title))
+        }
+        Event::End(Tag::Image(kind, dest, title)) => {
+            Event::End(Tag::Image(kind, safe_destination(dest), title))
+        }
+        event => event,
+    });
+    let mut html = String::new();
+    push_html(&mut html, events);
+    html
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct MarkdownProps {
+    pub source: AttrValue,
+}
+
+#[function_component(Markdown)]
+pub fn markdown(MarkdownProps { source }: &MarkdownProps) -> Html {
+    let div = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.create_element("div").ok());
+    match div {
+        Some(div) => {
+            div.set_class_name("markdown");
+            div.set_inner_html(&to_html(source));
+            Html::VRef(div.into())
+        }
+        None => html! { <div class="markdown">{ source }</div> },
+    }
+}
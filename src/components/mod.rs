@@ -1,7 +1,25 @@
+mod backups;
 mod bar;
 pub mod cards;
+pub mod confirm;
+mod duplicates;
 pub mod edit;
+mod export;
 mod greeting;
+mod history;
+mod import;
+mod markdown;
+mod profiles;
+mod settings;
+mod sync;
 
+pub use backups::Backups;
 pub use bar::Bar;
+pub use duplicates::Duplicates;
+pub use export::Export;
 pub use greeting::Greeting;
+pub use history::History;
+pub use import::Import;
+pub use profiles::ProfileSwitcher;
+pub use settings::LayoutSettings;
+pub use sync::SyncNotice;
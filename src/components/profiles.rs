@@ -0,0 +1,88 @@
+use web_sys::HtmlSelectElement;
+use yew::{
+    classes, function_component, html, use_context, use_state_eq, Callback, Event, Html, TargetCast,
+};
+
+use crate::{storage, GlobalCtx};
+
+use super::{
+    confirm::{ConfirmContext, Confirmation},
+    edit::{EditForm, Field, FormData, Key},
+};
+
+#[function_component(ProfileSwitcher)]
+pub fn profile_switcher() -> Html {
+    let ctx = use_context::<GlobalCtx>().unwrap();
+    let editable = ctx.editable;
+    let confirm = use_context::<ConfirmContext>().unwrap();
+    let current = storage::profile();
+    let profiles = storage::profiles();
+
+    // new profile form
+    const NAME: Key<String> = Key::new("name");
+    let fields = vec![Field::text(NAME, "Profile name:").required()];
+    let form_hide = use_state_eq(|| true);
+
+    let add_profile = {
+        let hidden = form_hide.clone();
+        Callback::from(move |data: Option<FormData>| {
+            if let Some(name) = data.and_then(|data| data.optional(NAME)) {
+                storage::add_profile(&name);
+                storage::switch_profile(&name);
+            }
+            hidden.set(true);
+        })
+    };
+
+    let open_form = {
+        let hidden = form_hide.clone();
+        Callback::from(move |_| hidden.set(false))
+    };
+
+    let rm_profile = {
+        let current = current.clone();
+        let ask = ctx.confirm.bulk();
+        let message = format!("Remove the profile \"{current}\" with all its cards and settings?");
+        Callback::from(move |_| {
+            let current = current.clone();
+            let remove = Callback::from(move |_| {
+                storage::remove_profile(&current);
+                storage::switch_profile(storage::DEFAULT_PROFILE);
+            });
+            confirm.emit(Confirmation::new(ask.clone(), message.clone(), remove));
+        })
+    };
+
+    let switch = Callback::from(|e: Event| {
+        let select = e.target_unchecked_into::<HtmlSelectElement>();
+        storage::switch_profile(&select.value());
+    });
+
+    let options: Html = profiles
+        .iter()
+        .map(|name| {
+            let selected = *name == current;
+            html! { <option value={name.clone()} {selected}>{ name }</option> }
+        })
+        .collect();
+
+    // nothing to switch to unless the user is editing
+    let hide_switcher = !editable && profiles.len() < 2;
+    let is_default = current == storage::DEFAULT_PROFILE;
+
+    html! {
+        <div class={classes!("profiles")} hidden={hide_switcher}>
+            <label>
+                <span>{ "👤" }</span>
+                <select onchange={switch}>{ options }</select>
+            </label>
+            <div class={classes!("buttons")} hidden={!editable}>
+                <button onclick={open_form} class={classes!("icon")}>{ "+" }</button>
+                <button onclick={rm_profile} class={classes!("icon")} disabled={is_default}>
+                    { "🗙" }
+                </button>
+            </div>
+            <EditForm {fields} hidden={*form_hide} save={add_profile}/>
+        </div>
+    }
+}
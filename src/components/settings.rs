@@ -0,0 +1,154 @@
+use std::str::FromStr;
+
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::{classes, function_component, html, use_context, Callback, Html, NodeRef, Properties};
+
+use crate::{
+    layout::{Alignment, Columns, Density, Layout},
+    CtxAction, GlobalCtx,
+};
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct LayoutSettingsProps {
+    pub hidden: bool,
+    pub close: Callback<()>,
+}
+
+#[function_component(LayoutSettings)]
+pub fn layout_settings(LayoutSettingsProps { hidden, close }: &LayoutSettingsProps) -> Html {
+    let ctx = use_context::<GlobalCtx>().unwrap();
+    let layout = ctx.layout.clone();
+
+    let columns_ref = NodeRef::default();
+    let min_width_ref = NodeRef::default();
+    let max_width_ref = NodeRef::default();
+    let density_ref = NodeRef::default();
+    let font_scale_ref = NodeRef::default();
+    let alignment_ref = NodeRef::default();
+
+    let save = {
+        let ctx = ctx.clone();
+        let close = close.clone();
+        let refs = (
+            columns_ref.clone(),
+            min_width_ref.clone(),
+            max_width_ref.clone(),
+            density_ref.clone(),
+            font_scale_ref.clone(),
+            alignment_ref.clone(),
+        );
+        Callback::from(move |_| {
+            let (columns, min_width, max_width, density, font_scale, alignment) = &refs;
+            let select = |node: &NodeRef| node.cast::<HtmlSelectElement>().unwrap().value();
+            let number = |node: &NodeRef| {
+                node.cast::<HtmlInputElement>()
+                    .unwrap()
+                    .value()
+                    .parse::<u16>()
+                    .ok()
+            };
+
+            let default = Layout::default();
+            let layout = Layout {
+                columns: Columns::from_str(&select(columns)).unwrap_or(default.columns),
+                card_min_width: number(min_width).unwrap_or(default.card_min_width),
+                card_max_width: number(max_width).unwrap_or(default.card_max_width),
+                density: Density::from_str(&select(density)).unwrap_or(default.density),
+                font_scale: number(font_scale).unwrap_or(default.font_scale),
+                alignment: Alignment::from_str(&select(alignment)).unwrap_or(default.alignment),
+            };
+            ctx.dispatch(CtxAction::SetLayout(layout));
+            close.emit(());
+        })
+    };
+
+    let reset = {
+        let ctx = ctx.clone();
+        let close = close.clone();
+        Callback::from(move |_| {
+            ctx.dispatch(CtxAction::SetLayout(Layout::default()));
+            close.emit(());
+        })
+    };
+
+    let cancel = {
+        let close = close.clone();
+        Callback::from(move |_| close.emit(()))
+    };
+
+    let options = |values: Vec<String>, selected: String| -> Html {
+        values
+            .into_iter()
+            .map(|value| {
+                let is_selected = value == selected;
+                html! { <option value={value.clone()} selected={is_selected}>{ value }</option> }
+            })
+            .collect()
+    };
+
+    let columns = std::iter::once(Columns::Auto)
+        .chain((1..=Layout::MAX_COLUMNS).map(Columns::Fixed))
+        .map(|c| c.to_string())
+        .collect();
+    let densities = [Density::Compact, Density::Comfortable]
+        .iter()
+        .map(|d| d.to_string())
+        .collect();
+    let alignments = [Alignment::Start, Alignment::Center, Alignment::End]
+        .iter()
+        .map(|a| a.to_string())
+        .collect();
+
+    let hidden_class = if *hidden { Some("hidden") } else { None };
+    html! {
+        <div class={classes!("edit-screen", hidden_class)}>
+            <div class={classes!("edit-form", "settings")}>
+                <h3>{ "Layout" }</h3>
+                <div>
+                    <label>{ "Columns:" }</label>
+                    <select ref={columns_ref}>
+                        { options(columns, layout.columns.to_string()) }
+                    </select>
+                </div>
+                <div>
+                    <label>{ "Card min width (em):" }</label>
+                    <input type="number" ref={min_width_ref}
+                        min={Layout::MIN_CARD_WIDTH.to_string()}
+                        max={Layout::MAX_CARD_WIDTH.to_string()}
+                        value={layout.card_min_width.to_string()}/>
+                </div>
+                <div>
+                    <label>{ "Card max width (em):" }</label>
+                    <input type="number" ref={max_width_ref}
+                        min={Layout::MIN_CARD_WIDTH.to_string()}
+                        max={Layout::MAX_CARD_WIDTH.to_string()}
+                        value={layout.card_max_width.to_string()}/>
+                </div>
+                <div>
+                    <label>{ "Density:" }</label>
+                    <select ref={density_ref}>
+                        { options(densities, layout.density.to_string()) }
+                    </select>
+                </div>
+                <div>
+                    <label>{ "Font scale (%):" }</label>
+                    <input type="number" ref={font_scale_ref}
+                        min={Layout::MIN_FONT_SCALE.to_string()}
+                        max={Layout::MAX_FONT_SCALE.to_string()}
+                        value={layout.font_scale.to_string()}/>
+                </div>
+                <div>
+                    <label>{ "Alignment:" }</label>
+                    <select ref={alignment_ref}>
+                        { options(alignments, layout.alignment.to_string()) }
+                    </select>
+                </div>
+                <div class={classes!("buttons")}>
+                    <button onclick={save} type="button">{"Save"}</button>
+                    <button onclick={reset} type="button">{"Reset"}</button>
+                    <button onclick={cancel} type="button">{"Cancel"}</button>
+                </div>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,93 @@
+use std::{cell::RefCell, rc::Rc};
+
+use wasm_bindgen_futures::spawn_local;
+use yew::{
+    classes, function_component, html, use_context, use_effect_with_deps, use_mut_ref,
+    use_state_eq, Callback, Html, UseStateHandle,
+};
+
+use crate::{
+    components::cards::{
+        cards_ctx::{CardsContext, CardsHandler},
+        doc::Doc,
+    },
+    http::Fetch,
+    sync::{self, SyncConfig},
+    GlobalCtx,
+};
+
+/// The document waiting to be synced while a sync is running.
+#[derive(Default)]
+struct Queue {
+    running: bool,
+    next: Option<Doc>,
+}
+
+/// Syncs the cards with the server on load and after every change, and shows when that
+/// fails.
+#[function_component(SyncNotice)]
+pub fn sync_notice() -> Html {
+    let ctx = use_context::<GlobalCtx>().unwrap();
+    let cards = use_context::<CardsContext>().unwrap();
+    let queue = use_mut_ref(Queue::default);
+    let error = use_state_eq(|| None::<String>);
+    // bumped to sync again after an error
+    let retries = use_state_eq(|| 0u32);
+
+    {
+        let cards = cards.clone();
+        let error = error.clone();
+        let revision = cards.revision;
+        use_effect_with_deps(
+            move |(config, ..)| {
+                if let Some(config) = config.clone() {
+                    let running = queue.borrow().running;
+                    queue.borrow_mut().next = Some(cards.doc.clone());
+                    if !running {
+                        queue.borrow_mut().running = true;
+                        spawn_local(run(config, queue, cards, error));
+                    }
+                }
+                || ()
+            },
+            (ctx.sync.clone(), revision, *retries),
+        );
+    }
+
+    let retry = {
+        let retries = retries.clone();
+        Callback::from(move |_| retries.set(*retries + 1))
+    };
+
+    html! {
+        <div class={classes!("notice")} hidden={ctx.sync.is_none() || error.is_none()}>
+            <span>{ format!("⚠ Sync failed: {}", error.as_deref().unwrap_or_default()) }</span>
+            <button onclick={retry}>{ "Retry" }</button>
+        </div>
+    }
+}
+
+/// Syncs the queued documents until none are left, only the latest one is queued.
+async fn run(
+    config: SyncConfig,
+    queue: Rc<RefCell<Queue>>,
+    cards: CardsContext,
+    error: UseStateHandle<Option<String>>,
+) {
+    loop {
+        let next = queue.borrow_mut().next.take();
+        let Some(doc) = next else {
+            queue.borrow_mut().running = false;
+            return;
+        };
+        match sync::sync(&Fetch, &config, &doc).await {
+            Ok(merged) => {
+                error.set(None);
+                if merged != doc {
+                    cards.dispatch(CardsHandler::Merge(merged));
+                }
+            }
+            Err(err) => error.set(Some(err)),
+        }
+    }
+}
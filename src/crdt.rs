@@ -0,0 +1,603 @@
+//! Conflict-free replicated data types, letting edits made by several tabs or devices be
+//! merged without losing any of them.
+//!
+//! Values are kept as trees of [`Node`]s: [`Register`]s for plain values, where the last
+//! write wins, and [`Sequence`]s for ordered lists. A replica records its edits by handing
+//! the new value to [`Node::update`], which diffs it against the current one. Merging two
+//! replicas, in any order and any number of times, gives the same result.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// When and where a write happened: a Lamport time and the replica making the write.
+/// Stamps are unique and their order is the same on every replica.
+#[derive(
+    Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default, Serialize, Deserialize,
+)]
+pub struct Stamp(u64, u64);
+
+impl Stamp {
+    fn time(self) -> u64 {
+        self.0
+    }
+}
+
+/// Gives the stamps of a replica's writes.
+pub struct Clock {
+    replica: u64,
+    time: u64,
+}
+
+impl Clock {
+    /// A clock for `replica` ahead of every time in `seen`.
+    pub fn new(replica: u64, seen: &impl Node) -> Self {
+        Self {
+            replica,
+            time: seen.time(),
+        }
+    }
+
+    fn tick(&mut self) -> Stamp {
+        self.time += 1;
+        Stamp(self.time, self.replica)
+    }
+}
+
+/// A replicated value.
+pub trait Node: Clone {
+    type Value;
+
+    /// A node holding `value`, written at the clock's time.
+    fn create(value: &Self::Value, clock: &mut Clock) -> Self;
+
+    fn value(&self) -> Self::Value;
+
+    /// Records the changes needed to hold `value`.
+    fn update(&mut self, value: &Self::Value, clock: &mut Clock);
+
+    /// Takes the writes of `other`, a replica of the same node.
+    fn merge(&mut self, other: &Self);
+
+    /// The latest time of any write to the node.
+    fn time(&self) -> u64;
+}
+
+/// A value where the last write wins.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Register<T> {
+    stamp: Stamp,
+    value: T,
+}
+
+impl<T: Clone + PartialEq> Node for Register<T> {
+    type Value = T;
+
+    fn create(value: &T, clock: &mut Clock) -> Self {
+        Self {
+            stamp: clock.tick(),
+            value: value.clone(),
+        }
+    }
+
+    fn value(&self) -> T {
+        self.value.clone()
+    }
+
+    fn update(&mut self, value: &T, clock: &mut Clock) {
+        if self.value != *value {
+            *self = Self::create(value, clock);
+        }
+    }
+
+    fn merge(&mut self, other: &Self) {
+        if other.stamp > self.stamp {
+            self.clone_from(other);
+        }
+    }
+
+    fn time(&self) -> u64 {
+        self.stamp.time()
+    }
+}
+
+/// An element of a [`Sequence`].
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+struct Item<N> {
+    id: Stamp,
+    /// The element this one was inserted after, `None` for the start of the list.
+    after: Option<Stamp>,
+    /// Removed elements are kept as tombstones, so that replicas which didn't see the
+    /// removal yet don't bring them back and elements inserted after them keep their place.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    removed: bool,
+    /// `None` once removed: removals win over edits, so only the place of the element
+    /// matters then.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    node: Option<N>,
+}
+
+impl<N> Item<N> {
+    fn remove(&mut self) {
+        self.removed = true;
+        self.node = None;
+    }
+}
+
+/// An ordered list (a replicated growable array). Every element remembers the one it was
+/// inserted after and elements inserted after the same one are ordered newest first, so
+/// concurrent insertions are interleaved the same way everywhere. Moving an element removes
+/// it and inserts a copy, the removed one is left as a small tombstone.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Sequence<N> {
+    /// In list order, removed elements included.
+    items: Vec<Item<N>>,
+}
+
+impl<N> Default for Sequence<N> {
+    fn default() -> Self {
+        Self { items: Vec::new() }
+    }
+}
+
+impl<N: Node> Sequence<N> {
+    fn live(&self) -> impl Iterator<Item = &N> {
+        self.items
+            .iter()
+            .filter(|item| !item.removed)
+            .filter_map(|item| item.node.as_ref())
+    }
+
+    /// Puts the items in list order: depth first from the start, with the children of an
+    /// item newest first. Items inserted after one that is unknown, which only a malformed
+    /// document has, are kept at the end.
+    fn sort(&mut self) {
+        let mut children: HashMap<Option<Stamp>, Vec<Item<N>>> = HashMap::new();
+        for item in self.items.drain(..) {
+            children.entry(item.after).or_default().push(item);
+        }
+        for siblings in children.values_mut() {
+            // popped from the end, so oldest first here
+            siblings.sort_by_key(|item| item.id);
+        }
+        let mut stack: Vec<Item<N>> = children.remove(&None).unwrap_or_default();
+        loop {
+            while let Some(item) = stack.pop() {
+                if let Some(next) = children.remove(&Some(item.id)) {
+                    stack.extend(next);
+                }
+                self.items.push(item);
+            }
+            // the same order everywhere, by the unknown predecessor
+            let Some(&orphans) = children.keys().min() else {
+                break;
+            };
+            stack = children.remove(&orphans).unwrap_or_default();
+        }
+    }
+}
+
+impl<N: Node> Node for Sequence<N>
+where
+    N::Value: PartialEq,
+{
+    type Value = Vec<N::Value>;
+
+    fn create(value: &Self::Value, clock: &mut Clock) -> Self {
+        let mut sequence = Self::default();
+        sequence.update(value, clock);
+        sequence
+    }
+
+    fn value(&self) -> Self::Value {
+        self.live().map(N::value).collect()
+    }
+
+    fn update(&mut self, value: &Self::Value, clock: &mut Clock) {
+        let old: Vec<usize> = (0..self.items.len())
+            .filter(|&i| !self.items[i].removed && self.items[i].node.is_some())
+            .collect();
+        let values: Vec<N::Value> = self.live().map(N::value).collect();
+        if values == *value {
+            return;
+        }
+
+        // the longest common subsequence is kept, the rest is edited in place when both
+        // sides have an element at the same spot, else removed or inserted
+        let kept = common(&values, value);
+        let mut inserted = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        let mut previous = None;
+        for (next_i, next_j) in kept.into_iter().chain([(values.len(), value.len())]) {
+            while i < next_i && j < next_j {
+                let item = &mut self.items[old[i]];
+                if let Some(node) = &mut item.node {
+                    node.update(&value[j], clock);
+                }
+                previous = Some(item.id);
+                (i, j) = (i + 1, j + 1);
+            }
+            for &index in &old[i..next_i] {
+                self.items[index].remove();
+            }
+            for new in &value[j..next_j] {
+                let item = Item {
+                    id: clock.tick(),
+                    after: previous,
+                    removed: false,
+                    node: Some(N::create(new, clock)),
+                };
+                previous = Some(item.id);
+                inserted.push(item);
+            }
+            if next_i < values.len() {
+                previous = Some(self.items[old[next_i]].id);
+            }
+            (i, j) = (next_i + 1, next_j + 1);
+        }
+        if !inserted.is_empty() {
+            self.items.extend(inserted);
+            self.sort();
+        }
+    }
+
+    fn merge(&mut self, other: &Self) {
+        let mut known: HashMap<Stamp, usize> = self
+            .items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| (item.id, i))
+            .collect();
+        let mut added = false;
+        for item in &other.items {
+            match known.get(&item.id) {
+                Some(&i) => {
+                    let mine = &mut self.items[i];
+                    match (&mut mine.node, &item.node) {
+                        _ if item.removed => mine.remove(),
+                        (Some(node), Some(theirs)) => node.merge(theirs),
+                        _ => (),
+                    }
+                }
+                None => {
+                    known.insert(item.id, self.items.len());
+                    self.items.push(item.clone());
+                    added = true;
+                }
+            }
+        }
+        if added {
+            self.sort();
+        }
+    }
+
+    fn time(&self) -> u64 {
+        self.items
+            .iter()
+            .map(|item| {
+                let node = item.node.as_ref().map_or(0, Node::time);
+                item.id.time().max(node)
+            })
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+/// Index pairs of a longest common subsequence of `a` and `b`, in order.
+fn common<T: PartialEq>(a: &[T], b: &[T]) -> Vec<(usize, usize)> {
+    // lengths[i][j] is the length for a[i..] and b[j..]
+    let mut lengths = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i][j] = if a[i] == b[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            pairs.push((i, j));
+            (i, j) = (i + 1, j + 1);
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+/// Implements [`Node`] for a struct of nodes replicating the fields of the struct `$value`,
+/// which must all be listed.
+macro_rules! replicated {
+    ($(#[$meta:meta])* $vis:vis struct $node:ident for $value:ident {
+        $($field:ident: $kind:ty,)*
+    }) => {
+        $(#[$meta])*
+        #[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+        $vis struct $node {
+            $($field: $kind,)*
+        }
+
+        impl $crate::crdt::Node for $node {
+            type Value = $value;
+
+            fn create(value: &$value, clock: &mut $crate::crdt::Clock) -> Self {
+                Self {
+                    $($field: $crate::crdt::Node::create(&value.$field, clock),)*
+                }
+            }
+
+            fn value(&self) -> $value {
+                $value {
+                    $($field: self.$field.value(),)*
+                }
+            }
+
+            fn update(&mut self, value: &$value, clock: &mut $crate::crdt::Clock) {
+                $(self.$field.update(&value.$field, clock);)*
+            }
+
+            fn merge(&mut self, other: &Self) {
+                $(self.$field.merge(&other.$field);)*
+            }
+
+            fn time(&self) -> u64 {
+                0 $(.max(self.$field.time()))*
+            }
+        }
+    };
+}
+
+pub(crate) use replicated;
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use proptest::prelude::*;
+
+    #[derive(Clone, PartialEq, Debug)]
+    struct Entry {
+        name: char,
+        items: Vec<u8>,
+    }
+
+    replicated! {
+        #[derive(Debug)]
+        struct EntryNode for Entry {
+            name: Register<char>,
+            items: Sequence<Register<u8>>,
+        }
+    }
+
+    type List = Sequence<EntryNode>;
+
+    /// Whether every element of `sequence` was inserted after an element it knows, which is
+    /// always true of replicas built with [`Node::update`] and [`Node::merge`].
+    fn well_formed<N>(sequence: &Sequence<N>) -> bool {
+        let ids: HashSet<Stamp> = sequence.items.iter().map(|item| item.id).collect();
+        ids.len() == sequence.items.len()
+            && sequence
+                .items
+                .iter()
+                .all(|item| item.after.is_none_or(|after| ids.contains(&after)))
+    }
+
+    fn entry() -> impl Strategy<Value = Entry> {
+        (
+            prop::char::range('a', 'e'),
+            prop::collection::vec(0..4u8, 0..4),
+        )
+            .prop_map(|(name, items)| Entry { name, items })
+    }
+
+    fn entries() -> impl Strategy<Value = Vec<Entry>> {
+        prop::collection::vec(entry(), 0..5)
+    }
+
+    /// Edits the value of a replica, small edits being the most common ones.
+    #[derive(Clone, Debug)]
+    enum Edit {
+        Replace(Vec<Entry>),
+        Insert(usize, Entry),
+        Remove(usize),
+        Rename(usize, char),
+        Move(usize, usize),
+    }
+
+    fn edit() -> impl Strategy<Value = Edit> {
+        prop_oneof![
+            1 => entries().prop_map(Edit::Replace),
+            3 => (0..6usize, entry()).prop_map(|(at, entry)| Edit::Insert(at, entry)),
+            2 => (0..6usize).prop_map(Edit::Remove),
+            2 => (0..6usize, prop::char::range('a', 'e')).prop_map(|(at, c)| Edit::Rename(at, c)),
+            1 => (0..6usize, 0..6usize).prop_map(|(from, to)| Edit::Move(from, to)),
+        ]
+    }
+
+    impl Edit {
+        fn apply(&self, mut value: Vec<Entry>) -> Vec<Entry> {
+            let len = value.len();
+            match self.clone() {
+                Self::Replace(new) => value = new,
+                Self::Insert(at, entry) => value.insert(at.min(len), entry),
+                Self::Remove(at) if at < len => drop(value.remove(at)),
+                Self::Rename(at, name) if at < len => value[at].name = name,
+                Self::Move(from, to) if from < len => {
+                    let entry = value.remove(from);
+                    value.insert(to.min(len - 1), entry);
+                }
+                _ => (),
+            }
+            value
+        }
+    }
+
+    /// An edit made on a replica, or a replica receiving the state of another one.
+    #[derive(Clone, Debug)]
+    enum Step {
+        Edit(usize, Edit),
+        Sync { from: usize, to: usize },
+    }
+
+    fn steps(replicas: usize) -> impl Strategy<Value = Vec<Step>> {
+        let step = prop_oneof![
+            3 => (0..replicas, edit()).prop_map(|(replica, edit)| Step::Edit(replica, edit)),
+            1 => (0..replicas, 0..replicas).prop_map(|(from, to)| Step::Sync { from, to }),
+        ];
+        prop::collection::vec(step, 0..30)
+    }
+
+    /// Replicas starting from the same list, after `steps`.
+    fn run(initial: &[Entry], replicas: usize, steps: &[Step]) -> Vec<List> {
+        let start = List::create(&initial.to_vec(), &mut Clock::new(0, &List::default()));
+        let mut lists = vec![start; replicas];
+        for step in steps {
+            match step {
+                Step::Edit(replica, edit) => {
+                    let list = &mut lists[*replica];
+                    let value = edit.apply(list.value());
+                    let mut clock = Clock::new(*replica as u64 + 1, list);
+                    list.update(&value, &mut clock);
+                    assert_eq!(list.value(), value);
+                }
+                Step::Sync { from, to } => {
+                    let from = lists[*from].clone();
+                    lists[*to].merge(&from);
+                }
+            }
+        }
+        lists
+    }
+
+    proptest! {
+        #[test]
+        fn update_gives_the_new_value(old in entries(), new in entries()) {
+            let mut clock = Clock::new(1, &List::default());
+            let mut list = List::create(&old, &mut clock);
+            list.update(&new, &mut clock);
+            prop_assert_eq!(list.value(), new);
+            prop_assert!(well_formed(&list));
+        }
+
+        #[test]
+        fn replicas_converge(initial in entries(), steps in steps(3)) {
+            let lists = run(&initial, 3, &steps);
+            // every replica receives every other one, in a different order
+            let mut merged = lists.clone();
+            for (i, list) in merged.iter_mut().enumerate() {
+                for j in (0..lists.len()).rev() {
+                    list.merge(&lists[(i + j) % lists.len()]);
+                }
+            }
+            for list in &merged {
+                prop_assert_eq!(list.value(), merged[0].value());
+                prop_assert!(well_formed(list));
+            }
+        }
+
+        #[test]
+        fn merge_is_commutative_associative_and_idempotent(
+            initial in entries(),
+            steps in steps(3),
+        ) {
+            let lists = run(&initial, 3, &steps);
+            let (a, b, c) = (&lists[0], &lists[1], &lists[2]);
+            let merged = |x: &List, y: &List| {
+                let mut x = x.clone();
+                x.merge(y);
+                x
+            };
+            prop_assert_eq!(merged(a, b).value(), merged(b, a).value());
+            prop_assert_eq!(
+                merged(&merged(a, b), c).value(),
+                merged(a, &merged(b, c)).value()
+            );
+            prop_assert_eq!(merged(a, a).value(), a.value());
+            prop_assert_eq!(merged(&merged(a, b), b).value(), merged(a, b).value());
+        }
+
+        #[test]
+        fn concurrent_edits_are_all_kept(initial in entries(), a in entry(), b in entry()) {
+            // one replica appends and the other prepends, both entries are kept in place
+            let lists = run(&initial, 2, &[
+                Step::Edit(0, Edit::Insert(usize::MAX, a.clone())),
+                Step::Edit(1, Edit::Insert(0, b.clone())),
+            ]);
+            let mut merged = lists[0].clone();
+            merged.merge(&lists[1]);
+            let mut expected = vec![b];
+            expected.extend(initial);
+            expected.push(a);
+            prop_assert_eq!(merged.value(), expected);
+        }
+    }
+
+    #[test]
+    fn concurrent_renames_keep_the_latest() {
+        let initial = vec![Entry {
+            name: 'a',
+            items: vec![1, 2],
+        }];
+        let lists = run(
+            &initial,
+            2,
+            &[
+                Step::Edit(0, Edit::Rename(0, 'b')),
+                Step::Edit(1, Edit::Rename(0, 'c')),
+                Step::Edit(1, Edit::Rename(0, 'd')),
+            ],
+        );
+        let mut merged = lists[0].clone();
+        merged.merge(&lists[1]);
+        assert_eq!(merged.value()[0].name, 'd');
+        assert_eq!(merged.value()[0].items, vec![1, 2]);
+    }
+
+    #[test]
+    fn elements_after_unknown_ones_are_kept() {
+        let entry = |name| Entry {
+            name,
+            items: Vec::new(),
+        };
+        let mut clock = Clock::new(1, &List::default());
+        let mut list = List::create(&vec![entry('a'), entry('b'), entry('c')], &mut clock);
+        // a malformed document, without the element the others were inserted after
+        list.items.remove(0);
+        let mut merged = List::default();
+        merged.merge(&list);
+        assert_eq!(merged.value(), [entry('b'), entry('c')]);
+    }
+
+    #[test]
+    fn removed_elements_keep_only_their_place() {
+        let entry = |name| Entry {
+            name,
+            items: vec![1, 2, 3],
+        };
+        let lists = run(
+            &[entry('a'), entry('c')],
+            2,
+            &[
+                Step::Edit(0, Edit::Remove(0)),
+                Step::Edit(1, Edit::Rename(0, 'b')),
+            ],
+        );
+        let mut merged = lists[1].clone();
+        merged.merge(&lists[0]);
+        // the removal wins over the rename
+        assert_eq!(merged.value(), [entry('c')]);
+        let tombstone = merged.items.iter().find(|item| item.removed).unwrap();
+        assert!(tombstone.node.is_none());
+
+        let json = serde_json::to_string(tombstone).unwrap();
+        assert!(!json.contains("node"), "{json}");
+    }
+}
@@ -0,0 +1,100 @@
+//! HTTP requests of the WebDAV and sync clients. They go through a [`Transport`], so the
+//! clients can be tested outside the browser.
+
+use js_sys::Promise;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Headers, RequestInit};
+
+pub struct Request {
+    pub method: &'static str,
+    pub url: String,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: Option<String>,
+}
+
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+/// Sends HTTP requests.
+#[allow(async_fn_in_trait)]
+pub trait Transport {
+    async fn send(&self, request: Request) -> Result<Response, String>;
+}
+
+/// Sends requests with the browser `fetch`, which unlike `gloo-net` allows any method.
+pub struct Fetch;
+
+impl Transport for Fetch {
+    async fn send(&self, request: Request) -> Result<Response, String> {
+        let error = |err: JsValue| format!("{err:?}");
+        let headers = Headers::new().map_err(error)?;
+        for (name, value) in &request.headers {
+            headers.set(name, value).map_err(error)?;
+        }
+        let init = RequestInit::new();
+        init.set_method(request.method);
+        init.set_headers(&headers);
+        if let Some(body) = &request.body {
+            init.set_body(&JsValue::from_str(body));
+        }
+        let window = web_sys::window().ok_or("no window")?;
+        let promise: Promise = window.fetch_with_str_and_init(&request.url, &init);
+        let response: web_sys::Response = JsFuture::from(promise)
+            .await
+            .map_err(|_| "the server can't be reached".to_owned())?
+            .unchecked_into();
+        let text = JsFuture::from(response.text().map_err(error)?)
+            .await
+            .map_err(error)?;
+        Ok(Response {
+            status: response.status(),
+            body: text.as_string().unwrap_or_default(),
+        })
+    }
+}
+
+#[cfg(test)]
+pub mod testing {
+    use std::task::{Context, Poll, Waker};
+
+    use super::*;
+
+    /// Runs a future that never waits, like the ones of [`Ureq`].
+    pub fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        match future
+            .as_mut()
+            .poll(&mut Context::from_waker(Waker::noop()))
+        {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("the future waited"),
+        }
+    }
+
+    /// Sends requests with `ureq`, blocking.
+    pub struct Ureq;
+
+    impl Transport for Ureq {
+        async fn send(&self, request: Request) -> Result<Response, String> {
+            let mut call = ureq::request(request.method, &request.url);
+            for (name, value) in &request.headers {
+                call = call.set(name, value);
+            }
+            let result = match request.body {
+                Some(body) => call.send_string(&body),
+                None => call.call(),
+            };
+            let response = match result {
+                Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+                Err(err) => return Err(err.to_string()),
+            };
+            Ok(Response {
+                status: response.status(),
+                body: response.into_string().unwrap(),
+            })
+        }
+    }
+}
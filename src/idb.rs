@@ -0,0 +1,95 @@
+//! A minimal promise based wrapper around the browser IndexedDB, for data too large or too
+//! numerous for local storage.
+
+use js_sys::Promise;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Event, IdbDatabase, IdbOpenDbRequest, IdbRequest, IdbTransactionMode};
+
+const DB_NAME: &str = "foxhole";
+const DB_VERSION: u32 = 2;
+
+/// Origins whose favicon couldn't be loaded, so they are not asked again for a while.
+pub const FAVICONS: &str = "favicons";
+
+/// Snapshots of the pages, see [`crate::snapshots`].
+pub const SNAPSHOTS: &str = "snapshots";
+
+/// Object stores created on upgrade, keys are given explicitly on every write.
+const STORES: [&str; 2] = [FAVICONS, SNAPSHOTS];
+
+/// Resolves with the result of a request once it succeeds.
+async fn resolve(request: &IdbRequest) -> Result<JsValue, JsValue> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        let on_success = Closure::once_into_js(move |e: Event| {
+            let request: IdbRequest = e.target().unwrap().unchecked_into();
+            let result = request.result().unwrap_or(JsValue::UNDEFINED);
+            resolve.call1(&JsValue::NULL, &result).ok();
+        });
+        let on_error = Closure::once_into_js(move |e: Event| {
+            let request: IdbRequest = e.target().unwrap().unchecked_into();
+            let error = request.error().ok().flatten().map(JsValue::from);
+            reject
+                .call1(&JsValue::NULL, &error.unwrap_or(JsValue::UNDEFINED))
+                .ok();
+        });
+        request.set_onsuccess(Some(on_success.unchecked_ref()));
+        request.set_onerror(Some(on_error.unchecked_ref()));
+    });
+    JsFuture::from(promise).await
+}
+
+async fn open() -> Result<IdbDatabase, JsValue> {
+    let factory = web_sys::window()
+        .ok_or_else(|| JsValue::from("no window"))?
+        .indexed_db()?
+        .ok_or_else(|| JsValue::from("IndexedDB is not available"))?;
+    let request: IdbOpenDbRequest = factory.open_with_u32(DB_NAME, DB_VERSION)?;
+
+    let on_upgrade = Closure::once_into_js(move |e: Event| {
+        let request: IdbOpenDbRequest = e.target().unwrap().unchecked_into();
+        let db: IdbDatabase = request.result().unwrap().unchecked_into();
+        let existing = db.object_store_names();
+        for store in STORES {
+            if !existing.contains(store) {
+                db.create_object_store(store).unwrap();
+            }
+        }
+    });
+    request.set_onupgradeneeded(Some(on_upgrade.unchecked_ref()));
+
+    Ok(resolve(&request).await?.unchecked_into())
+}
+
+/// The value stored under `key`, `undefined` if there is none.
+pub async fn get(store: &str, key: &str) -> Result<JsValue, JsValue> {
+    let db = open().await?;
+    let transaction = db.transaction_with_str(store)?;
+    let request = transaction.object_store(store)?.get(&key.into())?;
+    resolve(&request).await
+}
+
+pub async fn put(store: &str, key: &str, value: &JsValue) -> Result<(), JsValue> {
+    let db = open().await?;
+    let transaction = db.transaction_with_str_and_mode(store, IdbTransactionMode::Readwrite)?;
+    let request = transaction
+        .object_store(store)?
+        .put_with_key(value, &key.into())?;
+    resolve(&request).await.map(|_| ())
+}
+
+pub async fn delete(store: &str, key: &str) -> Result<(), JsValue> {
+    let db = open().await?;
+    let transaction = db.transaction_with_str_and_mode(store, IdbTransactionMode::Readwrite)?;
+    let request = transaction.object_store(store)?.delete(&key.into())?;
+    resolve(&request).await.map(|_| ())
+}
+
+/// Every key of the store, all of them strings.
+pub async fn keys(store: &str) -> Result<Vec<String>, JsValue> {
+    let db = open().await?;
+    let transaction = db.transaction_with_str(store)?;
+    let request = transaction.object_store(store)?.get_all_keys()?;
+    let keys: js_sys::Array = resolve(&request).await?.unchecked_into();
+    Ok(keys.iter().filter_map(|key| key.as_string()).collect())
+}
@@ -0,0 +1,911 @@
+//! Importers for the configuration and exports of other start pages, and for browser
+//! bookmarks. Each file becomes a page of cards, links whose URL can't be parsed or has a
+//! blocked scheme are left out.
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    components::cards::cards_ctx::{Anchor, Card, Group, Page},
+    urls,
+};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Format {
+    /// Homer `config.yml`: `services` of `items`.
+    Homer,
+    /// Dashy `conf.yml`: `sections` of `items`, which may have `subItems`.
+    Dashy,
+    /// Flame categories of bookmarks and applications, as its API returns them.
+    Flame,
+    /// Heimdall items, a list or `{"items": […]}`.
+    Heimdall,
+    /// Firefox `bookmarks-*.json` backups.
+    Firefox,
+    /// The Chromium `Bookmarks` file, also used by Chrome, Edge and Brave.
+    Chromium,
+}
+
+impl Format {
+    pub const ALL: [Format; 6] = [
+        Self::Homer,
+        Self::Dashy,
+        Self::Flame,
+        Self::Heimdall,
+        Self::Firefox,
+        Self::Chromium,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Homer => "homer",
+            Self::Dashy => "dashy",
+            Self::Flame => "flame",
+            Self::Heimdall => "heimdall",
+            Self::Firefox => "firefox",
+            Self::Chromium => "chromium",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Homer => "Homer config.yml",
+            Self::Dashy => "Dashy conf.yml",
+            Self::Flame => "Flame JSON",
+            Self::Heimdall => "Heimdall JSON",
+            Self::Firefox => "Firefox bookmarks JSON",
+            Self::Chromium => "Chromium Bookmarks",
+        }
+    }
+
+    /// The name of the page made when the file has no title.
+    fn title(self) -> &'static str {
+        match self {
+            Self::Homer => "Homer",
+            Self::Dashy => "Dashy",
+            Self::Flame => "Flame",
+            Self::Heimdall => "Heimdall",
+            Self::Firefox => "Firefox",
+            Self::Chromium => "Chromium",
+        }
+    }
+
+    /// Whether the file is a tree of bookmark folders, to pick from with [`pick`].
+    pub fn is_bookmarks(self) -> bool {
+        matches!(self, Self::Firefox | Self::Chromium)
+    }
+
+    /// Guesses the format of a file from its contents.
+    pub fn detect(text: &str) -> Option<Self> {
+        if let Ok(json) = serde_json::from_str::<Value>(text) {
+            if json.get("roots").is_some_and(Value::is_object) {
+                return Some(Self::Chromium);
+            }
+            if json.get("type").and_then(Value::as_str) == Some(FIREFOX_FOLDER) {
+                return Some(Self::Firefox);
+            }
+            let items = match &json {
+                Value::Object(object) => object.get("data").or_else(|| object.get("items")),
+                array => Some(array),
+            };
+            let first = items.and_then(Value::as_array)?.first()?;
+            return if first.get("title").is_some() {
+                Some(Self::Heimdall)
+            } else if first.get("name").is_some() {
+                Some(Self::Flame)
+            } else {
+                None
+            };
+        }
+        let yaml = serde_yaml::from_str::<serde_yaml::Value>(text).ok()?;
+        if yaml.get("services").is_some() {
+            Some(Self::Homer)
+        } else if yaml.get("sections").is_some() {
+            Some(Self::Dashy)
+        } else {
+            None
+        }
+    }
+
+    fn parse(self, text: &str, schemes: &[String]) -> Result<Page, String> {
+        let yaml = |err: serde_yaml::Error| format!("Not a {}: {err}", self.label());
+        let json = |err: serde_json::Error| format!("Not a {}: {err}", self.label());
+        let (title, cards) = match self {
+            Self::Homer => homer(serde_yaml::from_str(text).map_err(yaml)?, schemes),
+            Self::Dashy => dashy(serde_yaml::from_str(text).map_err(yaml)?, schemes),
+            Self::Flame => (
+                None,
+                flame(serde_json::from_str(text).map_err(json)?, schemes),
+            ),
+            Self::Heimdall => (
+                None,
+                heimdall(serde_json::from_str(text).map_err(json)?, schemes),
+            ),
+            Self::Firefox | Self::Chromium => {
+                let folders = self.folders(text, schemes)?;
+                (None, pick(&folders, &picked_by_default(&folders)))
+            }
+        };
+        let title = title.filter(|title| !title.trim().is_empty());
+        Ok(Page {
+            name: title.unwrap_or_else(|| self.title().to_owned()),
+            cards,
+        })
+    }
+
+    /// The folder tree of a bookmarks file.
+    pub fn folders(self, text: &str, schemes: &[String]) -> Result<Vec<Folder>, String> {
+        let json = |err: serde_json::Error| format!("Not a {}: {err}", self.label());
+        let folders = match self {
+            Self::Firefox => firefox(serde_json::from_str(text).map_err(json)?, schemes),
+            Self::Chromium => chromium(serde_json::from_str(text).map_err(json)?, schemes),
+            _ => return Err(format!("A {} has no bookmark folders", self.label())),
+        };
+        Ok(folders)
+    }
+}
+
+/// Reads a file of the given format, or of the one detected when `None`.
+pub fn import(text: &str, format: Option<Format>, schemes: &[String]) -> Result<Page, String> {
+    let format = format
+        .or_else(|| Format::detect(text))
+        .ok_or_else(|| "Can't tell which dashboard this file comes from, pick it".to_owned())?;
+    let page = format.parse(text, schemes)?;
+    if page.cards.is_empty() {
+        return Err(format!("No links found in this {}", format.label()));
+    }
+    Ok(page)
+}
+
+/// A link, `None` without a usable URL.
+fn anchor(label: Option<String>, url: Option<&str>, schemes: &[String]) -> Option<Anchor> {
+    let url = urls::parse(url?, schemes).ok()?;
+    Some(Anchor::new(label.unwrap_or_default(), url).with_default_label())
+}
+
+/// Keeps the icons Foxhole can show: emoji, and images embedded as data URLs up to
+/// [`Anchor::MAX_IMAGE_SIZE`]. Images at other URLs would be loaded from other sites on
+/// every visit, and the icon names of other dashboards (`fas fa-…`, `mdi-…`, `favicon`)
+/// mean nothing here.
+fn set_icon(anchor: &mut Anchor, icon: Option<&str>) {
+    let Some(icon) = icon.map(str::trim).filter(|icon| !icon.is_empty()) else {
+        return;
+    };
+    if icon.starts_with("data:image/") {
+        if icon.len() <= Anchor::MAX_IMAGE_SIZE {
+            anchor.image = Some(icon.to_owned());
+        }
+    } else if !icon.is_ascii() && icon.chars().count() <= 4 {
+        anchor.icon = Some(icon.to_owned());
+    }
+}
+
+fn non_empty(text: Option<String>) -> Option<String> {
+    text.map(|text| text.trim().to_owned())
+        .filter(|text| !text.is_empty())
+}
+
+/// A card of `links`, `None` if there are none.
+fn card(name: Option<String>, fallback: String, links: Vec<Anchor>) -> Option<Card> {
+    (!links.is_empty()).then(|| Card {
+        links,
+        ..Card::new(non_empty(name).unwrap_or(fallback))
+    })
+}
+
+#[derive(Deserialize)]
+struct HomerConfig {
+    title: Option<String>,
+    #[serde(default)]
+    services: Vec<HomerGroup>,
+}
+
+#[derive(Deserialize)]
+struct HomerGroup {
+    name: Option<String>,
+    #[serde(default)]
+    items: Vec<HomerItem>,
+}
+
+#[derive(Deserialize)]
+struct HomerItem {
+    name: Option<String>,
+    url: Option<String>,
+    subtitle: Option<String>,
+    tag: Option<String>,
+    logo: Option<String>,
+    icon: Option<String>,
+}
+
+fn homer(config: HomerConfig, schemes: &[String]) -> (Option<String>, Vec<Card>) {
+    let cards = config
+        .services
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, group)| {
+            let links = group.items.into_iter().filter_map(|item| {
+                let mut link = anchor(item.name, item.url.as_deref(), schemes)?;
+                link.description = non_empty(item.subtitle);
+                link.tags = Anchor::parse_tags(&item.tag.unwrap_or_default());
+                set_icon(&mut link, item.logo.as_deref().or(item.icon.as_deref()));
+                Some(link)
+            });
+            card(group.name, format!("Services {}", i + 1), links.collect())
+        });
+    (config.title, cards.collect())
+}
+
+#[derive(Deserialize)]
+struct DashyConfig {
+    #[serde(rename = "pageInfo")]
+    page_info: Option<DashyPageInfo>,
+    #[serde(default)]
+    sections: Vec<DashySection>,
+}
+
+#[derive(Deserialize)]
+struct DashyPageInfo {
+    title: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct DashySection {
+    name: Option<String>,
+    #[serde(default)]
+    items: Vec<DashyItem>,
+}
+
+#[derive(Deserialize)]
+struct DashyItem {
+    title: Option<String>,
+    url: Option<String>,
+    description: Option<String>,
+    icon: Option<String>,
+    color: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(rename = "subItems", default)]
+    sub_items: Vec<DashyItem>,
+}
+
+fn dashy_link(item: DashyItem, schemes: &[String]) -> Option<Anchor> {
+    let mut link = anchor(item.title, item.url.as_deref(), schemes)?;
+    link.description = non_empty(item.description);
+    link.tags = Anchor::parse_tags(&item.tags.join(","));
+    link.color = item.color.as_deref().and_then(Anchor::parse_color);
+    set_icon(&mut link, item.icon.as_deref());
+    Some(link)
+}
+
+/// Items with sub-items become groups.
+fn dashy(config: DashyConfig, schemes: &[String]) -> (Option<String>, Vec<Card>) {
+    let title = config.page_info.and_then(|info| info.title);
+    let cards = config
+        .sections
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, section)| {
+            let mut links = Vec::new();
+            let mut groups = Vec::new();
+            for mut item in section.items {
+                if item.sub_items.is_empty() {
+                    links.extend(dashy_link(item, schemes));
+                    continue;
+                }
+                let sub_items = std::mem::take(&mut item.sub_items);
+                let group = Group {
+                    links: sub_items
+                        .into_iter()
+                        .filter_map(|item| dashy_link(item, schemes))
+                        .collect(),
+                    ..Group::new(non_empty(item.title).unwrap_or_else(|| "Items".to_owned()))
+                };
+                if !group.links.is_empty() {
+                    groups.push(group);
+                }
+            }
+            let name = non_empty(section.name).unwrap_or_else(|| format!("Section {}", i + 1));
+            (!links.is_empty() || !groups.is_empty()).then(|| Card {
+                links,
+                groups,
+                ..Card::new(name)
+            })
+        });
+    (title, cards.collect())
+}
+
+/// A category of bookmarks, or an application.
+#[derive(Deserialize)]
+struct FlameItem {
+    name: Option<String>,
+    url: Option<String>,
+    icon: Option<String>,
+    description: Option<String>,
+    bookmarks: Option<Vec<FlameItem>>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FlameExport {
+    Api { data: Vec<FlameItem> },
+    List(Vec<FlameItem>),
+}
+
+fn flame_link(item: FlameItem, schemes: &[String]) -> Option<Anchor> {
+    let mut link = anchor(item.name, item.url.as_deref(), schemes)?;
+    link.description = non_empty(item.description);
+    set_icon(&mut link, item.icon.as_deref());
+    Some(link)
+}
+
+/// Categories become cards, applications a card of their own.
+fn flame(export: FlameExport, schemes: &[String]) -> Vec<Card> {
+    let (FlameExport::Api { data: items } | FlameExport::List(items)) = export;
+    let mut cards = Vec::new();
+    let mut apps = Vec::new();
+    for item in items {
+        match item.bookmarks {
+            Some(bookmarks) => {
+                let links = bookmarks
+                    .into_iter()
+                    .filter_map(|bookmark| flame_link(bookmark, schemes))
+                    .collect();
+                cards.extend(card(item.name, "Bookmarks".to_owned(), links));
+            }
+            None => apps.extend(flame_link(item, schemes)),
+        }
+    }
+    cards.splice(0..0, card(None, "Applications".to_owned(), apps));
+    cards
+}
+
+#[derive(Deserialize)]
+struct HeimdallItem {
+    title: Option<String>,
+    url: Option<String>,
+    colour: Option<String>,
+    icon: Option<String>,
+    description: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HeimdallExport {
+    Wrapped { items: Vec<HeimdallItem> },
+    List(Vec<HeimdallItem>),
+}
+
+/// Heimdall files items under tags, each tag becomes a card; untagged items go to one
+/// named `Applications`.
+fn heimdall(export: HeimdallExport, schemes: &[String]) -> Vec<Card> {
+    let (HeimdallExport::Wrapped { items } | HeimdallExport::List(items)) = export;
+    let mut cards: Vec<Card> = Vec::new();
+    for item in items {
+        let name = item
+            .tags
+            .first()
+            .cloned()
+            .unwrap_or_else(|| "Applications".to_owned());
+        let Some(mut link) = anchor(item.title, item.url.as_deref(), schemes) else {
+            continue;
+        };
+        link.description = non_empty(item.description);
+        link.color = item.colour.as_deref().and_then(Anchor::parse_color);
+        link.tags = Anchor::parse_tags(&item.tags.join(","));
+        set_icon(&mut link, item.icon.as_deref());
+        match cards.iter_mut().find(|card| card.name == name) {
+            Some(card) => card.links.push(link),
+            None => cards.push(Card {
+                links: vec![link],
+                ..Card::new(name)
+            }),
+        }
+    }
+    cards
+}
+
+/// A folder of a bookmarks file, with the folders in it.
+#[derive(Clone, PartialEq)]
+pub struct Folder {
+    pub name: String,
+    pub links: Vec<Anchor>,
+    pub folders: Vec<Folder>,
+}
+
+impl Folder {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            links: Vec::new(),
+            folders: Vec::new(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.links.is_empty() && self.folders.is_empty()
+    }
+}
+
+/// The folders and the ones in them, depth first, with how deep each is. Picks given to
+/// [`pick`] are in this order.
+pub fn walk(folders: &[Folder]) -> Vec<(usize, &Folder)> {
+    fn visit<'a>(folders: &'a [Folder], depth: usize, walked: &mut Vec<(usize, &'a Folder)>) {
+        for folder in folders {
+            walked.push((depth, folder));
+            visit(&folder.folders, depth + 1, walked);
+        }
+    }
+    let mut walked = Vec::new();
+    visit(folders, 0, &mut walked);
+    walked
+}
+
+/// What is picked until the user changes it: every folder holding links of its own.
+pub fn picked_by_default(folders: &[Folder]) -> Vec<bool> {
+    walk(folders)
+        .into_iter()
+        .map(|(_, folder)| !folder.links.is_empty())
+        .collect()
+}
+
+/// The cards of the picked folders, in [`walk`] order. A picked folder becomes a card, the
+/// folders in it that aren't picked become its groups, flattened below
+/// [`Card::MAX_GROUP_DEPTH`]. Folders outside any picked one are left out.
+pub fn pick(folders: &[Folder], picked: &[bool]) -> Vec<Card> {
+    let mut cards = Vec::new();
+    let mut index = 0;
+    for folder in folders {
+        pick_folder(folder, picked, &mut index, None, &mut cards);
+    }
+    cards
+}
+
+/// Where the links of a folder that isn't picked go: the links and groups of a card or
+/// group, and how many groups deep that is.
+type Parent<'a> = Option<(&'a mut Vec<Anchor>, &'a mut Vec<Group>, usize)>;
+
+/// Adds `folder`, the one at `index` in [`walk`] order, and the folders in it.
+fn pick_folder(
+    folder: &Folder,
+    picked: &[bool],
+    index: &mut usize,
+    parent: Parent<'_>,
+    cards: &mut Vec<Card>,
+) {
+    let is_picked = picked.get(*index).copied().unwrap_or(false);
+    *index += 1;
+    if is_picked {
+        // before the cards of the folders in it
+        let at = cards.len();
+        let mut card = Card {
+            links: folder.links.clone(),
+            ..Card::new(folder.name.clone())
+        };
+        for inner in &folder.folders {
+            let parent = Some((&mut card.links, &mut card.groups, 0));
+            pick_folder(inner, picked, index, parent, cards);
+        }
+        if !card.links.is_empty() || !card.groups.is_empty() {
+            cards.insert(at, card);
+        }
+        return;
+    }
+    match parent {
+        None => {
+            for inner in &folder.folders {
+                pick_folder(inner, picked, index, None, cards);
+            }
+        }
+        Some((_, groups, depth)) if depth < Card::MAX_GROUP_DEPTH => {
+            let mut group = Group {
+                links: folder.links.clone(),
+                ..Group::new(folder.name.clone())
+            };
+            for inner in &folder.folders {
+                let parent = Some((&mut group.links, &mut group.groups, depth + 1));
+                pick_folder(inner, picked, index, parent, cards);
+            }
+            if !group.links.is_empty() || !group.groups.is_empty() {
+                groups.push(group);
+            }
+        }
+        Some((links, groups, depth)) => {
+            links.extend(folder.links.iter().cloned());
+            for inner in &folder.folders {
+                let parent = Some((&mut *links, &mut *groups, depth));
+                pick_folder(inner, picked, index, parent, cards);
+            }
+        }
+    }
+}
+
+/// The page of the `picked` folders of a bookmarks file, see [`pick`].
+pub fn bookmarks(format: Format, folders: &[Folder], picked: &[bool]) -> Result<Page, String> {
+    let cards = pick(folders, picked);
+    if cards.is_empty() {
+        return Err("Pick a folder with links".to_owned());
+    }
+    Ok(Page {
+        name: format.title().to_owned(),
+        cards,
+    })
+}
+
+const FIREFOX_FOLDER: &str = "text/x-moz-place-container";
+const FIREFOX_BOOKMARK: &str = "text/x-moz-place";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FirefoxItem {
+    title: Option<String>,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    /// Which of the built-in folders this is.
+    root: Option<String>,
+    uri: Option<String>,
+    /// In microseconds since the epoch.
+    date_added: Option<f64>,
+    /// Separated by commas.
+    tags: Option<String>,
+    #[serde(default)]
+    children: Vec<FirefoxItem>,
+}
+
+/// The built-in folders of the backup, the root holding them is skipped.
+fn firefox(root: FirefoxItem, schemes: &[String]) -> Vec<Folder> {
+    root.children
+        .into_iter()
+        .filter(|item| item.kind.as_deref() == Some(FIREFOX_FOLDER))
+        .filter_map(|item| firefox_folder(item, schemes))
+        .collect()
+}
+
+/// A folder, `None` if it holds no links.
+fn firefox_folder(item: FirefoxItem, schemes: &[String]) -> Option<Folder> {
+    // the built-in folders are named by the user's language, or not at all in old backups
+    let name = match item.root.as_deref() {
+        Some("bookmarksMenuFolder") => Some("Bookmarks Menu".to_owned()),
+        Some("toolbarFolder") => Some("Bookmarks Toolbar".to_owned()),
+        Some("unfiledBookmarksFolder") => Some("Other Bookmarks".to_owned()),
+        Some("mobileFolder") => Some("Mobile Bookmarks".to_owned()),
+        _ => non_empty(item.title),
+    };
+    let mut folder = Folder::new(name.unwrap_or_else(|| "Bookmarks".to_owned()));
+    for child in item.children {
+        match child.kind.as_deref() {
+            Some(FIREFOX_FOLDER) => folder.folders.extend(firefox_folder(child, schemes)),
+            Some(FIREFOX_BOOKMARK) => {
+                // `place:` queries and the like have no usable URL
+                let Some(mut link) = anchor(child.title, child.uri.as_deref(), schemes) else {
+                    continue;
+                };
+                link.added = child.date_added.map(|micros| (micros / 1000.0).floor());
+                link.tags = Anchor::parse_tags(child.tags.as_deref().unwrap_or_default());
+                folder.links.push(link);
+            }
+            // separators
+            _ => {}
+        }
+    }
+    (!folder.is_empty()).then_some(folder)
+}
+
+#[derive(Deserialize)]
+struct ChromiumFile {
+    roots: ChromiumRoots,
+}
+
+#[derive(Deserialize)]
+struct ChromiumRoots {
+    bookmark_bar: Option<ChromiumNode>,
+    other: Option<ChromiumNode>,
+    synced: Option<ChromiumNode>,
+}
+
+#[derive(Deserialize)]
+struct ChromiumNode {
+    name: Option<String>,
+    /// `folder` or `url`.
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    url: Option<String>,
+    /// In microseconds since 1601, as a string.
+    date_added: Option<String>,
+    #[serde(default)]
+    children: Vec<ChromiumNode>,
+}
+
+/// Milliseconds between 1601, where Windows and Chromium count time from, and 1970.
+const WINDOWS_EPOCH: f64 = 11_644_473_600_000.0;
+
+fn chromium(file: ChromiumFile, schemes: &[String]) -> Vec<Folder> {
+    let ChromiumRoots {
+        bookmark_bar,
+        other,
+        synced,
+    } = file.roots;
+    [
+        (bookmark_bar, "Bookmarks bar"),
+        (other, "Other bookmarks"),
+        (synced, "Mobile bookmarks"),
+    ]
+    .into_iter()
+    .filter_map(|(node, name)| {
+        let mut node = node?;
+        node.name = non_empty(node.name).or_else(|| Some(name.to_owned()));
+        chromium_folder(node, schemes)
+    })
+    .collect()
+}
+
+/// A folder, `None` if it holds no links.
+fn chromium_folder(node: ChromiumNode, schemes: &[String]) -> Option<Folder> {
+    let mut folder = Folder::new(non_empty(node.name).unwrap_or_else(|| "Bookmarks".to_owned()));
+    for child in node.children {
+        match child.kind.as_deref() {
+            Some("folder") => folder.folders.extend(chromium_folder(child, schemes)),
+            Some("url") => {
+                let Some(mut link) = anchor(child.name, child.url.as_deref(), schemes) else {
+                    continue;
+                };
+                link.added = child
+                    .date_added
+                    .and_then(|micros| micros.parse::<f64>().ok())
+                    .map(|micros| (micros / 1000.0).floor() - WINDOWS_EPOCH)
+                    .filter(|&added| added > 0.0);
+                folder.links.push(link);
+            }
+            _ => {}
+        }
+    }
+    (!folder.is_empty()).then_some(folder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn urls(card: &Card) -> Vec<&str> {
+        card.links.iter().map(|link| link.url.as_str()).collect()
+    }
+
+    #[test]
+    fn imports_homer() {
+        let config = r##"
+title: "Team dashboard"
+subtitle: "Homer"
+services:
+  - name: "Monitoring"
+    icon: "fas fa-heartbeat"
+    items:
+      - name: "Grafana"
+        logo: "https://grafana.example.com/logo.png"
+        subtitle: "Metrics"
+        tag: "ops"
+        url: "https://grafana.example.com"
+      - name: "Evil"
+        url: "javascript:alert(1)"
+      - name: "Prometheus"
+        icon: "fas fa-fire"
+        url: "prometheus.example.com"
+  - items:
+      - name: "No URL"
+"##;
+        assert_eq!(Format::detect(config), Some(Format::Homer));
+        let page = import(config, None, &[]).unwrap();
+        assert_eq!(page.name, "Team dashboard");
+        assert_eq!(page.cards.len(), 1);
+        let card = &page.cards[0];
+        assert_eq!(card.name, "Monitoring");
+        assert_eq!(
+            urls(card),
+            [
+                "https://grafana.example.com/",
+                "https://prometheus.example.com/"
+            ]
+        );
+        let grafana = &card.links[0];
+        assert_eq!(grafana.description.as_deref(), Some("Metrics"));
+        assert_eq!(grafana.tags, ["ops"]);
+        assert_eq!(grafana.image, None);
+        assert_eq!(card.links[1].icon, None);
+    }
+
+    #[test]
+    fn only_small_embedded_icons_are_kept() {
+        let mut link = Anchor::new(
+            "Grafana".to_owned(),
+            "https://grafana.example.com".to_owned(),
+        );
+        let small = "data:image/png;base64,iVBORw0KGgo=";
+        set_icon(&mut link, Some(small));
+        assert_eq!(link.image.as_deref(), Some(small));
+
+        let mut link = Anchor::new(
+            "Grafana".to_owned(),
+            "https://grafana.example.com".to_owned(),
+        );
+        let large = format!(
+            "data:image/png;base64,{}",
+            "A".repeat(Anchor::MAX_IMAGE_SIZE)
+        );
+        for icon in [
+            large.as_str(),
+            "https://grafana.example.com/logo.png",
+            "data:text/html,x",
+        ] {
+            set_icon(&mut link, Some(icon));
+            assert_eq!(link.image, None, "{icon}");
+        }
+    }
+
+    #[test]
+    fn imports_dashy() {
+        let config = r##"
+pageInfo:
+  title: Home Lab
+sections:
+  - name: Media
+    items:
+      - title: Jellyfin
+        description: Movies
+        url: https://jellyfin.example.com
+        icon: "🎬"
+        color: "#ff8f40"
+        tags: [media, video]
+      - title: Arr
+        subItems:
+          - title: Sonarr
+            url: https://sonarr.example.com
+            icon: hl-sonarr
+          - title: Radarr
+            url: https://radarr.example.com
+"##;
+        assert_eq!(Format::detect(config), Some(Format::Dashy));
+        let page = import(config, None, &[]).unwrap();
+        assert_eq!(page.name, "Home Lab");
+        let card = &page.cards[0];
+        assert_eq!(card.name, "Media");
+        let jellyfin = &card.links[0];
+        assert_eq!(jellyfin.icon.as_deref(), Some("🎬"));
+        assert_eq!(jellyfin.color.as_deref(), Some("#ff8f40"));
+        assert_eq!(jellyfin.tags, ["media", "video"]);
+        assert_eq!(card.groups[0].name, "Arr");
+        assert_eq!(card.groups[0].links.len(), 2);
+        assert_eq!(card.groups[0].links[0].icon, None);
+    }
+
+    #[test]
+    fn imports_flame() {
+        let export = r##"{"success": true, "data": [
+            {"name": "Dev", "bookmarks": [
+                {"name": "GitHub", "url": "github.com", "icon": "mdi-github"},
+                {"name": "Bad", "url": "data:text/html,<script>alert(1)</script>"}]},
+            {"name": "Nextcloud", "url": "https://cloud.example.com", "icon": "cloud",
+             "description": "Files"}]}"##;
+        assert_eq!(Format::detect(export), Some(Format::Flame));
+        let page = import(export, None, &[]).unwrap();
+        assert_eq!(page.name, "Flame");
+        let names: Vec<&str> = page.cards.iter().map(|card| card.name.as_str()).collect();
+        assert_eq!(names, ["Applications", "Dev"]);
+        assert_eq!(page.cards[0].links[0].description.as_deref(), Some("Files"));
+        assert_eq!(urls(&page.cards[1]), ["https://github.com/"]);
+    }
+
+    #[test]
+    fn imports_heimdall() {
+        let export = r##"[
+            {"title": "Portainer", "url": "https://portainer.example.com", "colour": "#161b1f",
+             "icon": "icons/portainer.png", "tags": ["infra"]},
+            {"title": "Wiki", "url": "https://wiki.example.com", "colour": "red; x: y"}]"##;
+        assert_eq!(Format::detect(export), Some(Format::Heimdall));
+        let page = import(export, None, &[]).unwrap();
+        let names: Vec<&str> = page.cards.iter().map(|card| card.name.as_str()).collect();
+        assert_eq!(names, ["infra", "Applications"]);
+        let portainer = &page.cards[0].links[0];
+        assert_eq!(portainer.color.as_deref(), Some("#161b1f"));
+        assert_eq!(portainer.image, None);
+        assert_eq!(page.cards[1].links[0].color, None);
+    }
+
+    #[test]
+    fn imports_firefox() {
+        let backup = r##"{"title": "", "type": "text/x-moz-place-container", "root": "placesRoot",
+            "children": [
+                {"title": "menu", "type": "text/x-moz-place-container",
+                 "root": "bookmarksMenuFolder", "children": [
+                    {"title": "Rust", "type": "text/x-moz-place", "uri": "https://www.rust-lang.org/",
+                     "dateAdded": 1700000000123456, "tags": "lang, #dev"},
+                    {"type": "text/x-moz-place-separator"},
+                    {"title": "Recent", "type": "text/x-moz-place", "uri": "place:sort=8"},
+                    {"title": "Reading", "type": "text/x-moz-place-container", "children": [
+                        {"title": "Blog", "type": "text/x-moz-place", "uri": "https://blog.example.com/"}]}]},
+                {"title": "toolbar", "type": "text/x-moz-place-container",
+                 "root": "toolbarFolder", "children": []}]}"##;
+        assert_eq!(Format::detect(backup), Some(Format::Firefox));
+        let folders = Format::Firefox.folders(backup, &[]).unwrap();
+        let walked: Vec<(usize, &str)> = walk(&folders)
+            .into_iter()
+            .map(|(depth, folder)| (depth, folder.name.as_str()))
+            .collect();
+        assert_eq!(walked, [(0, "Bookmarks Menu"), (1, "Reading")]);
+        let rust = &folders[0].links[0];
+        assert_eq!(rust.added, Some(1_700_000_000_123.0));
+        assert_eq!(rust.tags, ["lang", "dev"]);
+        assert_eq!(folders[0].links.len(), 1);
+
+        let page = import(backup, None, &[]).unwrap();
+        let names: Vec<&str> = page.cards.iter().map(|card| card.name.as_str()).collect();
+        assert_eq!(names, ["Bookmarks Menu", "Reading"]);
+    }
+
+    #[test]
+    fn imports_chromium() {
+        let file = r##"{"checksum": "0", "version": 1, "roots": {
+            "bookmark_bar": {"name": "Bookmarks bar", "type": "folder", "children": [
+                {"name": "Docs", "type": "url", "url": "https://docs.rs/",
+                 "date_added": "13345000000000000"},
+                {"name": "Evil", "type": "url", "url": "javascript:alert(1)"}]},
+            "other": {"name": "", "type": "folder", "children": [
+                {"name": "Work", "type": "folder", "children": [
+                    {"name": "Tracker", "type": "url", "url": "https://issues.example.com/"}]}]},
+            "synced": {"name": "Mobile bookmarks", "type": "folder", "children": []}}}"##;
+        assert_eq!(Format::detect(file), Some(Format::Chromium));
+        let folders = Format::Chromium.folders(file, &[]).unwrap();
+        let names: Vec<&str> = walk(&folders)
+            .into_iter()
+            .map(|(_, folder)| folder.name.as_str())
+            .collect();
+        assert_eq!(names, ["Bookmarks bar", "Other bookmarks", "Work"]);
+        assert_eq!(
+            urls(&pick(&folders, &[true, false, false])[0]),
+            ["https://docs.rs/"]
+        );
+        assert_eq!(folders[0].links[0].added, Some(1_700_526_400_000.0));
+    }
+
+    #[test]
+    fn picked_folders_become_cards() {
+        let link =
+            |name: &str| Anchor::new(name.to_owned(), format!("https://{name}.example.com/"));
+        let mut deepest = Folder::new("Deepest".to_owned());
+        deepest.links.push(link("d"));
+        let mut deeper = Folder::new("Deeper".to_owned());
+        deeper.links.push(link("c"));
+        deeper.folders.push(deepest);
+        let mut inner = Folder::new("Inner".to_owned());
+        inner.links.push(link("b"));
+        inner.folders.push(deeper);
+        let mut other = Folder::new("Other".to_owned());
+        other.links.push(link("e"));
+        let mut top = Folder::new("Top".to_owned());
+        top.links.push(link("a"));
+        top.folders.extend([inner, other]);
+        let folders = [top];
+
+        // Top, Inner, Deeper, Deepest, Other
+        assert_eq!(picked_by_default(&folders), [true; 5]);
+        let cards = pick(&folders, &[true, false, false, false, true]);
+        let names: Vec<&str> = cards.iter().map(|card| card.name.as_str()).collect();
+        assert_eq!(names, ["Top", "Other"]);
+        let inner = &cards[0].groups[0];
+        assert_eq!(inner.name, "Inner");
+        assert_eq!(inner.groups[0].name, "Deeper");
+        // too deep for a group of its own
+        assert_eq!(inner.groups[0].links.len(), 2);
+
+        let cards = pick(&folders, &[false, true, false, false, false]);
+        assert_eq!(cards.len(), 1);
+        assert_eq!(cards[0].all_links().len(), 3);
+        assert!(bookmarks(Format::Firefox, &folders, &[false; 5]).is_err());
+    }
+
+    #[test]
+    fn unknown_files_are_refused() {
+        assert!(import("just: yaml", None, &[]).is_err());
+        assert!(import("[]", Some(Format::Flame), &[]).is_err());
+        assert!(import("services: 3", Some(Format::Homer), &[]).is_err());
+    }
+}
@@ -0,0 +1,205 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+
+/// How many columns the card grid has.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Columns {
+    /// Fit as many columns as the card width allows.
+    Auto,
+    Fixed(u8),
+}
+
+impl FromStr for Columns {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            n => match n.parse::<u8>() {
+                Ok(n) if n > 0 => Ok(Self::Fixed(n)),
+                _ => Err(format!("Invalid column count '{s}'")),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Columns {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Auto => write!(f, "auto"),
+            Self::Fixed(n) => write!(f, "{n}"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Density {
+    Compact,
+    Comfortable,
+}
+
+impl FromStr for Density {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "compact" => Ok(Self::Compact),
+            "comfortable" => Ok(Self::Comfortable),
+            _ => Err(format!("Invalid density '{s}'")),
+        }
+    }
+}
+
+impl fmt::Display for Density {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Compact => write!(f, "compact"),
+            Self::Comfortable => write!(f, "comfortable"),
+        }
+    }
+}
+
+/// Horizontal alignment of the card grid inside the page.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Alignment {
+    Start,
+    Center,
+    End,
+}
+
+impl FromStr for Alignment {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "start" => Ok(Self::Start),
+            "center" => Ok(Self::Center),
+            "end" => Ok(Self::End),
+            _ => Err(format!("Invalid alignment '{s}'")),
+        }
+    }
+}
+
+impl fmt::Display for Alignment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Start => write!(f, "start"),
+            Self::Center => write!(f, "center"),
+            Self::End => write!(f, "end"),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Layout {
+    pub columns: Columns,
+    /// Minimum card width in `em`.
+    pub card_min_width: u16,
+    /// Maximum card width in `em`.
+    pub card_max_width: u16,
+    pub density: Density,
+    /// Font size in percent of the browser default.
+    pub font_scale: u16,
+    pub alignment: Alignment,
+}
+
+impl Layout {
+    pub const MIN_FONT_SCALE: u16 = 50;
+    pub const MAX_FONT_SCALE: u16 = 200;
+    pub const MIN_CARD_WIDTH: u16 = 4;
+    pub const MAX_CARD_WIDTH: u16 = 80;
+    pub const MAX_COLUMNS: u8 = 8;
+
+    /// The saved layout, clamped since it may have been edited by hand or synced.
+    pub fn load() -> Self {
+        storage::get::<Self>("layout")
+            .map(Self::normalized)
+            .unwrap_or_default()
+    }
+
+    /// Clamps the numeric settings to sane values.
+    pub fn normalized(mut self) -> Self {
+        if let Columns::Fixed(n) = &mut self.columns {
+            *n = (*n).clamp(1, Self::MAX_COLUMNS);
+        }
+        self.card_min_width = self
+            .card_min_width
+            .clamp(Self::MIN_CARD_WIDTH, Self::MAX_CARD_WIDTH);
+        self.card_max_width = self
+            .card_max_width
+            .clamp(self.card_min_width, Self::MAX_CARD_WIDTH);
+        self.font_scale = self
+            .font_scale
+            .clamp(Self::MIN_FONT_SCALE, Self::MAX_FONT_SCALE);
+        self
+    }
+
+    /// CSS variables consumed by `style.scss`, meant for the `style` attribute of `#app`.
+    pub fn style(&self) -> String {
+        let columns = match self.columns {
+            Columns::Auto => "auto-fit".to_owned(),
+            Columns::Fixed(n) => n.to_string(),
+        };
+        let (padding, gap, link_gap) = match self.density {
+            Density::Compact => ("0.5em", "1em", "0px"),
+            Density::Comfortable => ("1em", "2em", "4px"),
+        };
+        format!(
+            "--columns: {columns}; --card-min-width: {}em; --card-max-width: {}em; \
+             --card-padding: {padding}; --card-gap: {gap}; --link-gap: {link_gap}; \
+             --font-scale: {}; --grid-align: {};",
+            self.card_min_width,
+            self.card_max_width,
+            f32::from(self.font_scale) / 100.0,
+            self.alignment,
+        )
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            columns: Columns::Auto,
+            card_min_width: 16,
+            card_max_width: 24,
+            density: Density::Comfortable,
+            font_scale: 100,
+            alignment: Alignment::Center,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stored_layouts_are_clamped() {
+        let layout = Layout {
+            columns: Columns::Fixed(0),
+            card_min_width: 0,
+            card_max_width: u16::MAX,
+            font_scale: 1,
+            ..Layout::default()
+        }
+        .normalized();
+        assert!(layout.columns == Columns::Fixed(1));
+        assert_eq!(layout.card_min_width, Layout::MIN_CARD_WIDTH);
+        assert_eq!(layout.card_max_width, Layout::MAX_CARD_WIDTH);
+        assert_eq!(layout.font_scale, Layout::MIN_FONT_SCALE);
+
+        let wide = Layout {
+            columns: Columns::Fixed(200),
+            card_min_width: 100,
+            card_max_width: 10,
+            ..Layout::default()
+        }
+        .normalized();
+        assert!(wide.columns == Columns::Fixed(Layout::MAX_COLUMNS));
+        assert_eq!(wide.card_min_width, Layout::MAX_CARD_WIDTH);
+        assert_eq!(wide.card_max_width, Layout::MAX_CARD_WIDTH);
+    }
+}
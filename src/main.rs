@@ -1,18 +1,40 @@
-use std::{rc::Rc, str::FromStr};
+use std::{fmt, rc::Rc, str::FromStr};
 
-use gloo_storage::{LocalStorage, Storage};
 use serde::{Deserialize, Serialize};
 use yew::{
-    classes, function_component, html, use_reducer, ContextProvider, Html, Reducible,
-    UseReducerHandle,
+    classes, function_component, html, use_effect_with_deps, use_reducer, ContextProvider, Html,
+    Reducible, UseReducerHandle,
 };
 
+mod backup;
+mod collections;
 mod components;
+mod crdt;
+mod http;
+mod idb;
+mod import;
+mod layout;
+mod policy;
+mod share;
+mod snapshots;
+mod storage;
+mod sync;
+mod urls;
+mod webdav;
 
-use components::{cards::LinkCards, Bar, Greeting};
+use collections::Subscription;
+use components::{
+    cards::{CardsProvider, LinkCards},
+    confirm::ConfirmProvider,
+    Bar, Greeting,
+};
+use layout::Layout;
+use policy::ConfirmPolicy;
+use sync::SyncConfig;
 
-#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Default, Serialize, Deserialize)]
 pub enum Theme {
+    #[default]
     Dark,
     White,
 }
@@ -24,11 +46,24 @@ impl Theme {
             Self::White => *self = Self::Dark,
         }
     }
-}
 
-impl Default for Theme {
-    fn default() -> Self {
-        Self::Dark
+    /// Accent colors of the theme, as in `style.scss`.
+    pub fn palette(&self) -> &'static [&'static str] {
+        match self {
+            Self::Dark => &[
+                "#ff8f40", "#83a598", "#d5c4a1", "#f2e5bc", "#b8bb26", "#d3869b",
+            ],
+            Self::White => &[
+                "#ff7733", "#86b300", "#828c99", "#399ee6", "#a37acc", "#f07171",
+            ],
+        }
+    }
+
+    pub fn background(&self) -> &'static str {
+        match self {
+            Self::Dark => "#0a0e14",
+            Self::White => "#fafafa",
+        }
     }
 }
 
@@ -44,11 +79,11 @@ impl FromStr for Theme {
     }
 }
 
-impl ToString for Theme {
-    fn to_string(&self) -> String {
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Dark => "dark".to_owned(),
-            Self::White => "white".to_owned(),
+            Self::Dark => write!(f, "dark"),
+            Self::White => write!(f, "white"),
         }
     }
 }
@@ -56,12 +91,37 @@ impl ToString for Theme {
 pub enum CtxAction {
     ToggleTheme,
     ToggleEdit,
+    SetLayout(Layout),
+    /// Narrows the cards to the links with a tag, `None` shows every link.
+    FilterTag(Option<String>),
+    ToggleTagView,
+    /// Replaces the URL schemes approved by the user.
+    SetSchemes(Vec<String>),
+    SetConfirmPolicy(ConfirmPolicy),
+    /// Syncs the cards with a server, `None` stops syncing.
+    SetSync(Option<SyncConfig>),
+    /// Replaces the shared collections shown with the cards.
+    SetCollections(Vec<Subscription>),
+    /// Reads the settings again after another tab changed them.
+    Reload,
 }
 
 #[derive(Clone, PartialEq)]
 pub struct GlobalData {
     pub theme: Theme,
     pub editable: bool,
+    pub layout: Layout,
+    pub tag_filter: Option<String>,
+    /// Show the links with the filtered tag from every card and page in a single card.
+    pub tag_view: bool,
+    /// URL schemes links can use besides [`urls::SAFE_SCHEMES`].
+    pub schemes: Vec<String>,
+    /// Which destructive actions have to be confirmed.
+    pub confirm: ConfirmPolicy,
+    /// The server the cards are synced with, if any.
+    pub sync: Option<SyncConfig>,
+    /// The shared collections whose cards are shown, read-only.
+    pub collections: Vec<Subscription>,
 }
 
 impl Reducible for GlobalData {
@@ -72,9 +132,52 @@ impl Reducible for GlobalData {
         match action {
             CtxAction::ToggleTheme => {
                 data.theme.toggle();
-                LocalStorage::set("theme", data.theme.to_string()).unwrap();
-            },
+                storage::set("theme", data.theme.to_string()).unwrap();
+            }
             CtxAction::ToggleEdit => data.editable = !data.editable,
+            CtxAction::SetLayout(layout) => {
+                data.layout = layout.normalized();
+                storage::set("layout", &data.layout).unwrap();
+            }
+            CtxAction::FilterTag(tag) => {
+                if tag.is_none() {
+                    data.tag_view = false;
+                }
+                data.tag_filter = tag;
+            }
+            CtxAction::ToggleTagView => data.tag_view = !data.tag_view,
+            CtxAction::SetSchemes(schemes) => {
+                storage::set("schemes", &schemes).unwrap();
+                data.schemes = schemes;
+            }
+            CtxAction::SetConfirmPolicy(policy) => {
+                storage::set("confirm", &policy).unwrap();
+                data.confirm = policy;
+            }
+            CtxAction::SetSync(config) => {
+                match &config {
+                    Some(config) => storage::set("sync", config).unwrap(),
+                    None => storage::delete("sync"),
+                }
+                data.sync = config;
+            }
+            CtxAction::SetCollections(collections) => {
+                storage::set("collections", &collections).unwrap();
+                data.collections = collections;
+            }
+            CtxAction::Reload => {
+                if let Some(theme) = storage::get::<String>("theme")
+                    .ok()
+                    .and_then(|theme| theme.parse().ok())
+                {
+                    data.theme = theme;
+                }
+                data.layout = Layout::load();
+                data.schemes = urls::custom_schemes();
+                data.confirm = storage::get("confirm").unwrap_or_default();
+                data.sync = SyncConfig::load();
+                data.collections = Subscription::load();
+            }
         }
         Rc::new(data)
     }
@@ -84,31 +187,71 @@ pub type GlobalCtx = UseReducerHandle<GlobalData>;
 
 #[function_component(App)]
 fn app() -> Html {
-    let theme = match LocalStorage::get::<String>("theme") {
+    let theme = match storage::get::<String>("theme") {
         Ok(val) => Theme::from_str(val.as_str()).unwrap(),
         Err(_) => {
             let default = Theme::default();
-            LocalStorage::set("theme", default.to_string()).unwrap();
+            storage::set("theme", default.to_string()).unwrap();
             default
         }
     };
 
+    let layout = Layout::load();
+
     let global_ctx = use_reducer(|| GlobalData {
         theme,
         editable: false,
+        layout,
+        tag_filter: None,
+        tag_view: false,
+        schemes: urls::custom_schemes(),
+        confirm: storage::get("confirm").unwrap_or_default(),
+        sync: SyncConfig::load(),
+        collections: Subscription::load(),
     });
+    // follow the settings changed in other tabs
+    {
+        let global_ctx = global_ctx.clone();
+        use_effect_with_deps(
+            move |_| {
+                let listener = storage::listen(move |name| {
+                    if [
+                        "theme",
+                        "layout",
+                        "schemes",
+                        "confirm",
+                        "sync",
+                        "collections",
+                    ]
+                    .contains(&name.as_str())
+                    {
+                        global_ctx.dispatch(CtxAction::Reload);
+                    }
+                });
+                move || drop(listener)
+            },
+            (),
+        );
+    }
+
     let app_theme = global_ctx.theme.to_string();
+    let app_style = global_ctx.layout.style();
+    let app_density = global_ctx.layout.density.to_string();
 
     html! {
         <ContextProvider<GlobalCtx> context={global_ctx}>
-            <div id="app" class={classes!(app_theme)}>
-                <Bar />
-                <header>
-                    <h1>{ "Foxhole" }</h1>
-                    <Greeting/>
-                </header>
-                <LinkCards/>
-            </div>
+            <CardsProvider>
+                <div id="app" class={classes!(app_theme, app_density)} style={app_style}>
+                    <ConfirmProvider>
+                        <Bar />
+                        <header>
+                            <h1>{ "Foxhole" }</h1>
+                            <Greeting/>
+                        </header>
+                        <LinkCards/>
+                    </ConfirmProvider>
+                </div>
+            </CardsProvider>
         </ContextProvider<GlobalCtx>>
     }
 }
@@ -0,0 +1,102 @@
+//! The confirmation policy: which destructive actions ask the user first, and how. Every
+//! action removing links goes through it, the settings let the user tune it.
+
+use serde::{Deserialize, Serialize};
+
+/// What to ask the user before a destructive action.
+#[derive(Clone, PartialEq)]
+pub enum Ask {
+    Nothing,
+    /// A yes/no dialog.
+    Confirm,
+    /// The user has to type this text, usually the name of what is removed.
+    Type(String),
+}
+
+/// When removals have to be confirmed.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConfirmPolicy {
+    /// Confirm removing cards and groups that have links.
+    pub cards: bool,
+    /// Type the name to remove cards and groups with more links than this, 0 never asks.
+    pub type_above: usize,
+    /// Confirm removing a single link.
+    pub links: bool,
+    /// Confirm actions touching many links at once, like removing a page or a profile.
+    pub bulk: bool,
+}
+
+impl ConfirmPolicy {
+    /// Before removing a card or group called `name` with `links` links, nested ones included.
+    pub fn container(&self, name: &str, links: usize) -> Ask {
+        match links {
+            0 => Ask::Nothing,
+            n if self.type_above > 0 && n > self.type_above => Ask::Type(name.to_owned()),
+            _ if self.cards => Ask::Confirm,
+            _ => Ask::Nothing,
+        }
+    }
+
+    pub fn link(&self) -> Ask {
+        if self.links {
+            Ask::Confirm
+        } else {
+            Ask::Nothing
+        }
+    }
+
+    pub fn bulk(&self) -> Ask {
+        if self.bulk {
+            Ask::Confirm
+        } else {
+            Ask::Nothing
+        }
+    }
+
+    /// Before removing `removed` duplicates of a link: one is a single link, more are bulk.
+    pub fn duplicates(&self, removed: usize) -> Ask {
+        match removed {
+            0 => Ask::Nothing,
+            1 => self.link(),
+            _ => self.bulk(),
+        }
+    }
+}
+
+impl Default for ConfirmPolicy {
+    fn default() -> Self {
+        Self {
+            cards: true,
+            type_above: 20,
+            links: false,
+            bulk: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn asks_by_what_is_removed() {
+        let policy = ConfirmPolicy::default();
+        assert!(policy.container("Empty", 0) == Ask::Nothing);
+        assert!(policy.container("Work", 3) == Ask::Confirm);
+        assert!(policy.container("Work", 21) == Ask::Type("Work".to_owned()));
+        assert!(policy.link() == Ask::Nothing);
+        assert!(policy.duplicates(1) == Ask::Nothing);
+        assert!(policy.duplicates(2) == Ask::Confirm);
+
+        let careless = ConfirmPolicy {
+            cards: false,
+            type_above: 0,
+            links: true,
+            bulk: false,
+        };
+        assert!(careless.container("Work", 100) == Ask::Nothing);
+        assert!(careless.duplicates(1) == Ask::Confirm);
+        assert!(careless.duplicates(5) == Ask::Nothing);
+    }
+}
@@ -0,0 +1,227 @@
+//! Sharing a single card with a link: the card is compressed into the URL fragment, which
+//! browsers never send to a server.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use miniz_oxide::{deflate::compress_to_vec, inflate::decompress_to_vec_with_limit};
+
+use crate::{
+    components::cards::cards_ctx::{Anchor, Card, Group},
+    urls,
+};
+
+const PREFIX: &str = "#card=";
+/// The longest fragment made or accepted, some browsers and chat apps cut longer links.
+pub const MAX_FRAGMENT: usize = 32 * 1024;
+/// What a fragment may inflate to at most, so a small link can't fill the memory.
+const MAX_JSON: usize = 1024 * 1024;
+const MAX_LINKS: usize = 500;
+/// The longest name, label, URL or description kept, notes may be longer.
+const MAX_TEXT: usize = 2000;
+const MAX_NOTES: usize = 20_000;
+/// Tags are a word or two, and a link needs only a few.
+const MAX_TAG: usize = 50;
+const MAX_TAGS: usize = 20;
+/// Icons are an emoji or a few letters.
+const MAX_ICON: usize = 8;
+
+/// The URL fragment sharing `card`. What comes from using the card stays home: clicks,
+/// dates and uploaded icons.
+pub fn fragment(card: &Card) -> Result<String, String> {
+    let mut card = card.clone();
+    card.collapsed = false;
+    card.pinned = false;
+    card.links_mut(&mut |link| {
+        link.clicks = 0;
+        link.added = None;
+        link.image = None;
+    });
+    let json = serde_json::to_vec(&card).map_err(|err| err.to_string())?;
+    let fragment = format!(
+        "{PREFIX}{}",
+        URL_SAFE_NO_PAD.encode(compress_to_vec(&json, 9))
+    );
+    if fragment.len() > MAX_FRAGMENT {
+        return Err(format!("\"{}\" is too large to share as a link", card.name));
+    }
+    Ok(fragment)
+}
+
+/// A link opening Foxhole with `card`, see [`fragment`].
+pub fn url(card: &Card) -> Result<String, String> {
+    let location = web_sys::window().unwrap().location();
+    let base = format!(
+        "{}{}{}",
+        location.origin().unwrap_or_default(),
+        location.pathname().unwrap_or_default(),
+        location.search().unwrap_or_default()
+    );
+    Ok(base + &fragment(card)?)
+}
+
+/// The card shared by a URL fragment, `None` if the fragment shares none. The card is
+/// sanitized, see [`sanitize`].
+pub fn parse(fragment: &str, schemes: &[String]) -> Option<Result<Card, String>> {
+    let data = fragment.strip_prefix(PREFIX)?;
+    Some(decode(data, schemes))
+}
+
+fn decode(data: &str, schemes: &[String]) -> Result<Card, String> {
+    if data.len() > MAX_FRAGMENT {
+        return Err("The shared card is too large".to_owned());
+    }
+    let damaged = |_| "The link is damaged, copy all of it".to_owned();
+    let compressed = URL_SAFE_NO_PAD.decode(data).map_err(damaged)?;
+    let json = decompress_to_vec_with_limit(&compressed, MAX_JSON)
+        .map_err(|_| "The link is damaged or the shared card too large".to_owned())?;
+    let mut card: Card =
+        serde_json::from_slice(&json).map_err(|_| "The link doesn't share a card".to_owned())?;
+    sanitize(&mut card, schemes)?;
+    Ok(card)
+}
+
+/// Cuts `text` to at most `max` bytes, on a character boundary.
+fn clip(text: &mut String, max: usize) {
+    if text.len() > max {
+        let end = (0..=max)
+            .rev()
+            .find(|&i| text.is_char_boundary(i))
+            .unwrap_or(0);
+        text.truncate(end);
+    }
+}
+
+/// Drops the groups nested deeper than the app shows, `depth` is the one of `groups`.
+fn prune(groups: &mut Vec<Group>, depth: usize) {
+    if depth > Card::MAX_GROUP_DEPTH {
+        groups.clear();
+    }
+    for group in groups {
+        clip(&mut group.name, MAX_TEXT);
+        prune(&mut group.groups, depth + 1);
+    }
+}
+
+/// Makes a card from someone else safe to show and keep: links with a blocked scheme are
+/// dropped, texts are cut, colors checked and uploaded images removed, since they would
+/// load from wherever the sender likes.
+fn sanitize(card: &mut Card, schemes: &[String]) -> Result<(), String> {
+    clip(&mut card.name, MAX_TEXT);
+    if card.name.trim().is_empty() {
+        card.name = "Shared card".to_owned();
+    }
+    card.collapsed = false;
+    card.pinned = false;
+    prune(&mut card.groups, 1);
+    card.retain_links(&mut |link| link.url.len() <= MAX_TEXT && urls::is_safe(&link.url, schemes));
+    if card.all_links().len() > MAX_LINKS {
+        return Err(format!("The shared card has more than {MAX_LINKS} links"));
+    }
+    card.links_mut(&mut |link: &mut Anchor| {
+        clip(&mut link.label, MAX_TEXT);
+        // cut before parsing, which compares every tag with the ones before it
+        link.tags.truncate(MAX_TAGS);
+        link.tags.iter_mut().for_each(|tag| clip(tag, MAX_TAG));
+        link.tags = Anchor::parse_tags(&link.tags.join(","));
+        link.tags.truncate(MAX_TAGS);
+        link.added = None;
+        link.clicks = 0;
+        link.image = None;
+        link.color = link.color.as_deref().and_then(Anchor::parse_color);
+        if let Some(description) = &mut link.description {
+            clip(description, MAX_TEXT);
+        }
+        if let Some(notes) = &mut link.notes {
+            clip(notes, MAX_NOTES);
+        }
+        if let Some(icon) = &mut link.icon {
+            clip(icon, MAX_ICON);
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card() -> Card {
+        let mut card = Card::new("Runbooks".to_owned());
+        card.links.push(Anchor {
+            clicks: 12,
+            tags: vec!["ops".to_owned()],
+            ..Anchor::new("Wiki".to_owned(), "https://wiki.example.com".to_owned())
+        });
+        let mut group = Group::new("Dashboards".to_owned());
+        group.links.push(Anchor::new(
+            "Grafana".to_owned(),
+            "https://grafana.example.com".to_owned(),
+        ));
+        card.groups.push(group);
+        card
+    }
+
+    #[test]
+    fn cards_survive_the_trip() {
+        let fragment = fragment(&card()).unwrap();
+        assert!(fragment.starts_with("#card="));
+        let shared = parse(&fragment, &[]).unwrap().unwrap();
+        assert_eq!(shared.name, "Runbooks");
+        assert_eq!(shared.links[0].tags, ["ops"]);
+        assert_eq!(shared.links[0].clicks, 0);
+        assert_eq!(shared.groups[0].links[0].url, "https://grafana.example.com");
+        assert!(parse("#page=Home", &[]).is_none());
+    }
+
+    #[test]
+    fn incoming_cards_are_sanitized() {
+        let mut card = card();
+        card.name = "é".repeat(MAX_TEXT);
+        card.links.push(Anchor {
+            color: Some("red;background:url(https://evil.example.com)".to_owned()),
+            image: Some("https://evil.example.com/pixel.png".to_owned()),
+            tags: (0..10 * MAX_TAGS)
+                .map(|i| format!("{i}{}", "x".repeat(MAX_TEXT)))
+                .collect(),
+            ..Anchor::new("Ok".to_owned(), "https://example.com".to_owned())
+        });
+        card.links.push(Anchor::new(
+            "Bad".to_owned(),
+            "javascript:alert(1)".to_owned(),
+        ));
+        let mut deep = Group::new("Level 1".to_owned());
+        deep.groups.push(Group::new("Level 2".to_owned()));
+        deep.groups[0].groups.push(Group::new("Level 3".to_owned()));
+        card.groups.push(deep);
+        let json = serde_json::to_vec(&card).unwrap();
+        let data = URL_SAFE_NO_PAD.encode(compress_to_vec(&json, 9));
+
+        let shared = parse(&format!("#card={data}"), &[]).unwrap().unwrap();
+        assert_eq!(shared.name.len(), MAX_TEXT);
+        let urls: Vec<&str> = shared.links.iter().map(|l| l.url.as_str()).collect();
+        assert_eq!(urls, ["https://wiki.example.com", "https://example.com"]);
+        assert_eq!(shared.links[1].color, None);
+        assert_eq!(shared.links[1].image, None);
+        assert_eq!(shared.links[1].tags.len(), MAX_TAGS);
+        assert!(shared.links[1].tags.iter().all(|tag| tag.len() <= MAX_TAG));
+        assert!(shared.groups[1].groups[0].groups.is_empty());
+    }
+
+    #[test]
+    fn damaged_and_huge_links_are_refused() {
+        assert!(parse("#card=not base64!", &[]).unwrap().is_err());
+        assert!(parse("#card=AAAA", &[]).unwrap().is_err());
+
+        let mut huge = Card::new("Everything".to_owned());
+        huge.links = (0..=MAX_LINKS)
+            .map(|i| Anchor::new(String::new(), format!("https://example.com/{i}")))
+            .collect();
+        let json = serde_json::to_vec(&huge).unwrap();
+        let data = URL_SAFE_NO_PAD.encode(compress_to_vec(&json, 9));
+        assert!(parse(&format!("#card={data}"), &[]).unwrap().is_err());
+
+        // a small fragment inflating to a lot
+        let bomb = URL_SAFE_NO_PAD.encode(compress_to_vec(&vec![b' '; 2 * MAX_JSON], 9));
+        assert!(bomb.len() < MAX_FRAGMENT);
+        assert!(parse(&format!("#card={bomb}"), &[]).unwrap().is_err());
+    }
+}
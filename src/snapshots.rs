@@ -0,0 +1,339 @@
+//! Snapshots of the pages kept in IndexedDB, one a day and one a week, to go back further
+//! than undo reaches.
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+
+use crate::{
+    components::cards::cards_ctx::{Card, Page},
+    idb, storage,
+};
+
+const DAY_MS: f64 = 86_400_000.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Kind {
+    Daily,
+    Weekly,
+}
+
+impl Kind {
+    const ALL: [Kind; 2] = [Kind::Daily, Kind::Weekly];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Daily => "Daily",
+            Self::Weekly => "Weekly",
+        }
+    }
+
+    /// How many snapshots of this kind are kept.
+    fn keep(self) -> usize {
+        match self {
+            Self::Daily => 7,
+            Self::Weekly => 4,
+        }
+    }
+
+    /// The first day of the period `day` is in, weeks start on Monday. Days are counted
+    /// from the epoch.
+    fn start(self, day: i64) -> i64 {
+        match self {
+            Self::Daily => day,
+            // the epoch was a Thursday
+            Self::Weekly => day - (day + 3).rem_euclid(7),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Milliseconds since the epoch.
+    pub taken: f64,
+    pub pages: Vec<Page>,
+}
+
+/// A stored snapshot of the active profile.
+#[derive(Clone, PartialEq)]
+pub struct Entry {
+    pub key: String,
+    pub kind: Kind,
+    /// The first day of the period of the snapshot, `YYYY-MM-DD`.
+    pub date: String,
+    pub snapshot: Snapshot,
+}
+
+/// The start of the keys of the active profile, the store is shared by all of them.
+fn prefix() -> String {
+    format!("{}/", storage::key("snapshot"))
+}
+
+/// The key of the snapshot of the period `day` is in, e.g. `snapshot/weekly/2026-10-19`.
+fn key(prefix: &str, kind: Kind, day: i64) -> String {
+    format!("{prefix}{}/{}", kind.name(), date(kind.start(day)))
+}
+
+fn parse(prefix: &str, key: &str) -> Option<(Kind, String)> {
+    let (kind, date) = key.strip_prefix(prefix)?.split_once('/')?;
+    let kind = Kind::ALL.into_iter().find(|k| k.name() == kind)?;
+    Some((kind, date.to_owned()))
+}
+
+/// The `YYYY-MM-DD` date of a day counted from the epoch.
+fn date(day: i64) -> String {
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = day + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    format!("{y:04}-{m:02}-{d:02}")
+}
+
+/// The keys of the snapshots to drop, the oldest ones beyond what each kind keeps.
+fn expired(prefix: &str, keys: &[String]) -> Vec<String> {
+    Kind::ALL
+        .into_iter()
+        .flat_map(|kind| {
+            let start = format!("{prefix}{}/", kind.name());
+            let mut own: Vec<&String> = keys.iter().filter(|k| k.starts_with(&start)).collect();
+            own.sort_by(|a, b| b.cmp(a));
+            own.into_iter()
+                .skip(kind.keep())
+                .cloned()
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Today counted from the epoch, in local time so days start at midnight.
+fn today() -> i64 {
+    let now = js_sys::Date::new_0();
+    let offset = now.get_timezone_offset() * 60_000.0;
+    ((now.get_time() - offset) / DAY_MS).floor() as i64
+}
+
+/// Takes the snapshots of today and of this week unless they exist, then drops the
+/// expired ones. A snapshot keeps the first pages seen in its period, the ones to go back
+/// to after a bad edit.
+pub async fn take(pages: &[Page]) -> Result<(), JsValue> {
+    let prefix = prefix();
+    let day = today();
+    let mut keys = idb::keys(idb::SNAPSHOTS).await?;
+    let snapshot = Snapshot {
+        taken: js_sys::Date::now(),
+        pages: pages.to_vec(),
+    };
+    let json = serde_json::to_string(&snapshot).map_err(|err| JsValue::from(err.to_string()))?;
+    for kind in Kind::ALL {
+        let key = key(&prefix, kind, day);
+        if !keys.contains(&key) {
+            idb::put(idb::SNAPSHOTS, &key, &JsValue::from_str(&json)).await?;
+            keys.push(key);
+        }
+    }
+    for key in expired(&prefix, &keys) {
+        idb::delete(idb::SNAPSHOTS, &key).await?;
+    }
+    Ok(())
+}
+
+/// The snapshots of the active profile, newest first.
+pub async fn list() -> Result<Vec<Entry>, JsValue> {
+    let prefix = prefix();
+    let mut entries = Vec::new();
+    for key in idb::keys(idb::SNAPSHOTS).await? {
+        let Some((kind, date)) = parse(&prefix, &key) else {
+            continue;
+        };
+        let value = idb::get(idb::SNAPSHOTS, &key).await?;
+        let snapshot = value
+            .as_string()
+            .and_then(|json| serde_json::from_str::<Snapshot>(&json).ok());
+        if let Some(snapshot) = snapshot {
+            entries.push(Entry {
+                key,
+                kind,
+                date,
+                snapshot,
+            });
+        }
+    }
+    entries.sort_by(|a, b| b.date.cmp(&a.date).then(a.kind.cmp(&b.kind)));
+    Ok(entries)
+}
+
+/// What became of a card of a snapshot. Cards are told apart by their page and name.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Change {
+    Same,
+    Changed,
+    Removed,
+}
+
+/// The card without what changes by merely using it: clicks and collapsing.
+fn used(card: &Card) -> Card {
+    let mut card = card.clone();
+    card.collapsed = false;
+    card.links_mut(&mut |link| link.clicks = 0);
+    card
+}
+
+/// What became of `card`, of the page named `page` in a snapshot, in the `current` pages.
+pub fn change(page: &str, card: &Card, current: &[Page]) -> Change {
+    let now = current
+        .iter()
+        .filter(|p| p.name == page)
+        .flat_map(|p| &p.cards)
+        .find(|c| c.name == card.name);
+    match now {
+        None => Change::Removed,
+        Some(now) if used(now) != used(card) => Change::Changed,
+        Some(_) => Change::Same,
+    }
+}
+
+/// How many cards changed between a snapshot and the current pages.
+#[derive(Default, PartialEq, Debug)]
+pub struct Diff {
+    /// Cards of the snapshot since removed or renamed.
+    pub removed: usize,
+    /// Cards that are not in the snapshot.
+    pub added: usize,
+    pub changed: usize,
+}
+
+pub fn diff(snapshot: &[Page], current: &[Page]) -> Diff {
+    let mut diff = Diff::default();
+    for page in snapshot {
+        for card in &page.cards {
+            match change(&page.name, card, current) {
+                Change::Removed => diff.removed += 1,
+                Change::Changed => diff.changed += 1,
+                Change::Same => {}
+            }
+        }
+    }
+    for page in current {
+        for card in &page.cards {
+            if change(&page.name, card, snapshot) == Change::Removed {
+                diff.added += 1;
+            }
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::cards::cards_ctx::Anchor;
+
+    fn page(name: &str, cards: Vec<Card>) -> Page {
+        Page {
+            name: name.to_owned(),
+            cards,
+        }
+    }
+
+    #[test]
+    fn periods_have_one_key_each() {
+        // 2026-10-19 is a Monday
+        let monday = 20_745;
+        assert_eq!(date(monday), "2026-10-19");
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(11_016), "2000-02-29");
+        for day in monday..monday + 7 {
+            assert_eq!(
+                key("snapshot/", Kind::Weekly, day),
+                "snapshot/weekly/2026-10-19"
+            );
+        }
+        assert_eq!(
+            key("snapshot/", Kind::Weekly, monday - 1),
+            "snapshot/weekly/2026-10-12"
+        );
+        assert_eq!(
+            key("snapshot/", Kind::Daily, monday + 1),
+            "snapshot/daily/2026-10-20"
+        );
+        assert_eq!(
+            parse("snapshot/", "snapshot/daily/2026-10-20"),
+            Some((Kind::Daily, "2026-10-20".to_owned()))
+        );
+        assert_eq!(
+            parse("snapshot/", "profile:work:snapshot/daily/2026-10-20"),
+            None
+        );
+    }
+
+    #[test]
+    fn only_the_newest_snapshots_are_kept() {
+        let mut keys: Vec<String> = (0..10)
+            .map(|day| key("snapshot/", Kind::Daily, 20_745 + day))
+            .chain((0..5).map(|week| key("snapshot/", Kind::Weekly, 20_745 + 7 * week)))
+            .collect();
+        keys.push("profile:work:snapshot/daily/2026-01-01".to_owned());
+        let mut expired = expired("snapshot/", &keys);
+        expired.sort();
+        assert_eq!(
+            expired,
+            [
+                "snapshot/daily/2026-10-19",
+                "snapshot/daily/2026-10-20",
+                "snapshot/daily/2026-10-21",
+                "snapshot/weekly/2026-10-19",
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_ignores_clicks_and_collapsing() {
+        let mut news = Card::new("News".to_owned());
+        news.links
+            .push(Anchor::new("Example".into(), "https://example.com".into()));
+        let snapshot = vec![page(
+            "Home",
+            vec![news.clone(), Card::new("Work".to_owned())],
+        )];
+
+        let mut used = news.clone();
+        used.collapsed = true;
+        used.links[0].clicks = 3;
+        let mut renamed = Card::new("Office".to_owned());
+        renamed.pinned = true;
+        let current = vec![page("Home", vec![used.clone(), renamed])];
+        assert_eq!(change("Home", &news, &current), Change::Same);
+        assert_eq!(
+            diff(&snapshot, &current),
+            Diff {
+                removed: 1,
+                added: 1,
+                changed: 0,
+            }
+        );
+
+        used.links[0].label = "Examples".to_owned();
+        let current = vec![page("Home", vec![used]), page("Other", vec![news.clone()])];
+        assert_eq!(change("Home", &news, &current), Change::Changed);
+        assert_eq!(
+            diff(&snapshot, &current),
+            Diff {
+                removed: 1,
+                added: 1,
+                changed: 1,
+            }
+        );
+    }
+}
@@ -0,0 +1,236 @@
+//! Browser local storage namespaced by the active profile.
+//!
+//! Every profile keeps its own cards, theme, user name and settings. The default profile
+//! uses the bare keys, so data stored before profiles existed belongs to it.
+
+use gloo_storage::{LocalStorage, Storage};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+use serde::{de::DeserializeOwned, Serialize};
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::StorageEvent;
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Escaped in the profile names of keys, so that a profile's prefix never starts another one.
+const PROFILE_NAME: &AsciiSet = &CONTROLS.add(b':').add(b'%');
+
+thread_local! {
+    static PROFILE: String = active_profile();
+}
+
+/// The profile from the `?profile=` URL parameter, or the last used one.
+fn active_profile() -> String {
+    let from_url = web_sys::window()
+        .and_then(|window| window.location().search().ok())
+        .and_then(|search| profile_param(&search));
+
+    let profile = match from_url {
+        Some(name) => {
+            // a homepage pointing to an unknown profile creates it
+            add_profile(&name);
+            name
+        }
+        None => LocalStorage::get::<String>("profile")
+            .ok()
+            .filter(|name| profiles().contains(name))
+            .unwrap_or_else(|| DEFAULT_PROFILE.to_owned()),
+    };
+    if let Err(err) = LocalStorage::set("profile", &profile) {
+        web_sys::console::log_1(&format!("{err}").into());
+    }
+    profile
+}
+
+/// The `profile` parameter of the query string `search`, if it names a profile.
+fn profile_param(search: &str) -> Option<String> {
+    search
+        .trim_start_matches('?')
+        .split('&')
+        .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+        .find(|(name, _)| *name == "profile")
+        .map(|(_, value)| {
+            let value = value.replace('+', " ");
+            percent_decode_str(&value).decode_utf8_lossy().into_owned()
+        })
+        .filter(|name| !name.trim().is_empty())
+}
+
+/// Name of the profile in use.
+pub fn profile() -> String {
+    PROFILE.with(Clone::clone)
+}
+
+/// The storage key of `name` for `profile`.
+fn profile_key(profile: &str, name: &str) -> String {
+    match profile {
+        DEFAULT_PROFILE => name.to_owned(),
+        profile => format!(
+            "profile:{}:{name}",
+            utf8_percent_encode(profile, PROFILE_NAME)
+        ),
+    }
+}
+
+/// The name of a storage key of `profile`, the inverse of [`profile_key`].
+fn key_name(profile: &str, key: &str) -> Option<String> {
+    match profile {
+        DEFAULT_PROFILE => (!key.starts_with("profile:")).then(|| key.to_owned()),
+        profile => key
+            .strip_prefix(&profile_key(profile, ""))
+            .map(str::to_owned),
+    }
+}
+
+/// The storage key of `name` for the active profile.
+pub fn key(name: &str) -> String {
+    PROFILE.with(|profile| profile_key(profile, name))
+}
+
+/// The name of a storage key of the active profile, the inverse of [`key`].
+fn name(key: &str) -> Option<String> {
+    PROFILE.with(|profile| key_name(profile, key))
+}
+
+pub fn get<T: DeserializeOwned>(name: &str) -> gloo_storage::Result<T> {
+    LocalStorage::get(key(name))
+}
+
+pub fn set<T: Serialize>(name: &str, value: T) -> gloo_storage::Result<()> {
+    LocalStorage::set(key(name), value)
+}
+
+pub fn delete(name: &str) {
+    LocalStorage::delete(key(name))
+}
+
+/// Listens for values of the active profile changed by other tabs, stops when dropped.
+pub struct Listener {
+    callback: Closure<dyn Fn(StorageEvent)>,
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        if let Some(window) = web_sys::window() {
+            let callback = self.callback.as_ref().unchecked_ref();
+            if let Err(err) = window.remove_event_listener_with_callback("storage", callback) {
+                web_sys::console::log_1(&err);
+            }
+        }
+    }
+}
+
+/// Calls `on_change` with the name of every value another tab changes in the active profile.
+/// The tab making a change is not notified.
+pub fn listen(on_change: impl Fn(String) + 'static) -> Listener {
+    let callback = Closure::<dyn Fn(StorageEvent)>::new(move |event: StorageEvent| {
+        if let Some(name) = event.key().as_deref().and_then(name) {
+            on_change(name);
+        }
+    });
+    if let Some(window) = web_sys::window() {
+        let listener = callback.as_ref().unchecked_ref();
+        if let Err(err) = window.add_event_listener_with_callback("storage", listener) {
+            web_sys::console::log_1(&err);
+        }
+    }
+    Listener { callback }
+}
+
+/// All known profiles, the default one is always the first.
+pub fn profiles() -> Vec<String> {
+    let mut profiles = LocalStorage::get::<Vec<String>>("profiles").unwrap_or_default();
+    profiles.retain(|name| name != DEFAULT_PROFILE);
+    profiles.insert(0, DEFAULT_PROFILE.to_owned());
+    profiles
+}
+
+pub fn add_profile(name: &str) {
+    let mut profiles = profiles();
+    if profiles.iter().any(|profile| profile == name) {
+        return;
+    }
+    profiles.push(name.to_owned());
+    if let Err(err) = LocalStorage::set("profiles", profiles) {
+        web_sys::console::log_1(&format!("{err}").into());
+    }
+}
+
+/// Forgets a profile and deletes all of its data. The default profile can't be removed.
+pub fn remove_profile(name: &str) {
+    if name == DEFAULT_PROFILE {
+        return;
+    }
+    let mut profiles = profiles();
+    profiles.retain(|profile| profile != name);
+    if let Err(err) = LocalStorage::set("profiles", profiles) {
+        web_sys::console::log_1(&format!("{err}").into());
+    }
+
+    let storage = LocalStorage::raw();
+    let keys: Vec<String> = (0..LocalStorage::length())
+        .filter_map(|i| storage.key(i).ok().flatten())
+        .filter(|key| key_name(name, key).is_some())
+        .collect();
+    keys.into_iter().for_each(LocalStorage::delete);
+}
+
+/// Reloads the page using another profile.
+pub fn switch_profile(name: &str) {
+    if let Err(err) = LocalStorage::set("profile", name) {
+        web_sys::console::log_1(&format!("{err}").into());
+    }
+    let search = format!("?profile={}", js_sys::encode_uri_component(name));
+    if let Some(window) = web_sys::window() {
+        if let Err(err) = window.location().set_search(&search) {
+            web_sys::console::log_1(&err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_profile_is_read_from_the_query() {
+        assert_eq!(profile_param("?profile=work").as_deref(), Some("work"));
+        assert_eq!(
+            profile_param("?theme=dark&profile=My%20work").as_deref(),
+            Some("My work")
+        );
+        assert_eq!(profile_param("?profile=a+b").as_deref(), Some("a b"));
+        assert_eq!(profile_param("?profile=%20"), None);
+        assert_eq!(profile_param("?profile"), None);
+        assert_eq!(profile_param("?profiles=work"), None);
+        assert_eq!(profile_param(""), None);
+    }
+
+    #[test]
+    fn the_default_profile_keeps_the_bare_keys() {
+        assert_eq!(profile_key(DEFAULT_PROFILE, "cards"), "cards");
+        assert_eq!(key_name(DEFAULT_PROFILE, "cards").as_deref(), Some("cards"));
+        assert_eq!(key_name(DEFAULT_PROFILE, "profile:work:cards"), None);
+        assert_eq!(key_name("work", "cards"), None);
+    }
+
+    #[test]
+    fn profiles_only_see_their_own_keys() {
+        let keys = [
+            profile_key(DEFAULT_PROFILE, "cards"),
+            profile_key("work", "cards"),
+            profile_key("work", "theme"),
+            profile_key("work:x", "cards"),
+            profile_key("work%3Ax", "cards"),
+        ];
+        let names = |profile| -> Vec<String> {
+            keys.iter()
+                .filter_map(|key| key_name(profile, key))
+                .collect()
+        };
+        assert_eq!(names(DEFAULT_PROFILE), ["cards"]);
+        assert_eq!(names("work"), ["cards", "theme"]);
+        assert_eq!(names("work:x"), ["cards"]);
+        assert_eq!(names("work%3Ax"), ["cards"]);
+        assert_ne!(keys[3], keys[4]);
+    }
+}
@@ -0,0 +1,263 @@
+//! Client of the sync server in `server/`, keeping the cards of every device the same.
+//!
+//! The server stores one document per user with a revision bumped on every write. The
+//! document holds the edits of every device, see [`Doc`], so syncing merges it with the
+//! local one and both sides end up with the edits of both.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    components::cards::{
+        cards_ctx::{Cards, Page},
+        doc::Doc,
+    },
+    http::{Request, Response, Transport},
+    storage,
+};
+
+/// Where to sync, stored under `"sync"`.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncConfig {
+    /// Base URL of the server, without `/api/document`.
+    pub server: String,
+    pub token: String,
+}
+
+impl SyncConfig {
+    pub fn load() -> Option<Self> {
+        storage::get("sync").ok()
+    }
+
+    fn request(&self, method: &'static str, body: Option<String>) -> Request {
+        let mut headers = vec![("Authorization", format!("Bearer {}", self.token))];
+        if body.is_some() {
+            headers.push(("Content-Type", "application/json".to_owned()));
+        }
+        Request {
+            method,
+            url: format!("{}/api/document", self.server.trim_end_matches('/')),
+            headers,
+            body,
+        }
+    }
+}
+
+/// The document on the server.
+#[derive(Deserialize)]
+struct Remote {
+    revision: u64,
+    document: Option<Cards>,
+}
+
+/// What is stored on the server, the pages are there for other clients.
+#[derive(Serialize)]
+struct Document<'a> {
+    pages: Vec<Page>,
+    doc: &'a Doc,
+}
+
+#[derive(Serialize)]
+struct Update<'a> {
+    base_revision: u64,
+    document: Document<'a>,
+}
+
+/// How many times to push, another device may push between a pull and a push.
+const MAX_TRIES: usize = 3;
+
+fn check(response: Response) -> Result<Response, String> {
+    match response.status {
+        200 | 409 => Ok(response),
+        401 => Err("the server doesn't know this token".to_owned()),
+        status => Err(format!("the server answered {status} {}", response.body)),
+    }
+}
+
+fn parse<T: DeserializeOwned>(response: &Response) -> Result<T, String> {
+    serde_json::from_str(&response.body).map_err(|err| err.to_string())
+}
+
+async fn pull(http: &impl Transport, config: &SyncConfig) -> Result<Remote, String> {
+    let response = check(http.send(config.request("GET", None)).await?)?;
+    parse(&response)
+}
+
+/// Sends the document as the next revision after `base`, answers the server's document
+/// if `base` isn't its revision anymore.
+async fn push(
+    http: &impl Transport,
+    config: &SyncConfig,
+    base: u64,
+    doc: &Doc,
+) -> Result<Option<Remote>, String> {
+    let update = Update {
+        base_revision: base,
+        document: Document {
+            pages: doc.pages(),
+            doc,
+        },
+    };
+    let body = serde_json::to_string(&update).map_err(|err| err.to_string())?;
+    let response = check(http.send(config.request("PUT", Some(body))).await?)?;
+    if response.status == 409 {
+        return parse(&response).map(Some);
+    }
+    Ok(None)
+}
+
+/// Merges the document with the server's one, pushing the result unless the server has
+/// all of its edits already. Answers the merged document.
+pub async fn sync(http: &impl Transport, config: &SyncConfig, doc: &Doc) -> Result<Doc, String> {
+    let mut remote = pull(http, config).await?;
+    for _ in 0..MAX_TRIES {
+        let mut merged = doc.clone();
+        if let Some(theirs) = &remote.document {
+            merged.merge(&theirs.doc);
+            if merged == theirs.doc {
+                return Ok(merged);
+            }
+        }
+        match push(http, config, remote.revision, &merged).await? {
+            None => return Ok(merged),
+            Some(newer) => remote = newer,
+        }
+    }
+    Err("the server changed too often, try again later".to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::Cell,
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread,
+    };
+
+    use foxhole_server::{Config, Server};
+
+    use super::*;
+    use crate::{
+        components::cards::cards_ctx::Card,
+        http::testing::{block_on, Ureq},
+    };
+
+    const TOKEN: &str = "alice-token-0123456789";
+
+    /// The sync server on a free localhost port, with a fresh data directory.
+    fn server() -> (Arc<Server>, SyncConfig) {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let data_dir = std::env::temp_dir().join(format!(
+            "foxhole-sync-{}-{}",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::SeqCst)
+        ));
+        let config = Config {
+            users: HashMap::from([("alice".to_owned(), TOKEN.to_owned())]),
+            data_dir,
+            allow_origin: "https://foxhole.example".to_owned(),
+        };
+        config.validate().unwrap();
+        let server = Arc::new(Server::bind("127.0.0.1:0", config).unwrap());
+        thread::spawn({
+            let server = server.clone();
+            move || server.run()
+        });
+        let config = SyncConfig {
+            server: format!("http://{}/", server.addr()),
+            token: TOKEN.to_owned(),
+        };
+        (server, config)
+    }
+
+    /// The document of a device that added `cards` to the default page.
+    fn device(cards: &[&str], replica: u64) -> Doc {
+        let mut pages = vec![Page::new(Cards::DEFAULT_PAGE.to_owned())];
+        let mut doc = Doc::new(&pages);
+        let cards = cards.iter().map(|&name| Card::new(name.to_owned()));
+        pages[0].cards.extend(cards);
+        doc.update(&pages, replica);
+        doc
+    }
+
+    /// Sorted, cards added at the same place by different devices are ordered by replica.
+    fn card_names(doc: &Doc) -> Vec<String> {
+        let cards = &doc.pages()[0].cards;
+        let mut names: Vec<String> = cards.iter().map(|card| card.name.clone()).collect();
+        names.sort();
+        names
+    }
+
+    fn revision(config: &SyncConfig) -> u64 {
+        block_on(pull(&Ureq, config)).unwrap().revision
+    }
+
+    #[test]
+    fn devices_get_the_edits_of_each_other() {
+        let (server, config) = server();
+        let laptop = block_on(sync(&Ureq, &config, &device(&["News"], 1))).unwrap();
+        assert_eq!(revision(&config), 1);
+
+        let phone = block_on(sync(&Ureq, &config, &device(&["Music"], 2))).unwrap();
+        assert_eq!(card_names(&phone), ["Music", "News"]);
+        assert_eq!(revision(&config), 2);
+
+        // the server has every edit of the laptop, nothing is pushed
+        let laptop = block_on(sync(&Ureq, &config, &laptop)).unwrap();
+        assert!(laptop == phone);
+        assert_eq!(revision(&config), 2);
+        server.stop();
+    }
+
+    /// Lets another device push right before the first push.
+    struct Racing<'a> {
+        other: &'a Doc,
+        config: &'a SyncConfig,
+        raced: Cell<bool>,
+    }
+
+    impl Transport for Racing<'_> {
+        async fn send(&self, request: Request) -> Result<Response, String> {
+            if request.method == "PUT" && !self.raced.replace(true) {
+                block_on(sync(&Ureq, self.config, self.other)).unwrap();
+            }
+            Ureq.send(request).await
+        }
+    }
+
+    #[test]
+    fn conflicts_are_merged_and_pushed_again() {
+        let (server, config) = server();
+        let phone = device(&["Music"], 2);
+        let racing = Racing {
+            other: &phone,
+            config: &config,
+            raced: Cell::new(false),
+        };
+        let laptop = block_on(sync(&racing, &config, &device(&["News"], 1))).unwrap();
+        assert_eq!(card_names(&laptop), ["Music", "News"]);
+        assert_eq!(revision(&config), 2);
+
+        let stored = block_on(pull(&Ureq, &config)).unwrap().document.unwrap();
+        assert!(stored.doc == laptop);
+        assert!(stored.pages == laptop.pages());
+        server.stop();
+    }
+
+    #[test]
+    fn unknown_tokens_are_reported() {
+        let (server, config) = server();
+        let config = SyncConfig {
+            token: "wrong".to_owned(),
+            ..config
+        };
+        let err = block_on(sync(&Ureq, &config, &device(&[], 1)))
+            .err()
+            .unwrap();
+        assert_eq!(err, "the server doesn't know this token");
+        server.stop();
+    }
+}
@@ -0,0 +1,355 @@
+//! A minimal WebDAV client for backups: uploading, listing and downloading files of one
+//! folder (a collection) on servers like Nextcloud.
+//!
+//! Requests go through a [`Transport`], [`Fetch`](crate::http::Fetch) in the browser.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+use quick_xml::{events::Event, Reader};
+
+use crate::http::{Request, Response, Transport};
+
+/// Characters escaped in file names, the ones `url` escapes in a path segment.
+const SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// A file of the folder.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Entry {
+    pub name: String,
+    /// As sent by the server, an HTTP date.
+    pub modified: Option<String>,
+    pub size: Option<u64>,
+}
+
+/// A folder on a WebDAV server.
+#[derive(Clone, PartialEq)]
+pub struct Folder {
+    /// Always ends with `/`.
+    url: String,
+    authorization: Option<String>,
+}
+
+impl Folder {
+    /// Basic authentication is used when `user` isn't empty.
+    pub fn new(url: &str, user: &str, password: &str) -> Self {
+        let url = format!("{}/", url.trim_end_matches('/'));
+        let authorization = (!user.is_empty())
+            .then(|| format!("Basic {}", STANDARD.encode(format!("{user}:{password}"))));
+        Self { url, authorization }
+    }
+
+    fn request(&self, method: &'static str, name: &str) -> Request {
+        Request {
+            method,
+            url: format!("{}{}", self.url, utf8_percent_encode(name, SEGMENT)),
+            headers: self
+                .authorization
+                .iter()
+                .map(|auth| ("Authorization", auth.clone()))
+                .collect(),
+            body: None,
+        }
+    }
+
+    /// Writes the file `name`, creating the folder if needed.
+    pub async fn put(
+        &self,
+        http: &impl Transport,
+        name: &str,
+        contents: &str,
+    ) -> Result<(), String> {
+        let put = || {
+            let mut request = self.request("PUT", name);
+            request
+                .headers
+                .push(("Content-Type", "application/json".to_owned()));
+            request.body = Some(contents.to_owned());
+            request
+        };
+        let mut response = http.send(put()).await?;
+        // the folder doesn't exist
+        if response.status == 409 {
+            check(http.send(self.request("MKCOL", "")).await?)?;
+            response = http.send(put()).await?;
+        }
+        check(response).map(drop)
+    }
+
+    /// The files of the folder, not its subfolders. Their names are decoded.
+    pub async fn list(&self, http: &impl Transport) -> Result<Vec<Entry>, String> {
+        let mut request = self.request("PROPFIND", "");
+        request.headers.push(("Depth", "1".to_owned()));
+        request
+            .headers
+            .push(("Content-Type", "application/xml".to_owned()));
+        request.body = Some(PROPFIND.to_owned());
+        let response = http.send(request).await?;
+        if response.status == 404 {
+            return Ok(Vec::new());
+        }
+        parse_multistatus(&check(response)?.body)
+    }
+
+    pub async fn get(&self, http: &impl Transport, name: &str) -> Result<String, String> {
+        let response = check(http.send(self.request("GET", name)).await?)?;
+        Ok(response.body)
+    }
+}
+
+const PROPFIND: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:">
+  <d:prop><d:resourcetype/><d:getlastmodified/><d:getcontentlength/></d:prop>
+</d:propfind>"#;
+
+fn check(response: Response) -> Result<Response, String> {
+    match response.status {
+        200..=299 => Ok(response),
+        401 | 403 => Err("the server refused the user name or password".to_owned()),
+        404 => Err("the folder doesn't exist".to_owned()),
+        status => Err(format!("the server answered {status}")),
+    }
+}
+
+/// The files listed in the answer to a `PROPFIND`, whatever the namespace prefix.
+fn parse_multistatus(xml: &str) -> Result<Vec<Entry>, String> {
+    let mut reader = Reader::from_str(xml);
+    let mut entries = Vec::new();
+    let mut entry: Option<(Entry, bool)> = None;
+    // the element whose text is being read
+    let mut current = Vec::new();
+    loop {
+        let event = reader.read_event().map_err(|err| err.to_string())?;
+        match event {
+            Event::Start(start) => {
+                let name = start.local_name().as_ref().to_vec();
+                if name == b"response" {
+                    entry = Some((
+                        Entry {
+                            name: String::new(),
+                            modified: None,
+                            size: None,
+                        },
+                        false,
+                    ));
+                }
+                current = name;
+            }
+            Event::Empty(empty) => {
+                if let (b"collection", Some((_, folder))) =
+                    (empty.local_name().as_ref(), entry.as_mut())
+                {
+                    *folder = true;
+                }
+            }
+            Event::Text(text) => {
+                let Some((entry, _)) = entry.as_mut() else {
+                    continue;
+                };
+                let text = text.unescape().map_err(|err| err.to_string())?;
+                let text = text.trim();
+                match current.as_slice() {
+                    b"href" => {
+                        let path = text.trim_end_matches('/');
+                        let name = path.rsplit('/').next().unwrap_or_default();
+                        entry.name = percent_decode_str(name).decode_utf8_lossy().into_owned();
+                    }
+                    b"getlastmodified" => entry.modified = Some(text.to_owned()),
+                    b"getcontentlength" => entry.size = text.parse().ok(),
+                    _ => (),
+                }
+            }
+            Event::End(end) => {
+                if end.local_name().as_ref() == b"response" {
+                    if let Some((entry, false)) = entry.take() {
+                        entries.push(entry);
+                    }
+                }
+                current.clear();
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeMap,
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    use super::*;
+    use crate::http::testing::{block_on, Ureq};
+
+    /// Files of the stand-in server, by path.
+    type Files = Arc<Mutex<BTreeMap<String, String>>>;
+
+    /// A WebDAV server on localhost, with one user and folders at any path. Returns its
+    /// address and its files, folders being the paths ending with `/`.
+    fn stand_in() -> (String, Files) {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let addr = format!("http://{}", server.server_addr().to_ip().unwrap());
+        let files = Files::default();
+        let shared = files.clone();
+        thread::spawn(move || {
+            for mut request in server.incoming_requests() {
+                let auth = request
+                    .headers()
+                    .iter()
+                    .find(|h| h.field.equiv("Authorization"))
+                    .map(|h| h.value.to_string());
+                let expected = format!("Basic {}", STANDARD.encode("alice:secret"));
+                if auth.as_deref() != Some(expected.as_str()) {
+                    request.respond(tiny_http::Response::empty(401)).unwrap();
+                    continue;
+                }
+                let path = request.url().to_owned();
+                let parent = format!("{}/", path.rsplit_once('/').unwrap().0);
+                let mut body = String::new();
+                request.as_reader().read_to_string(&mut body).unwrap();
+                let mut files = shared.lock().unwrap();
+                let (status, answer) = match request.method().as_str() {
+                    "MKCOL" => {
+                        files.insert(path, String::new());
+                        (201, String::new())
+                    }
+                    "PUT" if !files.contains_key(&parent) => (409, String::new()),
+                    "PUT" => {
+                        files.insert(path, body);
+                        (201, String::new())
+                    }
+                    "GET" => match files.get(&path) {
+                        Some(contents) => (200, contents.clone()),
+                        None => (404, String::new()),
+                    },
+                    "PROPFIND" if files.contains_key(&path) => {
+                        let responses: String = files
+                            .iter()
+                            .filter(|(file, _)| {
+                                file.strip_prefix(&path)
+                                    .is_some_and(|name| !name.trim_end_matches('/').contains('/'))
+                            })
+                            .map(|(file, contents)| {
+                                let kind = if file.ends_with('/') {
+                                    "<D:collection/>"
+                                } else {
+                                    ""
+                                };
+                                let href = quick_xml::escape::escape(file.as_str());
+                                format!(
+                                    "<D:response><D:href>{href}</D:href><D:propstat><D:prop>\
+                                     <D:resourcetype>{kind}</D:resourcetype>\
+                                     <D:getlastmodified>Mon, 19 Oct 2026 10:00:00 GMT\
+                                     </D:getlastmodified>\
+                                     <D:getcontentlength>{}</D:getcontentlength>\
+                                     </D:prop></D:propstat></D:response>",
+                                    contents.len()
+                                )
+                            })
+                            .collect();
+                        let xml = format!(
+                            "<?xml version=\"1.0\"?>\
+                             <D:multistatus xmlns:D=\"DAV:\">{responses}</D:multistatus>"
+                        );
+                        (207, xml)
+                    }
+                    "PROPFIND" => (404, String::new()),
+                    _ => (405, String::new()),
+                };
+                request
+                    .respond(tiny_http::Response::from_string(answer).with_status_code(status))
+                    .unwrap();
+            }
+        });
+        (addr, files)
+    }
+
+    #[test]
+    fn backs_up_lists_and_restores() {
+        let (addr, files) = stand_in();
+        let folder = Folder::new(&format!("{addr}/dav/foxhole"), "alice", "secret");
+
+        // the folder is created on the first upload
+        assert_eq!(block_on(folder.list(&Ureq)), Ok(Vec::new()));
+        block_on(folder.put(&Ureq, "first.json", "{\"pages\":[]}")).unwrap();
+        block_on(folder.put(&Ureq, "second.json", "{}")).unwrap();
+        assert!(files.lock().unwrap().contains_key("/dav/foxhole/"));
+
+        let names: Vec<String> = block_on(folder.list(&Ureq))
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.name)
+            .collect();
+        assert_eq!(names, ["first.json", "second.json"]);
+
+        let contents = block_on(folder.get(&Ureq, "first.json")).unwrap();
+        assert_eq!(contents, "{\"pages\":[]}");
+    }
+
+    #[test]
+    fn file_names_are_escaped() {
+        let (addr, files) = stand_in();
+        let folder = Folder::new(&format!("{addr}/dav/"), "alice", "secret");
+        let name = "foxhole-Work & play #2 é-2026-10-19T08-30-00.json";
+        block_on(folder.put(&Ureq, name, "{}")).unwrap();
+        assert!(files
+            .lock()
+            .unwrap()
+            .contains_key("/dav/foxhole-Work%20&%20play%20%232%20%C3%A9-2026-10-19T08-30-00.json"));
+
+        let listed = block_on(folder.list(&Ureq)).unwrap();
+        assert_eq!(listed[0].name, name);
+        assert_eq!(block_on(folder.get(&Ureq, name)).unwrap(), "{}");
+    }
+
+    #[test]
+    fn reports_wrong_credentials() {
+        let (addr, _) = stand_in();
+        let folder = Folder::new(&format!("{addr}/dav/"), "alice", "wrong");
+        let err = block_on(folder.put(&Ureq, "backup.json", "{}")).unwrap_err();
+        assert!(err.contains("password"), "{err}");
+    }
+
+    #[test]
+    fn parses_nextcloud_listings() {
+        let xml = r#"<?xml version="1.0"?>
+            <d:multistatus xmlns:d="DAV:" xmlns:oc="http://owncloud.org/ns">
+              <d:response>
+                <d:href>/remote.php/dav/files/alice/Foxhole/</d:href>
+                <d:propstat><d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop>
+                <d:status>HTTP/1.1 200 OK</d:status></d:propstat>
+              </d:response>
+              <d:response>
+                <d:href>/remote.php/dav/files/alice/Foxhole/foxhole-2026-10-19.json</d:href>
+                <d:propstat><d:prop>
+                  <d:resourcetype/>
+                  <d:getlastmodified>Mon, 19 Oct 2026 10:00:00 GMT</d:getlastmodified>
+                  <d:getcontentlength>1234</d:getcontentlength>
+                </d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat>
+              </d:response>
+            </d:multistatus>"#;
+        let entries = parse_multistatus(xml).unwrap();
+        assert_eq!(
+            entries,
+            [Entry {
+                name: "foxhole-2026-10-19.json".to_owned(),
+                modified: Some("Mon, 19 Oct 2026 10:00:00 GMT".to_owned()),
+                size: Some(1234),
+            }]
+        );
+    }
+}